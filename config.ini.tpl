@@ -1,5 +1,6 @@
 [debug]
 enable_bug_report = true
+record_zone_events = false
 
 [server]
 name = Rise of Clans
@@ -8,6 +9,44 @@ server_port = 5000
 unsecure = true
 releases_url = http://rolling.bux.fr/release
 
+; Additional servers can be declared with [server.<id>] sections (same keys as [server]).
+; A server picker is displayed at startup when more than one server is declared.
+; home_image and home_image_background can override [design] ones.
+;[server.s2]
+;name = S2
+;server_hostname = s2.bux.fr
+;server_port = 7431
+;unsecure = false
+;releases_url = http://rolling.bux.fr/release
+
+[network]
+connect_timeout_ms = 5000
+; Max duration of a whole request (response included), and of a media download
+request_timeout_ms = 30000
+download_timeout_ms = 300000
+; Failed GET requests (connection error, timeout, 502, 503, 504) are retried
+max_retries = 3
+retry_delay_ms = 250
+; Proxy used for http requests and zone websocket: http://[user:password@]host:port, or
+; socks5:// / socks5h:// (name resolved by proxy) if client is built with socks feature
+;proxy = http://127.0.0.1:3128
+; PEM file of certificates to trust in addition to system ones (e.g. self signed server)
+;ca_bundle = ca.pem
+; SHA-256 fingerprint of server certificate, connection is refused if it doesn't match
+; (openssl x509 -in server.pem -noout -fingerprint -sha256)
+;certificate_pin = AB:CD:...
+
+[cache]
+; Downloaded medias (illustrations, avatars, ...) are removed when cache exceed this size
+media_max_size_mb = 200
+
+[movement]
+; Player speed in tiles per second, multiplied by run_factor when shift key is pressed
+player_speed = 2.0
+run_factor = 3.0
+; Duration of other characters moves from a tile to another
+entity_move_duration_ms = 300
+
 [design]
 title = RoC
 home_image = resources/intro.png
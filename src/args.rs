@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
@@ -5,4 +6,23 @@ use structopt::StructOpt;
 pub struct Opt {
     #[structopt(name = "config_file_path", default_value = "config.ini")]
     pub config_file_path: String,
+    /// Record zone snapshot and zone events in recordings folder (see also
+    /// record_zone_events in debug section of config)
+    #[structopt(long)]
+    pub record_zone_events: bool,
+    /// Replay given recording folder instead of connecting to server
+    #[structopt(long, parse(from_os_str))]
+    pub replay: Option<PathBuf>,
+    /// Replay speed factor (2.0 replay events twice faster than recorded)
+    #[structopt(long, default_value = "1.0")]
+    pub replay_speed: f32,
+    /// Print media cache content and size, then exit
+    #[structopt(long)]
+    pub cache_info: bool,
+    /// Check integrity of cached medias (corrupted ones are removed), then exit
+    #[structopt(long)]
+    pub cache_verify: bool,
+    /// Remove all cached medias, then exit
+    #[structopt(long)]
+    pub cache_purge: bool,
 }
@@ -0,0 +1,166 @@
+pub const ZOOM_LEVELS: [f32; 5] = [0.5, 0.75, 1.0, 1.5, 2.0];
+const DEFAULT_ZOOM_LEVEL: usize = 2;
+// Followed target is kept at this distance (in tiles) from view borders, if view is large enough
+const FOLLOW_MARGIN_TILES: f32 = 15.0;
+
+/// Part of zone displayed in zone view, and its zoom. Zone positions are unscaled pixels
+/// (`col_i * tile_width`, `row_i * tile_height`), view positions are scaled pixels from
+/// view top left corner.
+#[derive(Debug, Clone)]
+pub struct Camera {
+    // Zone position displayed at view top left corner
+    x: f32,
+    y: f32,
+    zoom_level: usize,
+    tile_width: f32,
+    tile_height: f32,
+    // When free, camera don't follow player anymore
+    free: bool,
+}
+
+impl Camera {
+    pub fn new(tile_width: i16, tile_height: i16) -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            zoom_level: DEFAULT_ZOOM_LEVEL,
+            tile_width: tile_width as f32,
+            tile_height: tile_height as f32,
+            free: false,
+        }
+    }
+
+    pub fn zoom(&self) -> f32 {
+        ZOOM_LEVELS[self.zoom_level]
+    }
+
+    pub fn is_free(&self) -> bool {
+        self.free
+    }
+
+    pub fn set_free(&mut self, free: bool) {
+        self.free = free;
+    }
+
+    pub fn to_view(&self, x: f32, y: f32) -> (f32, f32) {
+        ((x - self.x) * self.zoom(), (y - self.y) * self.zoom())
+    }
+
+    pub fn to_zone(&self, view_x: f32, view_y: f32) -> (f32, f32) {
+        (view_x / self.zoom() + self.x, view_y / self.zoom() + self.y)
+    }
+
+    /// Zone tile (row_i, col_i) displayed at given view position
+    pub fn tile_at(&self, view_x: f32, view_y: f32) -> (i16, i16) {
+        let (x, y) = self.to_zone(view_x, view_y);
+        (
+            (y / self.tile_height).floor() as i16,
+            (x / self.tile_width).floor() as i16,
+        )
+    }
+
+    /// Change zoom level by given steps, zone position under anchor (view position) stay at
+    /// same place
+    pub fn zoom_by(&mut self, steps: i32, anchor: (f32, f32)) {
+        let (anchor_x, anchor_y) = self.to_zone(anchor.0, anchor.1);
+        self.zoom_level =
+            (self.zoom_level as i32 + steps).clamp(0, ZOOM_LEVELS.len() as i32 - 1) as usize;
+        self.x = anchor_x - anchor.0 / self.zoom();
+        self.y = anchor_y - anchor.1 / self.zoom();
+    }
+
+    /// Move camera by given view pixels
+    pub fn pan_by(&mut self, view_dx: f32, view_dy: f32) {
+        self.x += view_dx / self.zoom();
+        self.y += view_dy / self.zoom();
+    }
+
+    /// Move camera (if not free) to keep given zone position of a tile away from view borders
+    pub fn follow(&mut self, x: f32, y: f32, view_width: f32, view_height: f32) {
+        if self.free {
+            return;
+        }
+
+        let zoom = self.zoom();
+        let (view_x, view_y) = self.to_view(x, y);
+        let (dx, dy) = (
+            follow_delta(view_x, self.tile_width * zoom, view_width),
+            follow_delta(view_y, self.tile_height * zoom, view_height),
+        );
+        self.pan_by(dx, dy);
+    }
+
+    /// Place given zone position of a tile at view center
+    pub fn center_on(&mut self, x: f32, y: f32, view_width: f32, view_height: f32) {
+        let zoom = self.zoom();
+        self.x = x + self.tile_width / 2.0 - view_width / 2.0 / zoom;
+        self.y = y + self.tile_height / 2.0 - view_height / 2.0 / zoom;
+    }
+}
+
+// View pixels to move to keep a tile of given size between margins
+fn follow_delta(position: f32, tile_size: f32, view_size: f32) -> f32 {
+    let margin = (FOLLOW_MARGIN_TILES * tile_size)
+        .min((view_size - tile_size) / 2.0)
+        .max(0.0);
+    if position < margin {
+        position - margin
+    } else if position + tile_size > view_size - margin {
+        position + tile_size - (view_size - margin)
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tile_at() {
+        let mut camera = Camera::new(32, 24);
+        assert_eq!((1, 2), camera.tile_at(70.0, 30.0));
+
+        camera.zoom_by(2, (0.0, 0.0));
+        assert_eq!(2.0, camera.zoom());
+        assert_eq!((0, 1), camera.tile_at(70.0, 30.0));
+        assert_eq!((64.0, 48.0), camera.to_view(32.0, 24.0));
+
+        camera.pan_by(-64.0, 0.0);
+        assert_eq!((0, 0), camera.tile_at(70.0, 30.0));
+        assert_eq!((0, -1), camera.tile_at(60.0, 30.0));
+    }
+
+    #[test]
+    fn test_zoom_keep_anchor() {
+        let mut camera = Camera::new(32, 32);
+        camera.pan_by(100.0, 50.0);
+        let before = camera.to_zone(400.0, 300.0);
+
+        camera.zoom_by(-1, (400.0, 300.0));
+        assert_eq!(0.75, camera.zoom());
+        assert_eq!(before, camera.to_zone(400.0, 300.0));
+
+        camera.zoom_by(-10, (400.0, 300.0));
+        assert_eq!(ZOOM_LEVELS[0], camera.zoom());
+    }
+
+    #[test]
+    fn test_follow() {
+        let mut camera = Camera::new(32, 32);
+
+        // View can contain 25 tiles: target is kept near center
+        camera.follow(3200.0, 0.0, 800.0, 800.0);
+        let (view_x, view_y) = camera.to_view(3200.0, 0.0);
+        assert!((view_x - 384.0).abs() < 0.01);
+        assert!((view_y - 384.0).abs() < 0.01);
+
+        // Free camera don't follow
+        camera.set_free(true);
+        camera.follow(0.0, 0.0, 800.0, 800.0);
+        assert!(camera.to_view(0.0, 0.0).0 < 0.0);
+
+        camera.center_on(0.0, 0.0, 800.0, 800.0);
+        assert_eq!((384.0, 384.0), camera.to_view(0.0, 0.0));
+    }
+}
@@ -0,0 +1,304 @@
+use crate::event::CharacterActionLink;
+use crate::state::ZoneState;
+use crate::tooltip;
+use coffee::graphics::Point;
+
+pub const ITEM_HEIGHT: f32 = 22.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContextAction {
+    /// Open description page
+    Describe(String),
+    /// Send link like quick action buttons do
+    QuickAction(String),
+    MoveTo((i16, i16)),
+    StopMove,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextMenuItem {
+    pub label: String,
+    /// None for entity title items
+    pub action: Option<ContextAction>,
+}
+
+impl ContextMenuItem {
+    fn title(label: String) -> Self {
+        Self {
+            label,
+            action: None,
+        }
+    }
+
+    fn action(label: &str, action: ContextAction) -> Self {
+        Self {
+            label: format!("  {}", label),
+            action: Some(action),
+        }
+    }
+}
+
+/// Menu opened by right click on a zone tile, listing tile entities and their actions
+#[derive(Debug, Clone)]
+pub struct ContextMenu {
+    pub tile: (i16, i16),
+    /// Screen position of top left corner
+    pub position: Point,
+    pub items: Vec<ContextMenuItem>,
+    /// Known after menu is drawn (depend on labels size)
+    pub width: f32,
+}
+
+impl ContextMenu {
+    pub fn new(tile: (i16, i16), position: Point, items: Vec<ContextMenuItem>) -> Self {
+        Self {
+            tile,
+            position,
+            items,
+            width: 0.0,
+        }
+    }
+
+    pub fn height(&self) -> f32 {
+        self.items.len() as f32 * ITEM_HEIGHT
+    }
+
+    /// Index of item at given screen position
+    pub fn item_at(&self, point: Point) -> Option<usize> {
+        if point.x < self.position.x
+            || point.x > self.position.x + self.width
+            || point.y < self.position.y
+            || point.y >= self.position.y + self.height()
+        {
+            return None;
+        }
+        Some(((point.y - self.position.y) / ITEM_HEIGHT) as usize)
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.item_at(point).is_some()
+    }
+
+    /// Action of item at given screen position
+    pub fn action_at(&self, point: Point) -> Option<ContextAction> {
+        self.item_at(point)
+            .and_then(|index| self.items[index].action.clone())
+    }
+}
+
+// Around quick actions targeting given classes
+fn quick_action_items(
+    quick_actions: &[CharacterActionLink],
+    classes: &[String],
+) -> Vec<ContextMenuItem> {
+    quick_actions
+        .iter()
+        .filter(|quick_action| {
+            quick_action
+                .classes2
+                .iter()
+                .any(|class| classes.contains(class))
+        })
+        .map(|quick_action| {
+            ContextMenuItem::action(
+                &quick_action.name,
+                ContextAction::QuickAction(quick_action.link.clone()),
+            )
+        })
+        .collect()
+}
+
+/// Right click on player tile or on empty ground while moving stops the move instead of
+/// opening menu. On other tiles menu opens and its "Arrêter le déplacement" item stops the move.
+pub fn right_click_stops_move(
+    state: &ZoneState,
+    player_tile: (i16, i16),
+    tile: (i16, i16),
+    moving: bool,
+) -> bool {
+    moving && (tile == player_tile || state.entities_at(tile.0, tile.1).is_empty())
+}
+
+/// Menu items of every entity of tile then tile itself. Quick actions (received for player
+/// surroundings) are proposed for entities next to player only.
+pub fn context_menu_items(
+    state: &ZoneState,
+    player_id: &str,
+    player_tile: (i16, i16),
+    tile: (i16, i16),
+    quick_actions: &[CharacterActionLink],
+    moving: bool,
+) -> Vec<ContextMenuItem> {
+    let entities = state.entities_at(tile.0, tile.1);
+    let around = (tile.0 - player_tile.0).abs() <= 1 && (tile.1 - player_tile.1).abs() <= 1;
+    let quick_actions = if around { quick_actions } else { &[] };
+    let mut items = vec![];
+
+    for character in entities.characters.iter() {
+        items.push(ContextMenuItem::title(tooltip::character_label(
+            character, player_id,
+        )));
+        let url = if character.id == player_id {
+            format!("/_describe/character/{}/card", player_id)
+        } else {
+            format!(
+                "/_describe/character/{}/look-character/{}",
+                player_id, character.id
+            )
+        };
+        items.push(ContextMenuItem::action(
+            "Regarder",
+            ContextAction::Describe(url),
+        ));
+    }
+    for animated_corpse in entities.animated_corpses.iter() {
+        items.push(ContextMenuItem::title(tooltip::animated_corpse_label(
+            animated_corpse,
+        )));
+        items.extend(quick_action_items(
+            quick_actions,
+            std::slice::from_ref(&animated_corpse.type_),
+        ));
+    }
+    for build in entities.builds.iter() {
+        items.push(ContextMenuItem::title(tooltip::build_label(build)));
+        items.push(ContextMenuItem::action(
+            "Ouvrir",
+            ContextAction::Describe(format!("/character/{}/build/{}", player_id, build.id)),
+        ));
+        let mut classes = build.classes.clone();
+        classes.push(build.build_id.clone());
+        items.extend(quick_action_items(quick_actions, &classes));
+    }
+    for resource in entities.resources.iter() {
+        items.push(ContextMenuItem::title(tooltip::resource_label(resource)));
+        items.extend(quick_action_items(
+            quick_actions,
+            std::slice::from_ref(&resource.id),
+        ));
+    }
+    for stuff in entities.stuffs.iter() {
+        items.push(ContextMenuItem::title(tooltip::stuff_label(stuff)));
+        if around {
+            items.push(ContextMenuItem::action(
+                "Ramasser",
+                ContextAction::Describe(format!(
+                    "/character/{}/action/TRANSFER_GROUND/TRANSFER_GROUND",
+                    player_id
+                )),
+            ));
+        }
+        let mut classes = stuff.classes.clone();
+        classes.push(stuff.stuff_id.clone());
+        items.extend(quick_action_items(quick_actions, &classes));
+    }
+
+    items.push(ContextMenuItem::title(tooltip::tile_label(state, tile)));
+    if tile != player_tile {
+        items.push(ContextMenuItem::action(
+            "Aller ici",
+            ContextAction::MoveTo(tile),
+        ));
+    }
+    if moving {
+        items.push(ContextMenuItem::action(
+            "Arrêter le déplacement",
+            ContextAction::StopMove,
+        ));
+    }
+
+    items
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::state::test_state_with_entities;
+
+    fn quick_actions() -> Vec<CharacterActionLink> {
+        vec![CharacterActionLink {
+            name: "Lancer".to_string(),
+            link: "/throw/7".to_string(),
+            classes1: vec!["THROW".to_string()],
+            classes2: vec!["STONE".to_string()],
+        }]
+    }
+
+    fn actions(items: &[ContextMenuItem]) -> Vec<ContextAction> {
+        items
+            .iter()
+            .filter_map(|item| item.action.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_context_menu_items() {
+        let state = test_state_with_entities();
+        let quick_actions = quick_actions();
+
+        let items = context_menu_items(&state, "player", (0, 0), (0, 1), &quick_actions, true);
+        assert_eq!("Personnage : bob", items[0].label);
+        assert_eq!(
+            vec![
+                ContextAction::Describe(
+                    "/_describe/character/player/look-character/bob".to_string()
+                ),
+                ContextAction::Describe(
+                    "/character/player/action/TRANSFER_GROUND/TRANSFER_GROUND".to_string()
+                ),
+                ContextAction::QuickAction("/throw/7".to_string()),
+                ContextAction::MoveTo((0, 1)),
+                ContextAction::StopMove,
+            ],
+            actions(&items)
+        );
+
+        // Too far for ground actions
+        let items = context_menu_items(&state, "player", (0, 3), (0, 1), &quick_actions, false);
+        assert_eq!(
+            vec![
+                ContextAction::Describe(
+                    "/_describe/character/player/look-character/bob".to_string()
+                ),
+                ContextAction::MoveTo((0, 1)),
+            ],
+            actions(&items)
+        );
+    }
+
+    #[test]
+    fn test_right_click_stops_move() {
+        let state = test_state_with_entities();
+
+        assert!(right_click_stops_move(&state, (0, 1), (0, 1), true));
+        assert!(right_click_stops_move(&state, (0, 0), (0, 3), true));
+        assert!(!right_click_stops_move(&state, (0, 0), (0, 1), true));
+        let items = context_menu_items(&state, "player", (0, 0), (0, 1), &[], true);
+        assert_eq!(Some(ContextAction::StopMove), items.last().unwrap().action);
+        assert!(!right_click_stops_move(&state, (0, 0), (0, 3), false));
+    }
+
+    #[test]
+    fn test_action_at() {
+        let mut menu = ContextMenu::new(
+            (0, 1),
+            Point::new(100.0, 100.0),
+            context_menu_items(
+                &test_state_with_entities(),
+                "player",
+                (0, 3),
+                (0, 1),
+                &[],
+                false,
+            ),
+        );
+        menu.width = 200.0;
+
+        assert_eq!(None, menu.action_at(Point::new(150.0, 110.0)));
+        assert_eq!(
+            Some(ContextAction::MoveTo((0, 1))),
+            menu.action_at(Point::new(150.0, 100.0 + 4.5 * ITEM_HEIGHT))
+        );
+        assert!(!menu.contains(Point::new(350.0, 110.0)));
+    }
+}
@@ -4,6 +4,7 @@ use crate::gui::lang::model::{Description, Part};
 use crate::input::MyGameInput;
 use crate::message::{MainMessage, Message};
 use crate::server::client;
+use crate::server::request::PendingRequest;
 use crate::sheet::TileSheet;
 use crate::ui::widget::checkbox::Checkbox;
 use crate::ui::widget::link::Link;
@@ -79,8 +80,7 @@ pub struct DescriptionEngine {
     search_by_str_button_values: HashMap<i32, String>,
     search_by_str_selected: i32,
     search_by_str_button_pressed: i32,
-    pending_request: Option<(String, Map<String, Value>, Map<String, Value>)>,
-    loading_displayed: bool,
+    pending_request: Option<PendingRequest<Description>>,
     force_back_startup: bool,
     start_items_from: i32,
     submitable: bool,
@@ -404,7 +404,6 @@ impl DescriptionEngine {
             search_by_str_button_pressed: -1,
             search_by_str_names,
             pending_request: None,
-            loading_displayed: false,
             force_back_startup,
             start_items_from: 0,
             submitable,
@@ -524,6 +523,10 @@ impl DescriptionEngine {
     }
 
     fn submit_form(&mut self) {
+        if self.pending_request.is_some() {
+            return;
+        }
+
         let form_data = self.get_form_data();
         let force_in_query = self.form_data_in_query();
         let form_action = self.get_form_action().unwrap();
@@ -535,7 +538,13 @@ impl DescriptionEngine {
             (Map::new(), form_data.clone())
         };
 
-        self.pending_request = Some((form_action, final_form_data, final_form_query));
+        self.pending_request = Some(self.client.spawn(move |client| {
+            client.describe(
+                form_action.as_str(),
+                Some(final_form_data),
+                Some(final_form_query),
+            )
+        }));
     }
 
     fn create_row_from_item(
@@ -1078,34 +1087,28 @@ impl Engine for DescriptionEngine {
             });
         }
 
-        if self.loading_displayed {
-            let (url, form_data, form_query) = self.pending_request.as_ref().unwrap().clone();
-            self.pending_request = None;
-            self.loading_displayed = false;
-
-            let try_description = self.client.describe(
-                url.as_str(),
-                Some(form_data.clone()),
-                Some(form_query.clone()),
-            );
-            match try_description {
-                Result::Err(client_error) => {
-                    self.error_message = Some(client::ClientError::get_message(&client_error));
-                }
-                Result::Ok(description) => {
-                    if let Some(character_id) = description.new_character_id {
-                        return Some(MainMessage::NewCharacterId {
-                            character_id: character_id.clone(),
-                        });
+        if let Some(pending_request) = self.pending_request.as_ref() {
+            if let Some(try_description) = pending_request.try_take() {
+                self.pending_request = None;
+                match try_description {
+                    Result::Err(client_error) => {
+                        self.error_message = Some(client::ClientError::get_message(&client_error));
                     }
-                    if description.account_created {
-                        return Some(MainMessage::AccountCreated);
+                    Result::Ok(description) => {
+                        if let Some(character_id) = description.new_character_id {
+                            return Some(MainMessage::NewCharacterId {
+                                character_id: character_id.clone(),
+                            });
+                        }
+                        if description.account_created {
+                            return Some(MainMessage::AccountCreated);
+                        }
+                        return Some(MainMessage::ToDescriptionWithDescription {
+                            description,
+                            back_url: self.future_back_url.clone(),
+                            client: self.client.clone(),
+                        });
                     }
-                    return Some(MainMessage::ToDescriptionWithDescription {
-                        description,
-                        back_url: self.future_back_url.clone(),
-                        client: self.client.clone(),
-                    });
                 }
             }
         }
@@ -1226,7 +1229,7 @@ impl Engine for DescriptionEngine {
                 });
             }
             Message::WebBrowserLinkButtonPressed(url) => {
-                println!("Open url {} with web browser", url);
+                println!("Open url {} with web browser", util::redact_url(&url));
                 if webbrowser::open(&url).is_ok() {
                     // ..
                 }
@@ -1322,7 +1325,6 @@ impl Engine for DescriptionEngine {
 
     fn layout(&mut self, window: &Window, illustration: Option<Image>) -> Element {
         if self.pending_request.is_some() {
-            self.loading_displayed = true;
             return Column::new()
                 .width(window.width() as u32)
                 .height(window.height() as u32)
@@ -0,0 +1,145 @@
+use crate::engine::Engine;
+use crate::error::RollingError;
+use crate::input::MyGameInput;
+use crate::message::{MainMessage, Message};
+use crate::ui::widget::button;
+use crate::ui::widget::button::Button;
+use crate::ui::widget::text::Text;
+use crate::ui::Column;
+use crate::ui::Element;
+use coffee::graphics::{Color, Frame, Image, Window};
+use coffee::input::keyboard;
+use coffee::ui::{Align, Justify};
+use coffee::Timer;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+const CRASH_REPORTS_FOLDER: &str = "crash_reports";
+
+pub struct ErrorEngine {
+    error: RollingError,
+    context: String,
+    retry: Option<MainMessage>,
+    crash_report_path: Option<String>,
+    retry_button: button::State,
+    crash_report_button: button::State,
+    startup_button: button::State,
+}
+
+impl ErrorEngine {
+    /// Display given error. `context` describe what was happening (it is written in crash
+    /// report) and `retry` is the message to replay if user want to retry.
+    pub fn new(error: RollingError, context: &str, retry: Option<MainMessage>) -> Self {
+        eprintln!("Error during \"{}\": {}", context, error);
+        Self {
+            error,
+            context: context.to_string(),
+            retry,
+            crash_report_path: None,
+            retry_button: button::State::new(),
+            crash_report_button: button::State::new(),
+            startup_button: button::State::new(),
+        }
+    }
+
+    fn write_crash_report(&self) -> Result<String, RollingError> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let file_path = Path::new(CRASH_REPORTS_FOLDER).join(format!("{}.txt", timestamp));
+
+        let mut file = fs::create_dir_all(CRASH_REPORTS_FOLDER)
+            .and_then(|_| fs::File::create(&file_path))
+            .map_err(|error| RollingError::new(format!("Unable to create report: {}", error)))?;
+        write!(
+            file,
+            "version: {}\ntimestamp: {}\ncontext: {}\nerror: {}\n",
+            VERSION, timestamp, self.context, self.error
+        )
+        .map_err(|error| RollingError::new(format!("Unable to write report: {}", error)))?;
+
+        Ok(file_path
+            .to_str()
+            .unwrap_or(CRASH_REPORTS_FOLDER)
+            .to_string())
+    }
+}
+
+impl Engine for ErrorEngine {
+    fn draw(&mut self, frame: &mut Frame, _timer: &Timer, _illustration: Option<Image>) {
+        frame.clear(Color::BLACK);
+    }
+
+    fn update(&mut self, _window: &Window) -> Option<MainMessage> {
+        None
+    }
+
+    fn interact(&mut self, input: &mut MyGameInput, _window: &mut Window) -> Option<MainMessage> {
+        if let Some(keyboard::KeyCode::Escape) = input.key_code {
+            input.key_code = None;
+            return Some(MainMessage::ToStartup);
+        }
+
+        None
+    }
+
+    fn react(&mut self, event: Message, _window: &mut Window) -> Option<MainMessage> {
+        match event {
+            Message::RetryButtonPressed => return self.retry.clone(),
+            Message::CrashReportButtonPressed => match self.write_crash_report() {
+                Ok(file_path) => self.crash_report_path = Some(file_path),
+                Err(error) => eprintln!("{}", error),
+            },
+            Message::ToStartupPressed => return Some(MainMessage::ToStartup),
+            _ => {}
+        }
+
+        None
+    }
+
+    fn layout(&mut self, window: &Window, _illustration: Option<Image>) -> Element {
+        let mut column = Column::new()
+            .max_width(768)
+            .height(window.height() as u32)
+            .align_items(Align::Center)
+            .justify_content(Justify::Center)
+            .spacing(20)
+            .push(Text::new("Une erreur est survenue").size(50).height(60))
+            .push(Text::new(&self.error.message).color(Color::RED));
+
+        if self.retry.is_some() {
+            column = column.push(
+                Button::new(&mut self.retry_button, "Réessayer")
+                    .on_press(Message::RetryButtonPressed)
+                    .class(button::Class::Primary),
+            );
+        }
+
+        if let Some(crash_report_path) = &self.crash_report_path {
+            column = column.push(
+                Text::new(&format!("Rapport enregistré dans {}", crash_report_path))
+                    .color(Color::GREEN),
+            );
+        } else {
+            column = column.push(
+                Button::new(&mut self.crash_report_button, "Enregistrer un rapport")
+                    .on_press(Message::CrashReportButtonPressed)
+                    .class(button::Class::Secondary),
+            );
+        }
+
+        column
+            .push(
+                Button::new(&mut self.startup_button, "Retour à l'accueil")
+                    .on_press(Message::ToStartupPressed)
+                    .class(button::Class::Secondary),
+            )
+            .into()
+    }
+
+    fn teardown(&mut self) {}
+}
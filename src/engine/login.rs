@@ -1,9 +1,12 @@
 use crate::engine::Engine;
+use crate::gui::lang::model::Description;
 use crate::input::MyGameInput;
 use crate::message::{MainMessage, Message};
 use crate::server;
+use crate::server::request::PendingRequest;
 use crate::ui::widget::button;
 use crate::ui::widget::button::Button;
+use crate::ui::widget::checkbox::Checkbox;
 use crate::ui::widget::text::Text;
 use crate::ui::widget::text_input::TextInput;
 use crate::ui::{Column, Element, Row};
@@ -22,6 +25,14 @@ pub struct LoginEngine {
     password_lost_button: button::State,
     create_account_button: button::State,
     cancel_button: button::State,
+    change_server_button: button::State,
+    /// Display change server button (when more than one server is configured)
+    change_server: bool,
+    remembered_login_button: button::State,
+    logout_button: button::State,
+    /// Credentials remembered for this server, player can continue with them or forget them
+    remembered_auth: Option<server::client::Auth>,
+    remember: bool,
     login_input_text: String,
     login_input_text_is_selected: bool,
     password_input_text: String,
@@ -29,21 +40,32 @@ pub struct LoginEngine {
     blink_time: Instant,
     error_message: Option<String>,
     message: Option<String>,
+    pending_login: Option<PendingRequest<(server::client::Auth, String)>>,
+    pending_login_is_remembered: bool,
+    pending_password_lost: Option<PendingRequest<Description>>,
 }
 
 impl LoginEngine {
     pub fn new(
-        address: server::ServerAddress,
+        client: server::client::Client,
         message: Option<String>,
         default_login: String,
+        change_server: bool,
+        remembered_auth: Option<server::client::Auth>,
     ) -> Self {
         Self {
-            address: address.clone(),
-            client: server::client::Client::new(address.clone(), ("".to_string(), "".to_string())),
+            address: client.address.clone(),
+            client,
             login_button: button::State::new(),
             password_lost_button: button::State::new(),
             create_account_button: button::State::new(),
             cancel_button: button::State::new(),
+            change_server_button: button::State::new(),
+            change_server,
+            remembered_login_button: button::State::new(),
+            logout_button: button::State::new(),
+            remember: remembered_auth.is_some(),
+            remembered_auth,
             login_input_text: default_login.clone(),
             login_input_text_is_selected: false,
             password_input_text: "".to_string(),
@@ -51,6 +73,9 @@ impl LoginEngine {
             blink_time: Instant::now(),
             error_message: None,
             message,
+            pending_login: None,
+            pending_login_is_remembered: false,
+            pending_password_lost: None,
         }
     }
 
@@ -66,27 +91,58 @@ impl LoginEngine {
         }
     }
 
-    fn submit(&mut self) -> Option<MainMessage> {
+    fn submit(&mut self) {
+        if self.pending_login.is_some() {
+            return;
+        }
+
         self.error_message = None;
-        let credentials = (
-            self.login_input_text.trim().to_string(),
-            self.password_input_text.trim().to_string(),
-        );
-        match self.client.get_current_character_id((
-            self.login_input_text.clone(),
-            self.password_input_text.clone(),
-        )) {
-            Ok(current_character_id) => {
+        let login = self.login_input_text.trim().to_string();
+        let password = self.password_input_text.trim().to_string();
+        self.pending_login_is_remembered = false;
+        self.pending_login = Some(self.client.spawn(move |client| {
+            let auth = client.login(&login, &password)?;
+            let character_id = client.with_auth(auth.clone()).get_current_character_id()?;
+            Ok((auth, character_id))
+        }));
+    }
+
+    fn submit_remembered(&mut self) {
+        let auth = match (self.pending_login.as_ref(), self.remembered_auth.as_ref()) {
+            (None, Some(auth)) => auth.clone(),
+            _ => return,
+        };
+
+        self.error_message = None;
+        self.pending_login_is_remembered = true;
+        self.pending_login = Some(self.client.spawn(move |client| {
+            let character_id = client.with_auth(auth.clone()).get_current_character_id()?;
+            Ok((auth, character_id))
+        }));
+    }
+
+    fn proceed_pending_login(&mut self) -> Option<MainMessage> {
+        let result = self.pending_login.as_ref()?.try_take()?;
+        self.pending_login = None;
+
+        match result {
+            Ok((auth, current_character_id)) => {
                 let current_character_id_ = if current_character_id == "" {
                     None
                 } else {
                     Some(current_character_id)
                 };
                 return Some(MainMessage::EnterServer {
-                    credentials,
+                    auth,
+                    remember: self.remember,
                     character_id: current_character_id_,
                 });
             }
+            Err(server::client::ClientError::Unauthorized) if self.pending_login_is_remembered => {
+                return Some(MainMessage::ForgetCredentials {
+                    message: Some("Session expirée, veuillez vous identifier".to_string()),
+                });
+            }
             Err(server::client::ClientError::Unauthorized) => {
                 self.error_message = Some("Mauvais login ou mot de passe ?".to_string());
             }
@@ -97,6 +153,26 @@ impl LoginEngine {
 
         None
     }
+
+    fn proceed_pending_password_lost(&mut self) -> Option<MainMessage> {
+        let result = self.pending_password_lost.as_ref()?.try_take()?;
+        self.pending_password_lost = None;
+
+        match result {
+            Ok(description) => {
+                return Some(MainMessage::ToDescriptionWithDescription {
+                    description,
+                    back_url: None,
+                    client: self.client.clone(),
+                });
+            }
+            Err(client_error) => {
+                self.error_message = Some(server::client::ClientError::get_message(&client_error));
+            }
+        }
+
+        None
+    }
 }
 
 impl Engine for LoginEngine {
@@ -125,7 +201,11 @@ impl Engine for LoginEngine {
     }
 
     fn update(&mut self, _window: &Window) -> Option<MainMessage> {
-        None
+        if let Some(main_message) = self.proceed_pending_login() {
+            return Some(main_message);
+        }
+
+        self.proceed_pending_password_lost()
     }
 
     fn interact(&mut self, input: &mut MyGameInput, _window: &mut Window) -> Option<MainMessage> {
@@ -164,7 +244,11 @@ impl Engine for LoginEngine {
             }
             Some(keyboard::KeyCode::Return) => {
                 input.key_code = None;
-                return self.submit();
+                if self.remembered_auth.is_some() {
+                    self.submit_remembered();
+                } else {
+                    self.submit();
+                }
             }
             _ => {}
         }
@@ -185,28 +269,26 @@ impl Engine for LoginEngine {
                 self.error_message = None;
             }
             Message::ConfirmButtonPressed => {
-                return self.submit();
+                self.submit();
             }
+            Message::RememberMeToggled(remember) => self.remember = remember,
+            Message::RememberedLoginButtonPressed => self.submit_remembered(),
+            Message::LogoutButtonPressed => return Some(MainMessage::Logout),
             Message::PasswordLostButtonPressed => {
-                let description = self
-                    .client
-                    .describe(
-                        &format!("/account/password_lost?login={}", self.login_input_text),
-                        None,
-                        None,
-                    )
-                    .unwrap();
-                return Some(MainMessage::ToDescriptionWithDescription {
-                    description,
-                    back_url: None,
-                    client: self.client.clone(),
-                });
+                if self.pending_password_lost.is_none() {
+                    let url = format!("/account/password_lost?login={}", self.login_input_text);
+                    self.pending_password_lost = Some(
+                        self.client
+                            .spawn(move |client| client.describe(&url, None, None)),
+                    );
+                }
             }
             Message::CreateAccountButtonPressed => {
                 return Some(MainMessage::CreateAccount {
                     address: self.address.clone(),
                 })
             }
+            Message::ChangeServerButtonPressed => return Some(MainMessage::ToServerPicker),
             Message::ExitMenuButtonPressed => return Some(MainMessage::ExitRequested),
             _ => {}
         }
@@ -224,72 +306,110 @@ impl Engine for LoginEngine {
             .spacing(20)
             .push(Text::new("").size(30).height(30).width(600));
 
-        if let Some(error_message) = self.error_message.as_ref() {
+        if self.pending_login.is_some() || self.pending_password_lost.is_some() {
+            column = column.push(Text::new("Chargement ...").color(Color::WHITE))
+        } else if let Some(error_message) = self.error_message.as_ref() {
             column = column.push(Text::new(error_message).color(Color::RED))
         } else if let Some(message) = self.message.as_ref() {
             column = column.push(Text::new(message).color(Color::GREEN))
         }
 
-        column = column
-            .push(
-                Row::new()
-                    .push(TextInput::new(
-                        0,
-                        "Login/Email",
-                        &self.login_input_text,
-                        Message::LoginInputSelected,
-                        if self.login_input_text_is_selected {
-                            blink_char
-                        } else {
-                            None
-                        },
-                        None,
-                    ))
-                    .width(500),
-            )
-            .push(
-                Row::new()
-                    .push(
-                        TextInput::new(
-                            1,
-                            "Mot de passe",
-                            &self.password_input_text,
-                            Message::PasswordInputSelected,
-                            if self.password_input_text_is_selected {
+        if let Some(remembered_auth) = self.remembered_auth.as_ref() {
+            let login = remembered_auth.login().to_string();
+            column = column
+                .push(Text::new(&format!("Connecté en tant que {}", login)))
+                .push(
+                    Button::new(
+                        &mut self.remembered_login_button,
+                        &format!("Continuer en tant que {}", login),
+                    )
+                    .on_press(Message::RememberedLoginButtonPressed)
+                    .class(button::Class::Primary)
+                    .width(300),
+                )
+                .push(
+                    Button::new(&mut self.logout_button, "Se déconnecter")
+                        .on_press(Message::LogoutButtonPressed)
+                        .class(button::Class::Primary)
+                        .width(300),
+                );
+        } else {
+            column = column
+                .push(
+                    Row::new()
+                        .push(TextInput::new(
+                            0,
+                            "Login/Email",
+                            &self.login_input_text,
+                            Message::LoginInputSelected,
+                            if self.login_input_text_is_selected {
                                 blink_char
                             } else {
                                 None
                             },
                             None,
+                        ))
+                        .width(500),
+                )
+                .push(
+                    Row::new()
+                        .push(
+                            TextInput::new(
+                                1,
+                                "Mot de passe",
+                                &self.password_input_text,
+                                Message::PasswordInputSelected,
+                                if self.password_input_text_is_selected {
+                                    blink_char
+                                } else {
+                                    None
+                                },
+                                None,
+                            )
+                            .is_password(true),
                         )
-                        .is_password(true),
-                    )
-                    .width(500),
-            )
-            .push(
-                Button::new(&mut self.login_button, "S'identifier")
-                    .on_press(Message::ConfirmButtonPressed)
-                    .class(button::Class::Primary)
-                    .width(300),
-            )
-            .push(
-                Button::new(&mut self.password_lost_button, "Mot de passe perdu")
-                    .on_press(Message::PasswordLostButtonPressed)
-                    .class(button::Class::Primary)
-                    .width(300),
-            )
-            .push(
-                Button::new(&mut self.create_account_button, "Créer un compte")
-                    .on_press(Message::CreateAccountButtonPressed)
-                    .class(button::Class::Primary)
-                    .width(300),
-            )
-            .push(
-                Button::new(&mut self.cancel_button, "Quitter")
-                    .on_press(Message::ExitMenuButtonPressed)
+                        .width(500),
+                )
+                .push(Checkbox::new(
+                    self.remember,
+                    "Se souvenir de moi",
+                    Message::RememberMeToggled,
+                ))
+                .push(
+                    Button::new(&mut self.login_button, "S'identifier")
+                        .on_press(Message::ConfirmButtonPressed)
+                        .class(button::Class::Primary)
+                        .width(300),
+                )
+                .push(
+                    Button::new(&mut self.password_lost_button, "Mot de passe perdu")
+                        .on_press(Message::PasswordLostButtonPressed)
+                        .class(button::Class::Primary)
+                        .width(300),
+                )
+                .push(
+                    Button::new(&mut self.create_account_button, "Créer un compte")
+                        .on_press(Message::CreateAccountButtonPressed)
+                        .class(button::Class::Primary)
+                        .width(300),
+                );
+        }
+
+        if self.change_server {
+            column = column.push(
+                Button::new(&mut self.change_server_button, "Changer de serveur")
+                    .on_press(Message::ChangeServerButtonPressed)
                     .class(button::Class::Secondary)
                     .width(200),
             );
+        }
+
+        column = column.push(
+            Button::new(&mut self.cancel_button, "Quitter")
+                .on_press(Message::ExitMenuButtonPressed)
+                .class(button::Class::Secondary)
+                .width(200),
+        );
 
         column.into()
     }
@@ -5,8 +5,10 @@ use coffee::graphics::{CursorIcon, Frame, Image, Window};
 use coffee::Timer;
 
 pub mod description;
+pub mod error;
 pub mod exit;
 pub mod login;
+pub mod server_picker;
 pub mod upgrade;
 pub mod world;
 pub mod zone;
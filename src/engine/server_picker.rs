@@ -0,0 +1,214 @@
+use crate::engine::Engine;
+use crate::input::MyGameInput;
+use crate::message::{MainMessage, Message};
+use crate::server::client::{Client, ClientError};
+use crate::server::profile::ServerProfile;
+use crate::server::request::PendingRequest;
+use crate::ui::widget::button;
+use crate::ui::widget::button::Button;
+use crate::ui::widget::text::Text;
+use crate::ui::{Column, Element, Row};
+use crate::util;
+use coffee::graphics::{Batch, Color, Frame, Image, Point, Rectangle, Sprite, Window};
+use coffee::input::keyboard;
+use coffee::ui::{Align, Justify};
+use coffee::Timer;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+enum ServerStatus {
+    Checking(PendingRequest<(u8, u8, u8)>),
+    Unreachable(ClientError),
+    Reachable((u8, u8, u8)),
+}
+
+/// List server profiles with their reachability and let player choose one
+pub struct ServerPickerEngine {
+    client: Client,
+    profiles: Vec<ServerProfile>,
+    statuses: Vec<ServerStatus>,
+    profile_buttons: Vec<button::State>,
+    refresh_button: button::State,
+    exit_button: button::State,
+}
+
+impl ServerPickerEngine {
+    /// Profiles are checked with given client request workers
+    pub fn new(profiles: Vec<ServerProfile>, client: Client) -> Self {
+        let profile_buttons = profiles.iter().map(|_| button::State::new()).collect();
+        let mut engine = Self {
+            client,
+            profiles,
+            statuses: vec![],
+            profile_buttons,
+            refresh_button: button::State::new(),
+            exit_button: button::State::new(),
+        };
+        engine.check_servers();
+        engine
+    }
+
+    fn check_servers(&mut self) {
+        let client = &self.client;
+        self.statuses = self
+            .profiles
+            .iter()
+            .map(|profile| {
+                let mut client = client.clone();
+                client.address = profile.address.clone();
+                ServerStatus::Checking(client.spawn(|client| client.get_version()))
+            })
+            .collect();
+    }
+
+    fn proceed_statuses(&mut self) {
+        for status in self.statuses.iter_mut() {
+            let result = match status {
+                ServerStatus::Checking(pending_request) => match pending_request.try_take() {
+                    Some(result) => result,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            *status = match result {
+                Ok(version) => ServerStatus::Reachable(version),
+                Err(error) => ServerStatus::Unreachable(error),
+            };
+        }
+    }
+
+    fn status_text(status: &ServerStatus) -> (String, Color) {
+        match status {
+            ServerStatus::Checking(_) => ("Vérification ...".to_string(), Color::WHITE),
+            ServerStatus::Unreachable(error) => (
+                format!("Injoignable ({})", ClientError::get_message(error)),
+                Color::RED,
+            ),
+            ServerStatus::Reachable(version) => {
+                let (major, minor, correction) = *version;
+                let client_version = util::str_version_to_tuple(VERSION);
+                if util::is_compatible_versions(*version, client_version) {
+                    (
+                        format!("En ligne (v{}.{}.{})", major, minor, correction),
+                        Color::GREEN,
+                    )
+                } else {
+                    (
+                        format!(
+                            "En ligne (v{}.{}.{}, mise à jour requise)",
+                            major, minor, correction
+                        ),
+                        Color::from_rgb(255, 200, 0),
+                    )
+                }
+            }
+        }
+    }
+}
+
+impl Engine for ServerPickerEngine {
+    fn draw(&mut self, frame: &mut Frame, _timer: &Timer, illustration: Option<Image>) {
+        frame.clear(Color::BLACK);
+
+        if let Some(illustration) = illustration {
+            let illustration_width = illustration.width();
+            let illustration_height = illustration.height();
+            let mut batch = Batch::new(illustration);
+            batch.extend(vec![Sprite {
+                source: Rectangle {
+                    x: 0,
+                    y: 0,
+                    width: illustration_width,
+                    height: illustration_height,
+                },
+                position: Point::new(0.0, 0.0),
+                scale: (
+                    frame.width() / illustration_width as f32,
+                    frame.height() / illustration_height as f32,
+                ),
+            }]);
+            batch.draw(&mut frame.as_target());
+        };
+    }
+
+    fn update(&mut self, _window: &Window) -> Option<MainMessage> {
+        self.proceed_statuses();
+        None
+    }
+
+    fn interact(&mut self, input: &mut MyGameInput, _window: &mut Window) -> Option<MainMessage> {
+        if let Some(keyboard::KeyCode::Escape) = input.key_code {
+            input.key_code = None;
+            return Some(MainMessage::ExitRequested);
+        }
+
+        None
+    }
+
+    fn react(&mut self, event: Message, _window: &mut Window) -> Option<MainMessage> {
+        match event {
+            Message::ServerProfilePressed(profile_i) => {
+                if let Some(profile) = self.profiles.get(profile_i as usize) {
+                    return Some(MainMessage::ServerProfileSelected {
+                        profile_id: profile.id.clone(),
+                    });
+                }
+            }
+            Message::RefreshServersButtonPressed => self.check_servers(),
+            Message::ExitMenuButtonPressed => return Some(MainMessage::ExitRequested),
+            _ => {}
+        }
+
+        None
+    }
+
+    fn layout(&mut self, window: &Window, _illustration: Option<Image>) -> Element {
+        let mut column = Column::new()
+            .width(window.width() as u32)
+            .height(window.height() as u32)
+            .align_items(Align::Center)
+            .justify_content(Justify::Center)
+            .spacing(20)
+            .push(Text::new("Choisissez un serveur").size(30).height(40));
+
+        for (profile_i, ((profile, status), button_state)) in self
+            .profiles
+            .iter()
+            .zip(self.statuses.iter())
+            .zip(self.profile_buttons.iter_mut())
+            .enumerate()
+        {
+            let (status_text, status_color) = Self::status_text(status);
+            column = column.push(
+                Row::new()
+                    .width(700)
+                    .spacing(20)
+                    .align_items(Align::Center)
+                    .push(
+                        Button::new(button_state, &profile.name)
+                            .on_press(Message::ServerProfilePressed(profile_i as i32))
+                            .class(button::Class::Primary)
+                            .width(300),
+                    )
+                    .push(Text::new(&status_text).color(status_color).width(380)),
+            );
+        }
+
+        column
+            .push(
+                Button::new(&mut self.refresh_button, "Actualiser")
+                    .on_press(Message::RefreshServersButtonPressed)
+                    .class(button::Class::Secondary)
+                    .width(200),
+            )
+            .push(
+                Button::new(&mut self.exit_button, "Quitter")
+                    .on_press(Message::ExitMenuButtonPressed)
+                    .class(button::Class::Secondary)
+                    .width(200),
+            )
+            .into()
+    }
+
+    fn teardown(&mut self) {}
+}
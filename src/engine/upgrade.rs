@@ -18,9 +18,10 @@ use std::{env, fs};
 
 pub struct UpgradeEngine {
     conf: Ini,
+    releases_url: String,
     version: (u8, u8, u8),
     mandatory: bool,
-    _client: server::client::Client,
+    client: server::client::Client,
     folder: String,
     already_on_disk: bool,
     display_downloading: bool,
@@ -36,8 +37,9 @@ impl UpgradeEngine {
     pub fn new(
         version: (u8, u8, u8),
         mandatory: bool,
-        address: server::ServerAddress,
+        client: server::client::Client,
         conf: Ini,
+        releases_url: String,
     ) -> Self {
         // Determine where is the reference folder (executable can be in x.y.z folder)
         let executable_name = if cfg!(windows) {
@@ -66,9 +68,10 @@ impl UpgradeEngine {
 
         Self {
             conf,
+            releases_url,
             version,
             mandatory,
-            _client: server::client::Client::new(address, ("".to_string(), "".to_string())),
+            client,
             already_on_disk,
             folder: String::from(folder.to_str().unwrap()),
             display_downloading: false,
@@ -94,8 +97,8 @@ impl Engine for UpgradeEngine {
             let remote_file_name = format!("{}_{}.{}.{}.zip", file_name, major, minor, correction);
             let extracted_folder_name = file_name.clone();
             let folder = Path::new(&self.folder);
-            let releases_url = self.conf.get_from(Some("server"), "releases_url").unwrap();
-            let client = reqwest::blocking::Client::new();
+            let releases_url = &self.releases_url;
+            let client = self.client.network_config().http_client();
             let url = &format!("{}/{}", releases_url, remote_file_name);
             let download_into = folder.join(&remote_file_name);
             println!(
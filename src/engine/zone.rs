@@ -1,21 +1,26 @@
+use crate::camera::Camera;
+use crate::context_menu::{self, ContextAction, ContextMenu};
 use crate::engine::Engine;
 use crate::entity::build::Build;
 use crate::entity::character::Character;
-use crate::entity::corpse::AnimatedCorpse;
 use crate::entity::player::Player;
-use crate::entity::resource::Resource;
 use crate::entity::stuff::Stuff;
-use crate::event::{CharacterActionLink, TopBarMessageType, ZoneEventType};
+use crate::event::{CharacterActionLink, TopBarMessageType, ZoneEvent, ZoneEventType};
 use crate::game::{TILE_HEIGHT, TILE_WIDTH};
 use crate::gui::lang::model::{Description, RequestClicks};
 use crate::input::MyGameInput;
 use crate::level::Level;
 use crate::message::{self, MainMessage, Message};
+use crate::minimap::Minimap;
+use crate::movement::{Facing, Motions, MovementSettings, PendingMoves, Reconciliation, TileStep};
 use crate::server::client::{ClientError, ItemModel};
+use crate::server::media_cache::MediaKind;
+use crate::server::request::PendingRequest;
 use crate::server::Server;
 use crate::sheet::TileSheet;
-use crate::socket::ZoneSocket;
-use crate::tile::zone::Tiles;
+use crate::socket::{ConnectionState, ZoneSocket};
+use crate::state::{Applied, ZoneState};
+use crate::tooltip::{self, TOOLTIP_DELAY_MS, TOOLTIP_EXPAND_DELAY_MS};
 use crate::ui::widget::fixed_button;
 use crate::ui::widget::icon;
 use crate::ui::widget::link::Link;
@@ -30,22 +35,22 @@ use crate::ui::Element;
 use crate::ui::Row;
 use crate::{event, util};
 use coffee::graphics::{
-    Batch, Color, CursorIcon, Frame, HorizontalAlignment, Image, Point, Rectangle, Sprite,
-    VerticalAlignment, Window,
+    Batch, Color, CursorIcon, Font, Frame, Gpu, HorizontalAlignment, Image, Mesh, Point, Rectangle,
+    Shape, Sprite, VerticalAlignment, Window,
 };
 use coffee::input::keyboard;
 use coffee::input::mouse;
 use coffee::ui::Align;
 use coffee::{graphics, Timer};
 use crossbeam_channel::unbounded;
-use pathfinding::prelude::{absdiff, astar};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::thread;
 use std::time::{Duration, Instant, SystemTime};
 
 const START_SCREEN_X: i16 = 0;
 const LEFT_MENU_WIDTH: i16 = 190;
-const LEFT_MENU_HEIGHT: i16 = 435;
+const LEFT_MENU_HEIGHT: i16 = 460;
 const RIGHT_MENU_WIDTH: i16 = 120;
 const START_SCREEN_Y: i16 = 0;
 const TEXT_INPUT_CHAT_ID: i32 = 0;
@@ -53,8 +58,20 @@ const CHAT_MESSAGE_COUNT: i32 = 15;
 const TOP_BAR_BUTTON_WIDTH: u32 = 100;
 const MARGIN_RIGHT_CHAT: u32 = 25;
 const CHAT_LINE_HEIGHT: u32 = 20;
-const BORDERS_TO_SEE_PLAYER_LEN: i16 = 15;
 const QUICK_ACTION_ROW_HEIGHT: u32 = 50;
+const LATENCY_LOG_INTERVAL_S: u64 = 60;
+const IMPOSSIBLE_MOVE_MARKER_MS: u64 = 1500;
+const PATH_MARKER_RADIUS: f32 = 3.0;
+const PATH_DESTINATION_RADIUS: f32 = 8.0;
+// View pixels by second when camera is moved with arrow keys
+const CAMERA_PAN_SPEED: f32 = 600.0;
+// Server corrections farther than this (in tiles) are not animated
+const MAX_SMOOTH_CORRECTION_DISTANCE: f32 = 1.5;
+const MINIMAP_MARGIN: f32 = 10.0;
+const TOOLTIP_TEXT_SIZE: f32 = 18.0;
+const TOOLTIP_PADDING: f32 = 5.0;
+const TOOLTIP_CURSOR_OFFSET: f32 = 16.0;
+const CONTEXT_MENU_MIN_WIDTH: f32 = 150.0;
 
 fn contains_string(classes: &Vec<String>, search: &str) -> bool {
     for class in classes.iter() {
@@ -66,6 +83,17 @@ fn contains_string(classes: &Vec<String>, search: &str) -> bool {
     false
 }
 
+// Zone data, characters, stuffs and builds fetched after a reconnection
+type ZoneResync = (Value, Vec<Character>, Vec<Stuff>, Vec<Build>);
+
+/// Path to hovered tile, computed again only when player or hovered tile change
+struct PathPreview {
+    from: (i16, i16),
+    to: (i16, i16),
+    // None if hovered tile is unreachable
+    path: Option<Vec<(i16, i16)>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct TopBar {
     text: String,
@@ -82,7 +110,6 @@ pub struct Chat {
 pub struct ZoneEngine {
     i: i32,
     sprite_i: i32,
-    tiles: Tiles,
     tile_sheet: TileSheet,
     tile_sheet_batch: Batch,
     avatars_to_load: Vec<String>,
@@ -93,11 +120,19 @@ pub struct ZoneEngine {
     start_screen_y: i16,
     end_screen_x: i16,
     end_screen_y: i16,
-    start_zone_row_i: i16,
-    start_zone_col_i: i16,
+    camera: Camera,
+    // Cursor position of previous frame while camera is dragged
+    camera_drag_from: Option<Point>,
+    last_interact_at: Instant,
+    minimap: Minimap,
+    // Hovered zone tile, since when
+    hovered_tile: Option<((i16, i16), Instant)>,
+    tooltip_expand_pressed: bool,
+    tooltip_font: Option<Font>,
+    context_menu: Option<ContextMenu>,
     server: Server,
     player: Player,
-    level: Level,
+    state: ZoneState,
     socket: ZoneSocket,
     world_menu_button_state: thin_button::State,
     card_menu_button_state: thin_button::State,
@@ -124,19 +159,21 @@ pub struct ZoneEngine {
     around_quick_actions: Vec<CharacterActionLink>,
     current_quick_action_link_pressed: Option<String>,
     blinker: util::Blinker<char>,
-    characters: HashMap<String, Character>,
-    stuffs: HashMap<String, Stuff>,
-    resources: Vec<Resource>,
-    builds: HashMap<i32, Build>,
-    animated_corpses: HashMap<i32, AnimatedCorpse>,
-    builds_positions: HashMap<(i16, i16), Vec<i32>>,
     link_button_ids: HashMap<String, i32>,
     link_button_pressed: i32,
-    move_requested: Option<Vec<(i16, i16)>>,
+    movement: MovementSettings,
+    // Current player move to a neighbour tile
+    player_step: Option<TileStep>,
+    pending_moves: PendingMoves,
+    character_motions: Motions<String>,
+    animated_corpse_motions: Motions<i32>,
+    // Remaining tiles of path followed by player after a click
+    move_path: Option<Vec<(i16, i16)>>,
+    path_preview: Option<PathPreview>,
+    impossible_move: Option<((i16, i16), Instant)>,
     request_clicks: Option<RequestClicks>,
     cursor_position: Point,
     player_tile_id: String,
-    player_move_ticker: util::Ticker,
     top_bar: Option<TopBar>,
     displaying_chat: bool,
     display_chat_required: bool,
@@ -149,26 +186,26 @@ pub struct ZoneEngine {
     replace_top_bar_by: Option<TopBar>,
     send_quick_actions_transmitter: crossbeam_channel::Sender<String>,
     response_quick_actions_receiver: crossbeam_channel::Receiver<Result<Description, ClientError>>,
+    connection_state: ConnectionState,
+    latency_logged_at: Instant,
+    pending_resync: Option<PendingRequest<ZoneResync>>,
+    // Events applied since resync was requested, applied again over resync snapshot
+    resync_events: Vec<ZoneEvent>,
 }
 
 impl ZoneEngine {
     pub fn new(
-        tiles: Tiles,
         tile_sheet_image: graphics::Image,
         avatars: Vec<String>,
         tile_width: i16,
         tile_height: i16,
         player: Player,
         server: Server,
-        level: Level,
+        state: ZoneState,
         socket: ZoneSocket,
         resume_text: Vec<ItemModel>,
-        characters: HashMap<String, Character>,
-        stuffs: HashMap<String, Stuff>,
-        resources: Vec<Resource>,
-        builds: HashMap<i32, Build>,
-        animated_corpses: HashMap<i32, AnimatedCorpse>,
         request_clicks: Option<RequestClicks>,
+        movement: MovementSettings,
     ) -> Self {
         let (top_bar, replace_top_bar_start) = if request_clicks.is_some() {
             (
@@ -181,7 +218,7 @@ impl ZoneEngine {
                 None,
             )
         } else {
-            if characters.len() > 0 {
+            if state.characters.len() > 0 {
                 (
                     Some(TopBar {
                         text: "Appuyez sur ENTRER pour ouvrir le chat".to_string(),
@@ -221,7 +258,6 @@ impl ZoneEngine {
         let mut zone_engine = Self {
             i: 0,
             sprite_i: 0,
-            tiles,
             tile_sheet: TileSheet::new(tile_sheet_image.clone(), tile_width, tile_height),
             tile_sheet_batch: Batch::new(tile_sheet_image.clone()),
             avatars_to_load: avatars,
@@ -232,11 +268,17 @@ impl ZoneEngine {
             start_screen_y: START_SCREEN_Y,
             end_screen_x: 0,
             end_screen_y: 0,
-            start_zone_row_i: 0,
-            start_zone_col_i: 0,
+            camera: Camera::new(TILE_WIDTH, TILE_HEIGHT),
+            camera_drag_from: None,
+            last_interact_at: Instant::now(),
+            minimap: Minimap::default(),
+            hovered_tile: None,
+            tooltip_expand_pressed: false,
+            tooltip_font: None,
+            context_menu: None,
             server,
             player,
-            level,
+            state,
             socket,
             world_menu_button_state: thin_button::State::new(),
             card_menu_button_state: thin_button::State::new(),
@@ -265,19 +307,19 @@ impl ZoneEngine {
             blinker: util::Blinker {
                 items: HashMap::new(),
             },
-            characters,
-            stuffs,
-            resources,
-            builds,
-            animated_corpses,
-            builds_positions: HashMap::new(),
             link_button_ids: HashMap::new(),
             link_button_pressed: -1,
-            move_requested: None,
+            movement,
+            player_step: None,
+            pending_moves: PendingMoves::default(),
+            character_motions: Motions::default(),
+            animated_corpse_motions: Motions::default(),
+            move_path: None,
+            path_preview: None,
+            impossible_move: None,
             request_clicks,
             cursor_position: Point::new(0.0, 0.0),
             player_tile_id: String::from("PLAYER"),
-            player_move_ticker: util::Ticker::new(15),
             top_bar,
             displaying_chat: false,
             display_chat_required: false,
@@ -290,19 +332,110 @@ impl ZoneEngine {
             replace_top_bar_by: None,
             send_quick_actions_transmitter,
             response_quick_actions_receiver,
+            connection_state: ConnectionState::Connecting,
+            latency_logged_at: Instant::now(),
+            pending_resync: None,
+            resync_events: vec![],
         };
         zone_engine.update_link_button_data();
-        zone_engine.update_builds_data();
         zone_engine
     }
 
-    fn update_builds_data(&mut self) {
-        for (_, build) in self.builds.iter() {
-            let key = (build.row_i as i16, build.col_i as i16);
-            if self.builds_positions.contains_key(&key) {
-                self.builds_positions.get_mut(&key).unwrap().push(build.id);
-            } else {
-                self.builds_positions.insert(key, vec![build.id]);
+    fn proceed_connection_state(&mut self) {
+        let connection_state = self.socket.state();
+        if connection_state != self.connection_state {
+            if let ConnectionState::Reconnecting { .. } = self.connection_state {
+                if connection_state == ConnectionState::Connected {
+                    self.receive_new_top_bar_message(
+                        "Connexion au serveur rétablie".to_string(),
+                        TopBarMessageType::NORMAL,
+                        false,
+                    );
+                }
+            }
+            self.connection_state = connection_state;
+        }
+
+        if self.socket.take_reconnected() {
+            self.start_resync();
+        }
+
+        if self.latency_logged_at.elapsed() > Duration::from_secs(LATENCY_LOG_INTERVAL_S) {
+            if let Some(latency) = self.socket.latency() {
+                println!("Zone socket latency: {}ms", latency.as_millis());
+            }
+            self.latency_logged_at = Instant::now();
+        }
+    }
+
+    /// Events may have been lost during disconnection: ask again around data and reload zone
+    /// tiles and entities
+    fn start_resync(&mut self) {
+        println!("Resync zone after reconnection");
+        self.socket.send(event::ZoneEvent {
+            event_type_name: String::from(event::CLIENT_REQUIRE_AROUND),
+            event_type: event::ZoneEventType::ClientRequireAround {
+                zone_row_i: self.player.position.0,
+                zone_col_i: self.player.position.1,
+                character_id: String::from(self.player.id.as_str()),
+            },
+        });
+
+        let (world_row_i, world_col_i) = self.player.world_position;
+        self.resync_events.clear();
+        self.pending_resync = Some(self.server.client.spawn(move |client| {
+            Ok((
+                client.get_zone_data(world_row_i, world_col_i)?,
+                client.get_zone_characters(world_row_i, world_col_i)?,
+                client.get_zone_stuffs(world_row_i, world_col_i)?,
+                client.get_zone_builds(world_row_i, world_col_i)?,
+            ))
+        }));
+    }
+
+    fn proceed_resync(&mut self) {
+        let result = match self.pending_resync.as_ref() {
+            Some(pending_resync) => match pending_resync.try_take() {
+                Some(result) => result,
+                None => return,
+            },
+            None => return,
+        };
+        self.pending_resync = None;
+
+        match result {
+            Ok((zone_data, characters, stuffs, builds)) => {
+                match Level::from_zone_data(
+                    &zone_data,
+                    &self.state.tiles,
+                    self.state.level.world_tile_type_id.clone(),
+                ) {
+                    Ok(level) => self.state.level = level,
+                    Err(error) => eprintln!("Error when resync zone tiles : {}", error),
+                }
+                self.state.replace_entities(characters, stuffs, builds);
+                // Snapshot may be older than events received while it was requested
+                for event in std::mem::take(&mut self.resync_events) {
+                    self.state.apply(&event);
+                }
+                self.minimap.invalidate();
+
+                let server_tile =
+                    self.state.characters.get(&self.player.id).map(|character| {
+                        (character.zone_row_i as i16, character.zone_col_i as i16)
+                    });
+                if let Some(server_tile) = server_tile {
+                    self.reconcile_player_position(server_tile);
+                }
+            }
+            Err(error) => {
+                self.resync_events.clear();
+                eprintln!("Error when resync zone : {}", error);
+                self.receive_new_top_bar_message(
+                    format!("Erreur lors de la resynchronisation : {}", error),
+                    TopBarMessageType::ERROR,
+                    false,
+                );
             }
         }
     }
@@ -362,32 +495,36 @@ impl ZoneEngine {
         messages
     }
 
+    /// Sheet sprite at given screen position, scaled with camera zoom
+    fn create_zone_sprite(&self, tile_type_id: &str, real_x: i16, real_y: i16) -> Sprite {
+        let mut sprite =
+            self.tile_sheet
+                .create_sprite_for(tile_type_id, real_x, real_y, self.sprite_i);
+        sprite.scale = (self.camera.zoom(), self.camera.zoom());
+        sprite
+    }
+
     fn get_zone_sprites(&mut self, replace_by_back: Option<String>) -> Vec<Sprite> {
         let mut sprites: Vec<Sprite> = vec![];
-        let can_display_rows =
-            ((self.end_screen_y - self.start_screen_y) / self.tile_sheet.get_tile_height()) + 0;
-        let can_display_cols =
-            ((self.end_screen_x - self.start_screen_x) / self.tile_sheet.get_tile_width()) + 0;
-
-        for absolute_row_i in 0..can_display_rows {
-            let zone_row_i = absolute_row_i + self.start_zone_row_i;
-            if zone_row_i >= self.level.rows.len() as i16 || zone_row_i < 0 {
+        let (first_row_i, first_col_i) = self.camera.tile_at(0.0, 0.0);
+        let (last_row_i, last_col_i) = self.camera.tile_at(
+            (self.end_screen_x - self.start_screen_x) as f32,
+            (self.end_screen_y - self.start_screen_y) as f32,
+        );
+
+        for zone_row_i in first_row_i..=last_row_i {
+            if zone_row_i >= self.state.level.rows.len() as i16 || zone_row_i < 0 {
                 continue;
             }
-            let row = &self.level.rows[zone_row_i as usize];
+            let row = &self.state.level.rows[zone_row_i as usize];
 
-            for absolute_col_i in 0..can_display_cols {
-                let zone_col_i = absolute_col_i + self.start_zone_col_i;
+            for zone_col_i in first_col_i..=last_col_i {
                 if zone_col_i >= row.cols.len() as i16 || zone_col_i < 0 {
                     continue;
                 }
 
                 // If build is here, do not draw tile
-                if replace_by_back.is_none()
-                    && self
-                        .builds_positions
-                        .contains_key(&(zone_row_i, zone_col_i))
-                {
+                if replace_by_back.is_none() && self.state.has_build_at(zone_row_i, zone_col_i) {
                     continue;
                 }
 
@@ -398,11 +535,10 @@ impl ZoneEngine {
                     };
                 };
 
-                sprites.push(self.tile_sheet.create_sprite_for(
+                sprites.push(self.create_zone_sprite(
                     &tile_type_id,
-                    (self.tile_sheet.get_tile_width() * absolute_col_i) + self.start_screen_x,
-                    (self.tile_sheet.get_tile_height() * absolute_row_i) + self.start_screen_y,
-                    self.sprite_i,
+                    self.get_real_x(zone_col_i * TILE_WIDTH),
+                    self.get_real_y(zone_row_i * TILE_HEIGHT),
                 ));
             }
         }
@@ -411,30 +547,47 @@ impl ZoneEngine {
     }
 
     fn get_real_x(&self, x: i16) -> i16 {
-        x + START_SCREEN_X - (self.start_zone_col_i * TILE_WIDTH)
+        START_SCREEN_X + self.camera.to_view(x as f32, 0.0).0.round() as i16
     }
 
     fn get_real_y(&self, y: i16) -> i16 {
-        y + START_SCREEN_Y - (self.start_zone_row_i * TILE_HEIGHT)
+        START_SCREEN_Y + self.camera.to_view(0.0, y as f32).1.round() as i16
+    }
+
+    /// Sheet tile id facing given direction, if sheet have it
+    fn facing_tile_id(&self, tile_id: &str, facing: Facing) -> String {
+        let facing_tile_id = facing.tile_id(tile_id);
+        if self.tile_sheet.have_id(&facing_tile_id) {
+            facing_tile_id
+        } else {
+            tile_id.to_string()
+        }
     }
 
     fn get_characters_sprites(&mut self) -> Vec<Sprite> {
         let mut sprites: Vec<Sprite> = vec![];
+        let now = Instant::now();
 
-        sprites.push(self.tile_sheet.create_sprite_for(
+        sprites.push(self.create_zone_sprite(
             &self.player_tile_id,
             self.get_real_x(self.player.x),
             self.get_real_y(self.player.y),
-            self.sprite_i,
         ));
 
-        for character in self.characters.values().into_iter() {
+        for character in self.state.characters.values().into_iter() {
             if character.id == self.player.id {
                 continue;
             }
 
-            let real_x = self.get_real_x(character.position().1 as i16 * TILE_WIDTH);
-            let real_y = self.get_real_y(character.position().0 as i16 * TILE_HEIGHT);
+            let (x, y) = self.character_motions.pixel_position(
+                &character.id,
+                (character.zone_row_i as i16, character.zone_col_i as i16),
+                now,
+                TILE_WIDTH,
+                TILE_HEIGHT,
+            );
+            let real_x = self.get_real_x(x);
+            let real_y = self.get_real_y(y);
             if real_x < 0
                 || real_x < START_SCREEN_X
                 || real_x > self.end_screen_x
@@ -444,11 +597,10 @@ impl ZoneEngine {
                 continue;
             }
 
-            sprites.push(self.tile_sheet.create_sprite_for(
-                "CHARACTER",
+            sprites.push(self.create_zone_sprite(
+                &self.facing_tile_id("CHARACTER", self.character_motions.facing(&character.id)),
                 real_x,
                 real_y,
-                self.sprite_i,
             ));
         }
 
@@ -458,7 +610,7 @@ impl ZoneEngine {
     fn get_stuff_sprites(&mut self) -> Vec<Sprite> {
         let mut sprites: Vec<Sprite> = vec![];
 
-        for stuff in self.stuffs.values().into_iter() {
+        for stuff in self.state.stuffs.values().into_iter() {
             let real_x = self.get_real_x(stuff.position().1 as i16 * TILE_WIDTH);
             let real_y = self.get_real_y(stuff.position().0 as i16 * TILE_HEIGHT);
             if real_x < 0
@@ -472,12 +624,7 @@ impl ZoneEngine {
 
             for class in stuff.get_classes().iter().rev() {
                 if self.tile_sheet.have_id(class) {
-                    sprites.push(self.tile_sheet.create_sprite_for(
-                        class,
-                        real_x,
-                        real_y,
-                        self.sprite_i,
-                    ));
+                    sprites.push(self.create_zone_sprite(class, real_x, real_y));
                     break;
                 }
             }
@@ -489,7 +636,7 @@ impl ZoneEngine {
     fn get_resource_sprites(&mut self) -> Vec<Sprite> {
         let mut sprites: Vec<Sprite> = vec![];
 
-        for resource in self.resources.iter() {
+        for resource in self.state.resources.iter() {
             let real_x = self.get_real_x(resource.position().1 as i16 * TILE_WIDTH);
             let real_y = self.get_real_y(resource.position().0 as i16 * TILE_HEIGHT);
             if real_x < 0
@@ -503,19 +650,9 @@ impl ZoneEngine {
 
             // TODO BS 20200722: use class system like for build and stuff
             if self.tile_sheet.have_id(&resource.id) {
-                sprites.push(self.tile_sheet.create_sprite_for(
-                    &resource.id,
-                    real_x,
-                    real_y,
-                    self.sprite_i,
-                ));
+                sprites.push(self.create_zone_sprite(&resource.id, real_x, real_y));
             } else {
-                sprites.push(self.tile_sheet.create_sprite_for(
-                    "RESOURCE_GENERIC",
-                    real_x,
-                    real_y,
-                    self.sprite_i,
-                ));
+                sprites.push(self.create_zone_sprite("RESOURCE_GENERIC", real_x, real_y));
             }
         }
 
@@ -525,7 +662,7 @@ impl ZoneEngine {
     fn get_build_sprites(&mut self, is_floor: bool) -> Vec<Sprite> {
         let mut sprites: Vec<Sprite> = vec![];
 
-        for build in self.builds.values().into_iter() {
+        for build in self.state.builds.values().into_iter() {
             let real_x = self.get_real_x(build.position().1 as i16 * TILE_WIDTH);
             let real_y = self.get_real_y(build.position().0 as i16 * TILE_HEIGHT);
             if real_x < 0
@@ -540,12 +677,7 @@ impl ZoneEngine {
 
             for class in build.get_classes().iter().rev() {
                 if self.tile_sheet.have_id(class) {
-                    sprites.push(self.tile_sheet.create_sprite_for(
-                        class,
-                        real_x,
-                        real_y,
-                        self.sprite_i,
-                    ));
+                    sprites.push(self.create_zone_sprite(class, real_x, real_y));
                     break;
                 }
             }
@@ -557,9 +689,20 @@ impl ZoneEngine {
     fn get_animated_corpses(&mut self) -> Vec<Sprite> {
         let mut sprites: Vec<Sprite> = vec![];
 
-        for animated_corpse in self.animated_corpses.values().into_iter() {
-            let real_x = self.get_real_x(animated_corpse.position().1 as i16 * TILE_WIDTH);
-            let real_y = self.get_real_y(animated_corpse.position().0 as i16 * TILE_HEIGHT);
+        let now = Instant::now();
+        for animated_corpse in self.state.animated_corpses.values().into_iter() {
+            let (x, y) = self.animated_corpse_motions.pixel_position(
+                &animated_corpse.id,
+                (
+                    animated_corpse.zone_row_i as i16,
+                    animated_corpse.zone_col_i as i16,
+                ),
+                now,
+                TILE_WIDTH,
+                TILE_HEIGHT,
+            );
+            let real_x = self.get_real_x(x);
+            let real_y = self.get_real_y(y);
             if real_x < 0
                 || real_x < START_SCREEN_X
                 || real_x > self.end_screen_x
@@ -569,52 +712,45 @@ impl ZoneEngine {
                 continue;
             }
 
-            sprites.push(self.tile_sheet.create_sprite_for(
-                &animated_corpse.type_,
+            sprites.push(self.create_zone_sprite(
+                &self.facing_tile_id(
+                    &animated_corpse.type_,
+                    self.animated_corpse_motions.facing(&animated_corpse.id),
+                ),
                 real_x,
                 real_y,
-                self.sprite_i,
             ));
         }
 
         sprites
     }
 
-    fn try_travel(&mut self, moves: &Vec<(i16, i16)>) -> Option<MainMessage> {
-        for try_player_move in moves.iter() {
-            // Test if next requested move is an travel
-            if let Some(next_position) = self
-                .player
-                .next_position(try_player_move.0, try_player_move.1)
-            {
-                if let Some(corner) =
-                    util::get_corner(&self.level, next_position.0, next_position.1)
-                {
-                    let w_row_i = self.player.world_position.0;
-                    let w_col_i = self.player.world_position.1;
-                    let (to_row_i, to_col_i) = match corner {
-                        util::CornerEnum::Top => (w_row_i - 1, w_col_i),
-                        util::CornerEnum::TopRight => (w_row_i - 1, w_col_i + 1),
-                        util::CornerEnum::Right => (w_row_i, w_col_i + 1),
-                        util::CornerEnum::BottomRight => (w_row_i + 1, w_col_i + 1),
-                        util::CornerEnum::Bottom => (w_row_i + 1, w_col_i),
-                        util::CornerEnum::BottomLeft => (w_row_i + 1, w_col_i - 1),
-                        util::CornerEnum::Left => (w_row_i, w_col_i - 1),
-                        util::CornerEnum::TopLeft => (w_row_i - 1, w_col_i - 1),
-                    };
+    fn try_travel(&mut self, next_tile: (i16, i16)) -> Option<MainMessage> {
+        // Test if next requested move is an travel
+        if let Some(corner) = util::get_corner(&self.state.level, next_tile.0, next_tile.1) {
+            let w_row_i = self.player.world_position.0;
+            let w_col_i = self.player.world_position.1;
+            let (to_row_i, to_col_i) = match corner {
+                util::CornerEnum::Top => (w_row_i - 1, w_col_i),
+                util::CornerEnum::TopRight => (w_row_i - 1, w_col_i + 1),
+                util::CornerEnum::Right => (w_row_i, w_col_i + 1),
+                util::CornerEnum::BottomRight => (w_row_i + 1, w_col_i + 1),
+                util::CornerEnum::Bottom => (w_row_i + 1, w_col_i),
+                util::CornerEnum::BottomLeft => (w_row_i + 1, w_col_i - 1),
+                util::CornerEnum::Left => (w_row_i, w_col_i - 1),
+                util::CornerEnum::TopLeft => (w_row_i - 1, w_col_i - 1),
+            };
 
-                    // If world coordinates don't exist, do nothing
-                    if let Some(_) = self.server.world.tile_id(to_row_i, to_col_i) {
-                        let url = format!(
-                            "/_describe/character/{}/move-to-zone/{}/{}",
-                            self.player.id, to_row_i, to_col_i
-                        );
-                        return Some(MainMessage::ToDescriptionWithUrl {
-                            url,
-                            back_url: None,
-                        });
-                    }
-                }
+            // If world coordinates don't exist, do nothing
+            if let Some(_) = self.server.world.tile_id(to_row_i, to_col_i) {
+                let url = format!(
+                    "/_describe/character/{}/move-to-zone/{}/{}",
+                    self.player.id, to_row_i, to_col_i
+                );
+                return Some(MainMessage::ToDescriptionWithUrl {
+                    url,
+                    back_url: None,
+                });
             }
         }
 
@@ -651,140 +787,672 @@ impl ZoneEngine {
         }
     }
 
-    fn there_is_build_not_browseable(&self, row_i: i16, col_i: i16) -> bool {
-        if let Some(build_ids) = self.builds_positions.get(&(row_i, col_i)) {
-            for build_id in build_ids.iter() {
-                // TODO BS: transport type
-                if !self
-                    .builds
-                    .get(build_id)
-                    .unwrap()
-                    .traversable
-                    .get("WALKING")
-                    .unwrap_or(&true)
-                {
-                    return true;
+    /// Player can step from a tile to a neighbour one (diagonal steps can't cut corners)
+    fn can_step(&self, from: (i16, i16), to: (i16, i16)) -> bool {
+        let transport_type = self.player.transport_type;
+        self.state.is_traversable(to.0, to.1, transport_type)
+            && (from.0 == to.0
+                || from.1 == to.1
+                || (self.state.is_traversable(from.0, to.1, transport_type)
+                    && self.state.is_traversable(to.0, from.1, transport_type)))
+    }
+
+    /// Next tile to step on, from keyboard direction (row, col) or followed path
+    fn next_player_tile(&self, direction: (i16, i16)) -> Option<(i16, i16)> {
+        let position = self.player_tile();
+
+        if direction != (0, 0) {
+            // Slide along obstacles when diagonal is blocked
+            for try_direction in [direction, (direction.0, 0), (0, direction.1)].iter() {
+                if *try_direction == (0, 0) {
+                    continue;
+                }
+                let next_tile = (position.0 + try_direction.0, position.1 + try_direction.1);
+                if self.can_step(position, next_tile) {
+                    return Some(next_tile);
                 }
             }
+            return None;
         }
-        false
-    }
 
-    fn try_player_moves(&mut self, moves: &Vec<(i16, i16)>) -> (bool, bool) {
-        let mut player_have_move = (false, false);
+        match self
+            .move_path
+            .as_ref()
+            .and_then(|move_path| move_path.first())
+        {
+            Some(next_tile) if self.can_step(position, *next_tile) => Some(*next_tile),
+            _ => None,
+        }
+    }
 
-        for try_player_move in moves.iter() {
-            let player_have_this_move = self.try_player_move(*try_player_move);
-            if player_have_this_move.0 {
-                player_have_move.0 = player_have_this_move.0
+    fn start_player_step(
+        &mut self,
+        next_tile: (i16, i16),
+        previous_step_end: Option<Instant>,
+        running: bool,
+        now: Instant,
+    ) {
+        let from = self.player_tile();
+        let duration = self.movement.player_step_duration(from, next_tile, running);
+        // Chain with previous step if it just ended, so moves stay smooth at low frame rate
+        let start = match previous_step_end {
+            Some(previous_step_end)
+                if now.saturating_duration_since(previous_step_end) < duration =>
+            {
+                previous_step_end
             }
-            if player_have_this_move.1 {
-                player_have_move.1 = player_have_this_move.1
+            _ => now,
+        };
+        if let Some(facing) = Facing::of_move(from, next_tile) {
+            self.player_tile_id = facing.tile_id("PLAYER");
+        }
+        self.player_step = Some(TileStep::new(from, next_tile, start, duration));
+        self.player.position = (next_tile.0 as i32, next_tile.1 as i32);
+        self.pending_moves.push(next_tile, now);
+
+        // Forget walked part of followed path
+        if let Some(move_path) = self.move_path.as_mut() {
+            if let Some(index) = move_path.iter().position(|tile| *tile == next_tile) {
+                move_path.drain(..=index);
             }
         }
 
-        player_have_move
+        self.socket.send(event::ZoneEvent {
+            event_type_name: String::from(event::PLAYER_MOVE),
+            event_type: event::ZoneEventType::PlayerMove {
+                to_row_i: self.player.position.0 as i32,
+                to_col_i: self.player.position.1 as i32,
+                character_id: String::from(self.player.id.as_str()),
+            },
+        });
+        self.around_wait = Some(Instant::now());
+        self.around_items_count = 0;
+        self.around_builds_count = 0;
+        self.around_characters_count = 0;
+        self.around_quick_actions = vec![];
     }
 
-    fn try_player_move(&mut self, move_: (i16, i16)) -> (bool, bool) {
-        let try_next_position = (self.player.x + move_.0, self.player.y + move_.1);
-        let try_next_tile = util::get_tile_position_for_xy(
-            self.tile_sheet.get_tile_width(),
-            self.tile_sheet.get_tile_height(),
-            try_next_position.0,
-            try_next_position.1,
+    /// Player moves are predicted locally: compare them with server position of player and
+    /// move player to server position if it was not predicted
+    fn reconcile_player_position(&mut self, server_tile: (i16, i16)) {
+        let now = Instant::now();
+        if self.pending_moves.reconcile(server_tile, now) == Reconciliation::Confirmed {
+            return;
+        }
+
+        let player_tile = self.player_tile();
+        if server_tile == player_tile {
+            return;
+        }
+        println!(
+            "Player position corrected by server from {:?} to {:?}",
+            player_tile, server_tile
         );
-        let next_tile_id = self.level.tile_id(try_next_tile.0, try_next_tile.1);
-        let tile_is_browseable = self.tiles.browseable(&next_tile_id);
-        let build_not_browseable =
-            self.there_is_build_not_browseable(try_next_tile.0, try_next_tile.1);
+        self.stop_move();
 
-        if tile_is_browseable && !build_not_browseable {
-            return self.player.try_move_by(move_.0, move_.1);
+        let current_position = match &self.player_step {
+            Some(player_step) => player_step.position(now),
+            None => (player_tile.0 as f32, player_tile.1 as f32),
+        };
+        let distance = (current_position.0 - server_tile.0 as f32)
+            .abs()
+            .max((current_position.1 - server_tile.1 as f32).abs());
+        self.player_step = if distance <= MAX_SMOOTH_CORRECTION_DISTANCE {
+            Some(TileStep::from_position(
+                current_position,
+                server_tile,
+                now,
+                self.movement.entity_move_duration,
+            ))
+        } else {
+            None
+        };
+        self.player.position = (server_tile.0 as i32, server_tile.1 as i32);
+        self.update_player_pixel_position(now);
+        self.around_wait = Some(now);
+    }
+
+    fn update_player_pixel_position(&mut self, now: Instant) {
+        let (x, y) = match &self.player_step {
+            Some(player_step) => player_step.pixel_position(now, TILE_WIDTH, TILE_HEIGHT),
+            None => (
+                self.player.position.1 as i16 * TILE_WIDTH,
+                self.player.position.0 as i16 * TILE_HEIGHT,
+            ),
+        };
+        self.player.x = x;
+        self.player.y = y;
+    }
+
+    /// Tween other characters and animated corpses moves, before event is applied on state
+    fn start_entity_motion(&mut self, event_type: &ZoneEventType) {
+        let now = Instant::now();
+        let duration = self.movement.entity_move_duration;
+
+        match event_type {
+            ZoneEventType::PlayerMove {
+                to_row_i,
+                to_col_i,
+                character_id,
+            } if character_id != &self.player.id => {
+                if let Some(character) = self.state.characters.get(character_id) {
+                    self.character_motions.start(
+                        character_id.clone(),
+                        (character.zone_row_i as i16, character.zone_col_i as i16),
+                        (*to_row_i as i16, *to_col_i as i16),
+                        now,
+                        duration,
+                    );
+                }
+            }
+            ZoneEventType::AnimatedCorpseMove {
+                to_row_i,
+                to_col_i,
+                animated_corpse_id,
+            } => {
+                if let Some(animated_corpse) = self.state.animated_corpses.get(animated_corpse_id) {
+                    self.animated_corpse_motions.start(
+                        *animated_corpse_id,
+                        (
+                            animated_corpse.zone_row_i as i16,
+                            animated_corpse.zone_col_i as i16,
+                        ),
+                        (*to_row_i as i16, *to_col_i as i16),
+                        now,
+                        duration,
+                    );
+                }
+            }
+            _ => {}
         }
-        (false, false)
     }
 
     fn update_zone_display(&mut self) {
-        let player_from_top_left_x = self.get_real_x(self.player.x) - self.start_screen_x;
-        let player_from_top_left_y = self.get_real_y(self.player.y) - self.start_screen_y;
-        let player_bottom_right_left_x = self.end_screen_x - self.get_real_x(self.player.x);
-        let player_from_bottom_right_y = self.end_screen_y - self.get_real_y(self.player.y);
-        let player_from_top_left_cols = player_from_top_left_x / TILE_WIDTH;
-        let player_from_top_left_rows = player_from_top_left_y / TILE_HEIGHT;
-        let player_from_bottom_right_cols = player_bottom_right_left_x / TILE_WIDTH;
-        let player_from_bottom_right_rows = player_from_bottom_right_y / TILE_HEIGHT;
+        self.camera.follow(
+            self.player.x as f32,
+            self.player.y as f32,
+            (self.end_screen_x - self.start_screen_x) as f32,
+            (self.end_screen_y - self.start_screen_y) as f32,
+        );
+    }
+
+    /// Back to camera following player, centered on it
+    fn snap_camera_to_player(&mut self) {
+        self.camera.set_free(false);
+        self.camera.center_on(
+            self.player.x as f32,
+            self.player.y as f32,
+            (self.end_screen_x - self.start_screen_x) as f32,
+            (self.end_screen_y - self.start_screen_y) as f32,
+        );
+    }
 
-        if player_from_top_left_cols < BORDERS_TO_SEE_PLAYER_LEN {
-            self.start_zone_col_i -= 1;
+    fn set_free_camera(&mut self) {
+        if !self.camera.is_free() {
+            self.camera.set_free(true);
+            self.receive_new_top_bar_message(
+                "Caméra libre : appuyez sur C ou Echap pour revenir au personnage".to_string(),
+                TopBarMessageType::NORMAL,
+                false,
+            );
         }
-        if player_from_top_left_rows < BORDERS_TO_SEE_PLAYER_LEN {
-            self.start_zone_row_i -= 1;
+    }
+
+    fn xy_to_zone_coords(&self, x: i16, y: i16) -> (i16, i16) {
+        self.camera
+            .tile_at((x - START_SCREEN_X) as f32, (y - START_SCREEN_Y) as f32)
+    }
+
+    /// Position is in zone area, not in menus or minimap
+    fn is_in_zone_area(&self, x: i16, y: i16, window: &Window) -> bool {
+        x > START_SCREEN_X
+            && y > START_SCREEN_Y
+            && y < (window.height() - QUICK_ACTION_ROW_HEIGHT as f32) as i16
+            && !(x < LEFT_MENU_WIDTH && y < LEFT_MENU_HEIGHT)
+            && self
+                .minimap_tile_at(Point::new(x as f32, y as f32))
+                .is_none()
+    }
+
+    /// Minimap is displayed at bottom right of zone area
+    fn minimap_area(&self) -> Rectangle<f32> {
+        Minimap::area(
+            &self.state,
+            Point::new(
+                self.end_screen_x as f32 - RIGHT_MENU_WIDTH as f32 - MINIMAP_MARGIN,
+                self.end_screen_y as f32 - QUICK_ACTION_ROW_HEIGHT as f32 - MINIMAP_MARGIN,
+            ),
+        )
+    }
+
+    fn minimap_tile_at(&self, point: Point) -> Option<(i16, i16)> {
+        if !self.minimap.visible {
+            return None;
         }
-        if player_from_bottom_right_cols < BORDERS_TO_SEE_PLAYER_LEN {
-            self.start_zone_col_i += 1;
+        Minimap::tile_at(&self.state, &self.minimap_area(), point)
+    }
+
+    /// Description of hovered tile, displayed after a short delay and expanded to all tile
+    /// entities after a longer one or when alt key is pressed
+    fn draw_tooltip(&mut self, frame: &mut Frame) {
+        let (tile, since) = match self.hovered_tile {
+            Some(hovered_tile) => hovered_tile,
+            None => return,
+        };
+        let elapsed = since.elapsed();
+        if elapsed < Duration::from_millis(TOOLTIP_DELAY_MS) {
+            return;
         }
-        if player_from_bottom_right_rows < BORDERS_TO_SEE_PLAYER_LEN {
-            self.start_zone_row_i += 1;
+        let expanded = self.tooltip_expand_pressed
+            || elapsed >= Duration::from_millis(TOOLTIP_EXPAND_DELAY_MS);
+        let lines = tooltip::tooltip_lines(&self.state, &self.player.id, tile, expanded);
+
+        self.load_tooltip_font(frame.gpu());
+        let font = match self.tooltip_font.as_mut() {
+            Some(font) => font,
+            None => return,
+        };
+
+        let sizes: Vec<(f32, f32)> = lines
+            .iter()
+            .map(|line| {
+                font.measure(graphics::Text {
+                    content: line,
+                    size: TOOLTIP_TEXT_SIZE,
+                    ..graphics::Text::default()
+                })
+            })
+            .collect();
+        let width = sizes.iter().map(|size| size.0).fold(0.0, f32::max) + 2.0 * TOOLTIP_PADDING;
+        let height = sizes.iter().map(|size| size.1).sum::<f32>() + 2.0 * TOOLTIP_PADDING;
+        // Keep tooltip inside window
+        let x = (self.cursor_position.x + TOOLTIP_CURSOR_OFFSET)
+            .min(self.end_screen_x as f32 - width)
+            .max(0.0);
+        let y = (self.cursor_position.y + TOOLTIP_CURSOR_OFFSET)
+            .min(self.end_screen_y as f32 - height)
+            .max(0.0);
+
+        let mut background = Mesh::new();
+        background.fill(
+            Shape::Rectangle(Rectangle {
+                x,
+                y,
+                width,
+                height,
+            }),
+            Color::new(0.0, 0.0, 0.0, 0.75),
+        );
+        background.draw(&mut frame.as_target());
+
+        let mut line_y = y + TOOLTIP_PADDING;
+        for (line, size) in lines.iter().zip(sizes.iter()) {
+            font.add(graphics::Text {
+                content: line,
+                position: Point::new(x + TOOLTIP_PADDING, line_y),
+                bounds: *size,
+                size: TOOLTIP_TEXT_SIZE,
+                color: Color::WHITE,
+                ..graphics::Text::default()
+            });
+            line_y += size.1;
         }
+        font.draw(&mut frame.as_target());
     }
 
-    fn xy_to_zone_coords(&self, x: i16, y: i16) -> (i16, i16) {
-        let x_from_start_screen = x - START_SCREEN_X;
-        let y_from_start_screen = y - START_SCREEN_Y;
-        let absolute_row_i = y_from_start_screen / TILE_HEIGHT;
-        let absolute_col_i = x_from_start_screen / TILE_WIDTH;
-        let row_i = absolute_row_i + self.start_zone_row_i;
-        let col_i = absolute_col_i + self.start_zone_col_i;
-
-        (row_i, col_i)
+    fn load_tooltip_font(&mut self, gpu: &mut Gpu) {
+        if self.tooltip_font.is_none() {
+            match Font::from_bytes(
+                gpu,
+                include_bytes!("../../resources/font/Inconsolata-Regular.ttf"),
+            ) {
+                Ok(font) => self.tooltip_font = Some(font),
+                Err(error) => eprintln!("Error when load tooltip font : {}", error),
+            }
+        }
+    }
+
+    fn open_context_menu(&mut self, tile: (i16, i16), position: Point) {
+        let items = context_menu::context_menu_items(
+            &self.state,
+            &self.player.id,
+            self.player_tile(),
+            tile,
+            &self.around_quick_actions,
+            self.move_path.is_some(),
+        );
+        self.context_menu = Some(ContextMenu::new(tile, position, items));
     }
 
-    fn get_move_modifier_for_around(
-        &self,
-        current_row_i: i16,
-        current_col_i: i16,
-        row_i: i16,
-        col_i: i16,
-    ) -> (i16, i16) {
-        if current_row_i - 1 == row_i && current_col_i - 1 == col_i {
-            return (-1, -1);
+    fn run_context_action(&mut self, action: ContextAction) -> Option<MainMessage> {
+        match action {
+            ContextAction::Describe(url) => {
+                return Some(MainMessage::ToDescriptionWithUrl {
+                    url,
+                    back_url: None,
+                });
+            }
+            ContextAction::QuickAction(link) => {
+                match self.send_quick_actions_transmitter.send(link) {
+                    Ok(_) => {}
+                    Err(error) => {
+                        eprintln!("Error when send link to quick actions channel: {}", error)
+                    }
+                }
+            }
+            ContextAction::MoveTo(tile) => self.request_move_to(tile),
+            ContextAction::StopMove => self.stop_move(),
         }
+        None
+    }
 
-        if current_row_i - 1 == row_i && current_col_i == col_i {
-            return (0, -1);
+    /// Context menu is kept inside window, its width is known once labels are measured
+    fn draw_context_menu(&mut self, frame: &mut Frame) {
+        if self.context_menu.is_none() {
+            return;
         }
+        self.load_tooltip_font(frame.gpu());
+        let font = match self.tooltip_font.as_mut() {
+            Some(font) => font,
+            None => return,
+        };
+        let menu = self.context_menu.as_mut().unwrap();
+
+        let sizes: Vec<(f32, f32)> = menu
+            .items
+            .iter()
+            .map(|item| {
+                font.measure(graphics::Text {
+                    content: &item.label,
+                    size: TOOLTIP_TEXT_SIZE,
+                    ..graphics::Text::default()
+                })
+            })
+            .collect();
+        menu.width = (sizes.iter().map(|size| size.0).fold(0.0, f32::max) + 2.0 * TOOLTIP_PADDING)
+            .max(CONTEXT_MENU_MIN_WIDTH);
+        menu.position = Point::new(
+            menu.position
+                .x
+                .min(self.end_screen_x as f32 - menu.width)
+                .max(0.0),
+            menu.position
+                .y
+                .min(self.end_screen_y as f32 - menu.height())
+                .max(0.0),
+        );
 
-        if current_row_i - 1 == row_i && current_col_i + 1 == col_i {
-            return (1, -1);
+        let mut background = Mesh::new();
+        background.fill(
+            Shape::Rectangle(Rectangle {
+                x: menu.position.x,
+                y: menu.position.y,
+                width: menu.width,
+                height: menu.height(),
+            }),
+            Color::new(0.0, 0.0, 0.0, 0.85),
+        );
+        if let Some(index) = menu.item_at(self.cursor_position) {
+            if menu.items[index].action.is_some() {
+                background.fill(
+                    Shape::Rectangle(Rectangle {
+                        x: menu.position.x,
+                        y: menu.position.y + index as f32 * context_menu::ITEM_HEIGHT,
+                        width: menu.width,
+                        height: context_menu::ITEM_HEIGHT,
+                    }),
+                    Color::new(0.3, 0.3, 0.5, 0.85),
+                );
+            }
+        }
+        background.stroke(
+            Shape::Rectangle(Rectangle {
+                x: menu.position.x,
+                y: menu.position.y,
+                width: menu.width,
+                height: menu.height(),
+            }),
+            Color::new(0.6, 0.6, 0.6, 1.0),
+            1.0,
+        );
+        background.draw(&mut frame.as_target());
+
+        for (index, (item, size)) in menu.items.iter().zip(sizes.iter()).enumerate() {
+            font.add(graphics::Text {
+                content: &item.label,
+                position: Point::new(
+                    menu.position.x + TOOLTIP_PADDING,
+                    menu.position.y + index as f32 * context_menu::ITEM_HEIGHT,
+                ),
+                bounds: (size.0, context_menu::ITEM_HEIGHT),
+                size: TOOLTIP_TEXT_SIZE,
+                // Entity titles are not clickable
+                color: if item.action.is_some() {
+                    Color::WHITE
+                } else {
+                    Color::new(0.7, 0.7, 0.7, 1.0)
+                },
+                ..graphics::Text::default()
+            });
         }
+        font.draw(&mut frame.as_target());
+    }
 
-        if current_row_i == row_i && current_col_i - 1 == col_i {
-            return (-1, 0);
+    fn draw_minimap(&mut self, frame: &mut Frame) {
+        if !self.minimap.visible {
+            return;
+        }
+        let area = self.minimap_area();
+        self.minimap.prepare(frame.gpu(), &self.state);
+
+        let mut background = Mesh::new();
+        background.fill(Shape::Rectangle(area), Color::new(0.0, 0.0, 0.0, 0.6));
+        background.draw(&mut frame.as_target());
+        self.minimap.draw(&mut frame.as_target(), &area);
+
+        let mut mesh = Mesh::new();
+        let dot_radius = (Minimap::tile_size(&self.state) / 2.0).max(1.5);
+        for stuff in self.state.stuffs.values() {
+            mesh.fill(
+                Shape::Circle {
+                    center: Minimap::tile_center(
+                        &self.state,
+                        &area,
+                        (stuff.zone_row_i as i16, stuff.zone_col_i as i16),
+                    ),
+                    radius: dot_radius * 0.75,
+                },
+                Color::new(1.0, 0.85, 0.2, 1.0),
+            );
+        }
+        for character in self.state.characters.values() {
+            if character.id == self.player.id {
+                continue;
+            }
+            mesh.fill(
+                Shape::Circle {
+                    center: Minimap::tile_center(
+                        &self.state,
+                        &area,
+                        (character.zone_row_i as i16, character.zone_col_i as i16),
+                    ),
+                    radius: dot_radius,
+                },
+                Color::new(0.9, 0.2, 0.2, 1.0),
+            );
         }
+        mesh.fill(
+            Shape::Circle {
+                center: Minimap::tile_center(&self.state, &area, self.player_tile()),
+                radius: dot_radius * 1.5,
+            },
+            Color::WHITE,
+        );
 
-        if current_row_i == row_i && current_col_i + 1 == col_i {
-            return (1, 0);
+        // Part of zone visible on screen
+        let (first_row_i, first_col_i) = self.camera.tile_at(0.0, 0.0);
+        let (last_row_i, last_col_i) = self.camera.tile_at(
+            (self.end_screen_x - self.start_screen_x) as f32,
+            (self.end_screen_y - self.start_screen_y) as f32,
+        );
+        let tile_size = Minimap::tile_size(&self.state);
+        let top_left = Point::new(
+            area.x + first_col_i as f32 * tile_size,
+            area.y + first_row_i as f32 * tile_size,
+        );
+        let bottom_right = Point::new(
+            area.x + (last_col_i + 1) as f32 * tile_size,
+            area.y + (last_row_i + 1) as f32 * tile_size,
+        );
+        mesh.stroke(
+            Shape::Polyline {
+                points: vec![
+                    top_left,
+                    Point::new(bottom_right.x, top_left.y),
+                    bottom_right,
+                    Point::new(top_left.x, bottom_right.y),
+                    top_left,
+                ],
+            },
+            Color::new(1.0, 1.0, 1.0, 0.6),
+            1.0,
+        );
+        mesh.draw(&mut frame.as_target());
+    }
+
+    /// Follow path to given tile, or display impossible move marker
+    fn request_move_to(&mut self, to: (i16, i16)) {
+        match self
+            .state
+            .find_path(self.player_tile(), to, self.player.transport_type)
+        {
+            Some(path) => {
+                self.move_path = Some(path);
+                self.impossible_move = None;
+            }
+            None => {
+                self.stop_move();
+                self.impossible_move = Some((to, Instant::now()));
+            }
         }
+    }
+
+    fn stop_move(&mut self) {
+        self.move_path = None;
+    }
+
+    fn player_tile(&self) -> (i16, i16) {
+        (self.player.position.0 as i16, self.player.position.1 as i16)
+    }
+
+    /// Preview path to hovered tile when a click on it would move player
+    fn update_path_preview(&mut self, hovered_tile: Option<(i16, i16)>) {
+        let hovered_tile = match hovered_tile {
+            Some(hovered_tile)
+                if self.request_clicks.is_none()
+                    && self.hover_character_id.is_none()
+                    && self.hover_build_id.is_none() =>
+            {
+                hovered_tile
+            }
+            _ => {
+                self.path_preview = None;
+                return;
+            }
+        };
 
-        if current_row_i == row_i && current_col_i == col_i {
-            return (0, 0);
+        let from = self.player_tile();
+        if let Some(path_preview) = &self.path_preview {
+            if path_preview.from == from && path_preview.to == hovered_tile {
+                return;
+            }
         }
+        self.path_preview = Some(PathPreview {
+            from,
+            to: hovered_tile,
+            path: self
+                .state
+                .find_path(from, hovered_tile, self.player.transport_type),
+        });
+    }
+
+    fn tile_center(&self, position: (i16, i16)) -> Point {
+        Point::new(
+            self.get_real_x(position.1 * TILE_WIDTH) as f32
+                + TILE_WIDTH as f32 * self.camera.zoom() / 2.0,
+            self.get_real_y(position.0 * TILE_HEIGHT) as f32
+                + TILE_HEIGHT as f32 * self.camera.zoom() / 2.0,
+        )
+    }
 
-        if current_row_i + 1 == row_i && current_col_i - 1 == col_i {
-            return (-1, 1);
+    fn add_path_markers(&self, mesh: &mut Mesh, path: &[(i16, i16)], color: Color) {
+        for position in path.iter() {
+            mesh.fill(
+                Shape::Circle {
+                    center: self.tile_center(*position),
+                    radius: PATH_MARKER_RADIUS,
+                },
+                color,
+            );
+        }
+        if let Some(destination) = path.last() {
+            mesh.stroke(
+                Shape::Circle {
+                    center: self.tile_center(*destination),
+                    radius: PATH_DESTINATION_RADIUS,
+                },
+                color,
+                2.0,
+            );
         }
+    }
 
-        if current_row_i + 1 == row_i && current_col_i == col_i {
-            return (0, 1);
+    fn add_impossible_move_marker(&self, mesh: &mut Mesh, position: (i16, i16)) {
+        let center = self.tile_center(position);
+        let size = PATH_DESTINATION_RADIUS;
+        for (from, to) in [
+            ((-size, -size), (size, size)),
+            ((-size, size), (size, -size)),
+        ]
+        .iter()
+        {
+            mesh.stroke(
+                Shape::Polyline {
+                    points: vec![
+                        Point::new(center.x + from.0, center.y + from.1),
+                        Point::new(center.x + to.0, center.y + to.1),
+                    ],
+                },
+                Color::RED,
+                3.0,
+            );
         }
+    }
+
+    /// Draw path followed by player, hovered tile path preview and impossible move markers
+    fn draw_path_markers(&self, frame: &mut Frame) {
+        let mut mesh = Mesh::new();
 
-        if current_row_i + 1 == row_i && current_col_i + 1 == col_i {
-            return (1, 1);
+        if let Some(move_path) = &self.move_path {
+            self.add_path_markers(&mut mesh, move_path, Color::new(1.0, 1.0, 1.0, 0.8));
+        }
+        if let Some(path_preview) = &self.path_preview {
+            match &path_preview.path {
+                Some(path) => {
+                    self.add_path_markers(&mut mesh, path, Color::new(1.0, 1.0, 0.6, 0.5))
+                }
+                None => self.add_impossible_move_marker(&mut mesh, path_preview.to),
+            }
+        }
+        if let Some((position, since)) = self.impossible_move {
+            if since.elapsed() < Duration::from_millis(IMPOSSIBLE_MOVE_MARKER_MS) {
+                self.add_impossible_move_marker(&mut mesh, position);
+            }
         }
 
-        eprintln!("Around position must used !");
-        (0, 0)
+        if !mesh.is_empty() {
+            mesh.draw(&mut frame.as_target());
+        }
     }
 
     fn apply_chat_text_buffer(&mut self, text_buffer: String) {
@@ -815,7 +1483,7 @@ impl Engine for ZoneEngine {
         frame.clear(Color::BLACK);
         let mut sprites: Vec<Sprite> = vec![];
 
-        sprites.extend(self.get_zone_sprites(Some(self.level.world_tile_type_id.clone())));
+        sprites.extend(self.get_zone_sprites(Some(self.state.level.world_tile_type_id.clone())));
         sprites.extend(self.get_zone_sprites(None));
         sprites.extend(self.get_build_sprites(true));
         sprites.extend(self.get_build_sprites(false));
@@ -845,12 +1513,7 @@ impl Engine for ZoneEngine {
                         let real_x = self.get_real_x(cursor_col_i * TILE_WIDTH);
                         let real_y = self.get_real_y(cursor_row_i * TILE_HEIGHT);
 
-                        sprites.push(self.tile_sheet.create_sprite_for(
-                            class,
-                            real_x,
-                            real_y,
-                            self.sprite_i,
-                        ));
+                        sprites.push(self.create_zone_sprite(class, real_x, real_y));
                         break;
                     }
                 }
@@ -860,9 +1523,13 @@ impl Engine for ZoneEngine {
         self.tile_sheet_batch.clear();
         self.tile_sheet_batch.extend(sprites);
         self.tile_sheet_batch.draw(&mut frame.as_target());
+        self.draw_path_markers(frame);
+        self.draw_minimap(frame);
+        self.draw_tooltip(frame);
+        self.draw_context_menu(frame);
 
         if let Some(hover_character_id) = &self.hover_character_id {
-            if let Some(character) = self.characters.get(hover_character_id) {
+            if let Some(character) = self.state.characters.get(hover_character_id) {
                 let avatar_uuid =
                     if character.avatar_is_validated && character.avatar_uuid.is_some() {
                         if let Some(avatar_uuid) = &character.avatar_uuid {
@@ -874,8 +1541,15 @@ impl Engine for ZoneEngine {
                         "0000".to_string()
                     };
 
-                let real_x = self.get_real_x(character.position().1 as i16 * TILE_WIDTH);
-                let real_y = self.get_real_y(character.position().0 as i16 * TILE_HEIGHT);
+                let (x, y) = self.character_motions.pixel_position(
+                    &character.id,
+                    (character.zone_row_i as i16, character.zone_col_i as i16),
+                    Instant::now(),
+                    TILE_WIDTH,
+                    TILE_HEIGHT,
+                );
+                let real_x = self.get_real_x(x);
+                let real_y = self.get_real_y(y);
 
                 if let Some((avatar_batch, width, height)) = self.avatars.get_mut(&avatar_uuid) {
                     avatar_batch.clear();
@@ -947,58 +1621,42 @@ impl Engine for ZoneEngine {
             }
         }
 
+        self.proceed_connection_state();
+        self.proceed_resync();
+
         for event in self.socket.pending_events() {
-            // TODO Move code ailleurs
-            match event.event_type {
-                ZoneEventType::PlayerMove {
-                    to_row_i,
-                    to_col_i,
-                    character_id,
-                } => {
-                    if let Some(mut moved_character) =
-                        self.characters.get_mut(character_id.as_str())
-                    {
-                        moved_character.zone_row_i = to_row_i;
-                        moved_character.zone_col_i = to_col_i;
-                    } else if character_id != self.player.id {
-                        eprintln!("Unknown character {} for move", character_id)
-                    }
-                }
-                ZoneEventType::CharacterEnter {
-                    zone_row_i,
-                    zone_col_i,
-                    character_id,
-                } => {
-                    println!("{} is enter in zone", &character_id);
-                    self.characters.insert(
-                        character_id.clone(),
-                        Character {
-                            id: character_id.clone(),
-                            zone_row_i,
-                            zone_col_i,
-                            // FIXME BS NOW : avatar_uuid & avatar_is_validated,
-                            avatar_uuid: None,
-                            avatar_is_validated: false,
-                        },
-                    );
+            if let ZoneEventType::ZoneTileReplace { .. } | ZoneEventType::NewBuild { .. } =
+                &event.event_type
+            {
+                self.minimap.invalidate();
+            }
+            if let ZoneEventType::PlayerMove {
+                to_row_i,
+                to_col_i,
+                character_id,
+            } = &event.event_type
+            {
+                if character_id == &self.player.id {
+                    self.reconcile_player_position((*to_row_i as i16, *to_col_i as i16));
                 }
-                ZoneEventType::CharacterExit { character_id } => {
-                    if let None = self.characters.remove(&character_id) {
-                        if &character_id == &self.player.id {
-                            println!("Reload zone because player left on server side");
-                            return Some(MainMessage::DescriptionToZone {
-                                request_clicks: None,
-                            });
-                        } else {
-                            println!(
-                                "{} left zone but was not in list of characters",
-                                &character_id
-                            );
-                        }
-                    } else {
-                        println!("{} exit from zone", &character_id);
-                    }
+            }
+            self.start_entity_motion(&event.event_type);
+            let applied = self.state.apply(&event);
+            if applied == Applied::Changed && self.pending_resync.is_some() {
+                self.resync_events.push(event.clone());
+            }
+            match applied {
+                Applied::Changed => continue,
+                Applied::PlayerLeft => {
+                    println!("Reload zone because player left on server side");
+                    return Some(MainMessage::DescriptionToZone {
+                        request_clicks: None,
+                    });
                 }
+                Applied::Ignored => {}
+            }
+
+            match event.event_type {
                 ZoneEventType::ThereIsAround {
                     stuff_count,
                     resource_count,
@@ -1012,35 +1670,9 @@ impl Engine for ZoneEngine {
                     self.around_quick_actions = quick_actions;
                 }
                 ZoneEventType::NewResumeText { resume } => {
-                    self.resume_text = resume;
+                    self.resume_text = resume.items;
                     self.update_link_button_data();
                 }
-                ZoneEventType::NewBuild { build } => {
-                    self.builds.insert(build.id, build);
-                    self.update_builds_data();
-                }
-                ZoneEventType::ZoneTileReplace {
-                    row_i,
-                    col_i,
-                    new_tile_id,
-                } => {
-                    println!("Replace tile at {}:{} with {}", row_i, col_i, &new_tile_id);
-                    self.level.rows[row_i as usize].cols[col_i as usize] = new_tile_id;
-                }
-                ZoneEventType::AnimatedCorpseMove {
-                    to_row_i,
-                    to_col_i,
-                    animated_corpse_id,
-                } => {
-                    if let Some(mut moved_animated_corpse) =
-                        self.animated_corpses.get_mut(&animated_corpse_id)
-                    {
-                        moved_animated_corpse.zone_row_i = to_row_i;
-                        moved_animated_corpse.zone_col_i = to_col_i;
-                    } else {
-                        eprintln!("Unknown animated corpse {} for move", animated_corpse_id)
-                    }
-                }
                 ZoneEventType::NewChatMessage {
                     character_id: _,
                     conversation_id,
@@ -1112,54 +1744,17 @@ impl Engine for ZoneEngine {
                 ZoneEventType::TopBarMessage { message, type_ } => {
                     self.receive_new_top_bar_message(message, type_, false);
                 }
-                ZoneEventType::ZoneGroundResourceRemoved {
-                    row_i,
-                    col_i,
-                    resource_id,
-                } => self.resources.retain(|resource| {
-                    let delete = {
-                        resource.zone_row_i == row_i
-                            && resource.zone_col_i == col_i
-                            && resource.id == resource_id
-                    };
-                    !delete
-                }),
-                ZoneEventType::ZoneGroundStuffRemoved { stuff_id } => {
-                    self.stuffs.retain(|_, stuff| stuff.id != stuff_id);
-                }
-                ZoneEventType::ZoneGroundResourceAdded {
-                    row_i,
-                    col_i,
-                    resource_id,
-                } => {
-                    self.resources.push(Resource {
-                        id: resource_id,
-                        zone_row_i: row_i,
-                        zone_col_i: col_i,
-                    });
-                }
-                ZoneEventType::ZoneGroundStuffAdded {
-                    id_,
-                    stuff_id,
-                    zone_row_i,
-                    zone_col_i,
-                    classes,
-                } => {
-                    self.stuffs.insert(
-                        id_.to_string(),
-                        Stuff {
-                            id: id_,
-                            stuff_id,
-                            zone_row_i,
-                            zone_col_i,
-                            classes,
-                        },
-                    );
-                }
                 _ => println!("unknown event type {:?}", &event.event_type),
             }
         }
 
+        let characters = &self.state.characters;
+        self.character_motions
+            .retain(|character_id| characters.contains_key(character_id));
+        let animated_corpses = &self.state.animated_corpses;
+        self.animated_corpse_motions
+            .retain(|animated_corpse_id| animated_corpses.contains_key(animated_corpse_id));
+
         let messages = self.proceed_quick_action_responses();
 
         for (msg, type_) in messages {
@@ -1170,8 +1765,79 @@ impl Engine for ZoneEngine {
     }
 
     fn interact(&mut self, input: &mut MyGameInput, window: &mut Window) -> Option<MainMessage> {
-        let mut try_player_moves: Vec<(i16, i16)> = vec![];
+        let mut direction: (i16, i16) = (0, 0);
+        let mut running = false;
         self.cursor_position = input.cursor_position.clone();
+        let frame_duration = self.last_interact_at.elapsed();
+        self.last_interact_at = Instant::now();
+        let cursor_x = input.cursor_position.x.round() as i16;
+        let cursor_y = input.cursor_position.y.round() as i16;
+
+        if input.mouse_wheel.y != 0.0 {
+            if self.is_in_zone_area(cursor_x, cursor_y, window) {
+                self.camera.zoom_by(
+                    input.mouse_wheel.y.signum() as i32,
+                    (
+                        input.cursor_position.x - START_SCREEN_X as f32,
+                        input.cursor_position.y - START_SCREEN_Y as f32,
+                    ),
+                );
+            }
+            input.mouse_wheel = Point::new(0.0, 0.0);
+        }
+
+        // Drag camera with middle button
+        if input.mouse_buttons_pressed.contains(&mouse::Button::Middle) {
+            if let Some(drag_from) = self.camera_drag_from {
+                if drag_from != input.cursor_position {
+                    self.set_free_camera();
+                    self.camera.pan_by(
+                        drag_from.x - input.cursor_position.x,
+                        drag_from.y - input.cursor_position.y,
+                    );
+                }
+                self.camera_drag_from = Some(input.cursor_position);
+            } else if self.is_in_zone_area(cursor_x, cursor_y, window) {
+                self.camera_drag_from = Some(input.cursor_position);
+            }
+        } else {
+            self.camera_drag_from = None;
+        }
+
+        // Right click outside zone area, on player or on empty ground while moving stops the
+        // move, elsewhere it opens context menu (which proposes to stop the move)
+        if input.mouse_buttons_pressed.remove(&mouse::Button::Right) {
+            self.context_menu = None;
+            if !self.is_in_zone_area(cursor_x, cursor_y, window) {
+                self.stop_move();
+            } else if self.request_clicks.is_none() {
+                let tile = self.xy_to_zone_coords(cursor_x, cursor_y);
+                if context_menu::right_click_stops_move(
+                    &self.state,
+                    self.player_tile(),
+                    tile,
+                    self.move_path.is_some(),
+                ) {
+                    self.stop_move();
+                } else {
+                    self.open_context_menu(tile, input.cursor_position);
+                }
+            }
+        }
+
+        // Click while context menu is open run hovered action or only close menu
+        if let Some(menu) = &self.context_menu {
+            if input.mouse_buttons_pressed.contains(&mouse::Button::Left) {
+                input.mouse_buttons_pressed.clear();
+                let action = menu.action_at(input.cursor_position);
+                self.context_menu = None;
+                if let Some(action) = action {
+                    if let Some(main_message) = self.run_context_action(action) {
+                        return Some(main_message);
+                    }
+                }
+            }
+        }
 
         if input.mouse_buttons_pressed.contains(&mouse::Button::Left) {
             let click_x = input.cursor_position.x.round() as i16;
@@ -1179,12 +1845,12 @@ impl Engine for ZoneEngine {
             let (to_row_i, to_col_i) = self.xy_to_zone_coords(click_x, click_y);
             input.mouse_buttons_pressed.clear();
 
+            if let Some(minimap_tile) = self.minimap_tile_at(input.cursor_position) {
+                if self.request_clicks.is_none() {
+                    self.request_move_to(minimap_tile);
+                }
             // Is that a move/requested click ? Must not be in menu
-            if click_x > START_SCREEN_X
-                && click_y > START_SCREEN_Y
-                && click_y < (window.height() - QUICK_ACTION_ROW_HEIGHT as f32) as i16
-                && !(click_x < LEFT_MENU_WIDTH && click_y < LEFT_MENU_HEIGHT)
-            {
+            } else if self.is_in_zone_area(click_x, click_y, window) {
                 if let Some(request_clicks) = &self.request_clicks {
                     // REQUESTED CLICK
                     self.socket.send(event::ZoneEvent {
@@ -1231,42 +1897,7 @@ impl Engine for ZoneEngine {
                     }
 
                     // MOVE
-                    let player_position =
-                        (self.player.position.0 as i16, self.player.position.1 as i16);
-                    if let Some(result) = astar(
-                        &player_position,
-                        |(row_i, col_i)| {
-                            self.level
-                                .get_successors(&self.tiles, row_i.clone(), col_i.clone())
-                        },
-                        |(row_i, col_i)| {
-                            ((absdiff(row_i.clone(), to_row_i) + absdiff(col_i.clone(), to_col_i))
-                                as u32)
-                                / 3
-                        },
-                        |(row_i, col_i)| (row_i.clone(), col_i) == (to_row_i, &to_col_i),
-                    ) {
-                        let mut moves = vec![];
-                        let mut current_position = player_position.clone();
-                        self.player.x = current_position.1 * TILE_HEIGHT;
-                        self.player.y = current_position.0 * TILE_WIDTH;
-                        for next_move in result.0 {
-                            let modifier = self.get_move_modifier_for_around(
-                                current_position.0,
-                                current_position.1,
-                                next_move.0,
-                                next_move.1,
-                            );
-                            // FIXME BS: Can work only with tile squares !
-                            for _ in 0..TILE_WIDTH {
-                                moves.push(modifier.clone());
-                            }
-                            current_position = next_move;
-                        }
-                        self.move_requested = Some(moves);
-                    } else {
-                        println!("Impossible move");
-                    }
+                    self.request_move_to((to_row_i, to_col_i));
                 }
             }
         }
@@ -1276,30 +1907,28 @@ impl Engine for ZoneEngine {
                 self.apply_chat_text_buffer(input.text_buffer.clone());
                 input.text_buffer = String::new();
             }
-            let move_modifier = if input.keys_pressed.contains(&keyboard::KeyCode::LShift)
-                || input.keys_pressed.contains(&keyboard::KeyCode::RShift)
-            {
-                3
-            } else {
-                1
-            };
+            running = input.keys_pressed.contains(&keyboard::KeyCode::LShift)
+                || input.keys_pressed.contains(&keyboard::KeyCode::RShift);
             if input.keys_pressed.contains(&keyboard::KeyCode::Right) {
-                try_player_moves.push((move_modifier, 0));
-                self.move_requested = None;
-                self.player_tile_id = String::from("PLAYER");
+                direction.1 += 1;
             }
             if input.keys_pressed.contains(&keyboard::KeyCode::Left) {
-                try_player_moves.push((-move_modifier, 0));
-                self.move_requested = None;
-                self.player_tile_id = String::from("PLAYER_LEFT");
+                direction.1 -= 1;
             }
             if input.keys_pressed.contains(&keyboard::KeyCode::Up) {
-                try_player_moves.push((0, -move_modifier));
-                self.move_requested = None;
+                direction.0 -= 1;
             }
             if input.keys_pressed.contains(&keyboard::KeyCode::Down) {
-                try_player_moves.push((0, move_modifier));
-                self.move_requested = None;
+                direction.0 += 1;
+            }
+            if self.camera.is_free() && direction != (0, 0) {
+                // Arrow keys move free camera instead of player
+                let distance = CAMERA_PAN_SPEED * frame_duration.as_secs_f32();
+                self.camera
+                    .pan_by(direction.1 as f32 * distance, direction.0 as f32 * distance);
+                direction = (0, 0);
+            } else if direction != (0, 0) {
+                self.stop_move();
             }
         }
 
@@ -1307,17 +1936,35 @@ impl Engine for ZoneEngine {
             Some(keyboard::KeyCode::Escape) => {
                 input.key_code = None;
 
-                if self.request_clicks.is_some() {
+                if self.context_menu.is_some() {
+                    self.context_menu = None;
+                } else if self.request_clicks.is_some() {
                     self.request_clicks = None;
                     self.top_bar = None;
                 } else if self.displaying_chat {
                     self.top_bar = None;
                     self.chat = None;
                     self.displaying_chat = false;
+                } else if self.camera.is_free() {
+                    self.snap_camera_to_player();
+                } else if self.move_path.is_some() {
+                    self.stop_move();
                 } else {
                     return Some(MainMessage::ToExit);
                 }
             }
+            Some(keyboard::KeyCode::C) if self.chat.is_none() => {
+                input.key_code = None;
+                if self.camera.is_free() {
+                    self.snap_camera_to_player();
+                } else {
+                    self.set_free_camera();
+                }
+            }
+            Some(keyboard::KeyCode::M) if self.chat.is_none() => {
+                input.key_code = None;
+                self.minimap.visible = !self.minimap.visible;
+            }
             Some(keyboard::KeyCode::Return) => {
                 if !self.displaying_chat && !self.display_chat_required {
                     self.display_chat_required = true;
@@ -1349,80 +1996,55 @@ impl Engine for ZoneEngine {
             _ => {}
         }
 
-        if let Some(main_message) = self.try_travel(&try_player_moves) {
-            return Some(main_message);
-        }
-
-        if self.player_move_ticker.tick() {
-            if let Some(move_requested) = self.move_requested.as_ref() {
-                if let Some(next_move) = move_requested.iter().next() {
-                    try_player_moves.push(*next_move);
-                    self.move_requested.as_mut().unwrap().remove(0);
-                } else {
-                    self.move_requested = None;
+        let now = Instant::now();
+        let step_finished = match &self.player_step {
+            Some(player_step) => player_step.is_finished(now),
+            None => true,
+        };
+        let mut step_started = false;
+        if step_finished {
+            if direction != (0, 0) {
+                let player_tile = self.player_tile();
+                let next_tile = (player_tile.0 + direction.0, player_tile.1 + direction.1);
+                if let Some(main_message) = self.try_travel(next_tile) {
+                    return Some(main_message);
                 }
             }
 
-            let mut player_have_move = (false, false);
-            if try_player_moves.len() != 0 {
-                player_have_move = self.try_player_moves(&try_player_moves);
-                if !player_have_move.0 {
-                    // try diags is any
-                    let mut additional_try_player_mode: (i16, i16) = (0, 0);
-                    if try_player_moves.len() > 1 {
-                        for try_player_move in try_player_moves.iter() {
-                            additional_try_player_mode.0 += try_player_move.0;
-                            additional_try_player_mode.1 += try_player_move.1;
-                        }
-                    }
-                    player_have_move = self.try_player_move(additional_try_player_mode);
+            let previous_step_end = self.player_step.take().map(|player_step| player_step.end());
+            match self.next_player_tile(direction) {
+                Some(next_tile) => {
+                    self.start_player_step(next_tile, previous_step_end, running, now);
+                    step_started = true;
                 }
+                // Path is blocked (or ended)
+                None => self.stop_move(),
             }
-            if player_have_move.1 {
-                // NOTE: There is problem with moves and we send ws move on NOTHING tile :/ skip it
-                let next_tile_id = self
-                    .level
-                    .tile_id(self.player.position.0 as i16, self.player.position.1 as i16);
-                if self.tiles.browseable(&next_tile_id) {
+        }
+        self.update_player_pixel_position(now);
+
+        if !step_started {
+            if let Some(around_wait) = self.around_wait.as_ref() {
+                if around_wait.elapsed().as_millis() > 350 {
+                    self.around_wait = None;
                     self.socket.send(event::ZoneEvent {
-                        event_type_name: String::from(event::PLAYER_MOVE),
-                        event_type: event::ZoneEventType::PlayerMove {
-                            to_row_i: self.player.position.0 as i32,
-                            to_col_i: self.player.position.1 as i32,
+                        event_type_name: String::from(event::CLIENT_REQUIRE_AROUND),
+                        event_type: event::ZoneEventType::ClientRequireAround {
+                            zone_row_i: self.player.position.0 as i32,
+                            zone_col_i: self.player.position.1 as i32,
                             character_id: String::from(self.player.id.as_str()),
                         },
                     });
-                    self.around_wait = Some(Instant::now());
-                    self.around_items_count = 0;
-                    self.around_builds_count = 0;
-                    self.around_characters_count = 0;
-                    self.around_quick_actions = vec![];
                 }
-            } else {
-                if let Some(around_wait) = self.around_wait.as_ref() {
-                    if around_wait.elapsed().as_millis() > 350 {
-                        self.around_wait = None;
-                        self.socket.send(event::ZoneEvent {
-                            event_type_name: String::from(event::CLIENT_REQUIRE_AROUND),
-                            event_type: event::ZoneEventType::ClientRequireAround {
-                                zone_row_i: self.player.position.0 as i32,
-                                zone_col_i: self.player.position.1 as i32,
-                                character_id: String::from(self.player.id.as_str()),
-                            },
-                        });
-                    }
-                }
-            }
-            if !player_have_move.0 {
-                self.move_requested = None;
             }
         }
 
         while let Some(avatar_to_load) = self.avatars_to_load.pop() {
-            match graphics::Image::new(
-                window.gpu(),
-                format!("cache/character_avatar__zone_thumb__{}.png", avatar_to_load),
-            ) {
+            let avatar_path = self.server.client.media_cache().path(
+                MediaKind::Media,
+                &format!("character_avatar__zone_thumb__{}.png", avatar_to_load),
+            );
+            match graphics::Image::new(window.gpu(), avatar_path) {
                 Ok(image) => {
                     let width = image.width();
                     let height = image.height();
@@ -1436,15 +2058,17 @@ impl Engine for ZoneEngine {
             };
         }
 
+        let tile_width = TILE_WIDTH as f32 * self.camera.zoom();
+        let tile_height = TILE_HEIGHT as f32 * self.camera.zoom();
         let mut hover_character = false;
-        for (character_id, character) in &self.characters {
+        for (character_id, character) in &self.state.characters {
             let real_x = self.get_real_x(character.position().1 as i16 * TILE_WIDTH) as f32;
             let real_y = self.get_real_y(character.position().0 as i16 * TILE_HEIGHT) as f32;
 
             if input.cursor_position.x > real_x
-                && input.cursor_position.x <= (real_x + TILE_WIDTH as f32)
+                && input.cursor_position.x <= (real_x + tile_width)
                 && input.cursor_position.y > real_y
-                && input.cursor_position.y <= (real_y + TILE_HEIGHT as f32)
+                && input.cursor_position.y <= (real_y + tile_height)
             {
                 hover_character = true;
                 self.hover_character_id = Some(character_id.clone());
@@ -1457,14 +2081,14 @@ impl Engine for ZoneEngine {
 
         let mut hover_build = false;
         if !hover_character {
-            for (build_id, build) in &self.builds {
+            for (build_id, build) in &self.state.builds {
                 let real_x = self.get_real_x(build.position().1 as i16 * TILE_WIDTH) as f32;
                 let real_y = self.get_real_y(build.position().0 as i16 * TILE_HEIGHT) as f32;
 
                 if input.cursor_position.x > real_x
-                    && input.cursor_position.x <= (real_x + TILE_WIDTH as f32)
+                    && input.cursor_position.x <= (real_x + tile_width)
                     && input.cursor_position.y > real_y
-                    && input.cursor_position.y <= (real_y + TILE_HEIGHT as f32)
+                    && input.cursor_position.y <= (real_y + tile_height)
                 {
                     hover_build = true;
                     self.hover_build_id = Some(build_id.clone());
@@ -1476,6 +2100,23 @@ impl Engine for ZoneEngine {
             self.hover_build_id = None;
         }
 
+        let hovered_tile =
+            if self.context_menu.is_none() && self.is_in_zone_area(cursor_x, cursor_y, window) {
+                Some(self.xy_to_zone_coords(cursor_x, cursor_y))
+            } else {
+                None
+            };
+        self.update_path_preview(hovered_tile);
+        self.hovered_tile = match (hovered_tile, self.hovered_tile) {
+            (Some(tile), Some((previous_tile, since))) if tile == previous_tile => {
+                Some((tile, since))
+            }
+            (Some(tile), _) => Some((tile, Instant::now())),
+            (None, _) => None,
+        };
+        self.tooltip_expand_pressed = input.keys_pressed.contains(&keyboard::KeyCode::LAlt)
+            || input.keys_pressed.contains(&keyboard::KeyCode::RAlt);
+
         None
     }
 
@@ -1639,6 +2280,11 @@ impl Engine for ZoneEngine {
             Message::DismissRequestClicks => {
                 self.request_clicks = None;
             }
+            Message::ReloadZonePressed => {
+                return Some(MainMessage::DescriptionToZone {
+                    request_clicks: None,
+                })
+            }
             Message::RequestChat(conversation_id) => {
                 self.display_chat_required = true;
                 self.replace_top_bar_start = None;
@@ -1711,6 +2357,19 @@ impl Engine for ZoneEngine {
             "Actions".to_string()
         };
 
+        let latency_label = match self.socket.latency() {
+            Some(latency) => format!("{} ms", latency.as_millis()),
+            None => "- ms".to_string(),
+        };
+        let (connection_label, connection_color) = match self.connection_state {
+            ConnectionState::Connected => ("Connecté", Color::from_rgb(0, 200, 0)),
+            ConnectionState::Connecting => ("Connexion", Color::from_rgb(255, 200, 0)),
+            ConnectionState::Reconnecting { .. } => ("Reconnexion", Color::from_rgb(255, 200, 0)),
+            ConnectionState::Lost | ConnectionState::Closed => {
+                ("Déconnecté", Color::from_rgb(255, 0, 0))
+            }
+        };
+
         let left_menu = Column::new()
             .width(LEFT_MENU_WIDTH as u32)
             .height(window.height() as u32 - QUICK_ACTION_ROW_HEIGHT)
@@ -1801,6 +2460,17 @@ impl Engine for ZoneEngine {
                     .class(thin_button::Class::Secondary)
                     .on_press(Message::ExitMenuButtonPressed)
                     .width(175),
+            )
+            .push(
+                Row::new()
+                    .width(175)
+                    .push(
+                        text::Text::new(connection_label)
+                            .size(16)
+                            .color(connection_color)
+                            .width(80),
+                    )
+                    .push(text::Text::new(&latency_label).size(16).width(95)),
             );
 
         let mut right_menu = Column::new()
@@ -1970,6 +2640,38 @@ impl Engine for ZoneEngine {
             )
             .height(window.height() as u32 - QUICK_ACTION_ROW_HEIGHT);
 
+        match self.connection_state {
+            ConnectionState::Reconnecting { attempt } => {
+                center_column = center_column.push(
+                    text::Text::new(&format!(
+                        "Connexion au serveur perdue, reconnexion en cours (tentative {}) ...",
+                        attempt
+                    ))
+                    .color(Color::from_rgb(255, 200, 0))
+                    .class(Some(text::Class::BgGray2))
+                    .height(fixed_button::NODE_HEIGHT)
+                    .vertical_alignment(VerticalAlignment::Center)
+                    .horizontal_alignment(HorizontalAlignment::Center),
+                );
+            }
+            ConnectionState::Lost => {
+                center_column = center_column.push(
+                    Link::new(
+                        false,
+                        "Connexion au serveur perdue, cliquez pour recharger la zone",
+                        Message::ReloadZonePressed,
+                        Message::ReloadZonePressed,
+                        Some(text::Class::BgGray2),
+                    )
+                    .fill_width()
+                    .height(fixed_button::NODE_HEIGHT)
+                    .vertical_alignment(VerticalAlignment::Center)
+                    .horizontal_alignment(HorizontalAlignment::Center),
+                );
+            }
+            _ => {}
+        }
+
         if let Some(top_bar) = self.top_bar.as_ref() {
             let mut top_bar_row = Row::new().height(fixed_button::NODE_HEIGHT);
             if top_bar.display_buttons {
@@ -1,7 +1,8 @@
 use crate::game::{TILE_HEIGHT, TILE_WIDTH};
+use crate::tile::TransportType;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
     pub id: String,
     pub name: String,
@@ -24,6 +25,9 @@ pub struct Player {
     pub pending_actions: i16,
     pub avatar_uuid: Option<String>,
     pub avatar_is_validated: bool,
+    /// Current transport type (walking, swimming, ...) used for pathfinding
+    #[serde(default)]
+    pub transport_type: TransportType,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -47,6 +51,8 @@ pub struct ApiCharacter {
     pub pending_actions: i16,
     pub avatar_uuid: Option<String>,
     pub avatar_is_validated: bool,
+    #[serde(default)]
+    pub transport_type: TransportType,
 }
 
 impl Player {
@@ -68,6 +74,7 @@ impl Player {
         pending_actions: i16,
         avatar_uuid: Option<String>,
         avatar_is_validated: bool,
+        transport_type: TransportType,
     ) -> Self {
         Self {
             id: id.to_string(),
@@ -91,64 +98,7 @@ impl Player {
             pending_actions,
             avatar_uuid,
             avatar_is_validated,
+            transport_type,
         }
     }
-
-    pub fn try_move_by(&mut self, x: i16, y: i16) -> (bool, bool) {
-        let before_x = self.x;
-        let before_y = self.y;
-        let before_row_i = self.position.0;
-        let before_col_i = self.position.1;
-
-        if (self.x + x) > -1 {
-            self.x += x;
-        }
-        if (self.y + y) > -1 {
-            self.y += y;
-        }
-
-        self.position.0 = ((self.y + TILE_HEIGHT / 2) / TILE_HEIGHT) as i32;
-        self.position.1 = ((self.x + TILE_WIDTH / 2) / TILE_WIDTH) as i32;
-        (
-            before_x != x || before_y != y,
-            before_row_i != self.position.0 || before_col_i != self.position.1,
-        )
-    }
-
-    pub fn next_position(&self, x: i16, y: i16) -> Option<(i16, i16)> {
-        let before_row_i = self.position.0;
-        let before_col_i = self.position.1;
-        let mut next_x = self.x + x;
-        let mut next_y = self.y + y;
-
-        if x > 0 {
-            next_x += 1;
-        } else if x < 0 {
-            next_x -= 1;
-        }
-        if y > 0 {
-            next_y += 1;
-        } else if y < 0 {
-            next_y -= 1;
-        }
-        let next_row_i_f = next_y as f32 / TILE_HEIGHT as f32;
-        let next_col_i_f = next_x as f32 / TILE_WIDTH as f32;
-
-        let next_row_i = if next_row_i_f < 0.0 {
-            -1
-        } else {
-            (next_y + TILE_HEIGHT / 2) / TILE_HEIGHT
-        };
-
-        let next_col_i = if next_col_i_f < 0.0 {
-            -1
-        } else {
-            (next_x + TILE_WIDTH / 2) / TILE_WIDTH
-        };
-
-        if before_row_i != next_row_i as i32 || before_col_i != next_col_i as i32 {
-            return Some((next_row_i, next_col_i));
-        }
-        None
-    }
 }
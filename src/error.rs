@@ -1,6 +1,7 @@
 use std::error::Error;
 use std::fmt;
 
+use crate::server::bootstrap::ZoneBootstrapError;
 use crate::server::client::ClientError;
 
 #[derive(Debug)]
@@ -28,9 +29,13 @@ impl From<ClientError> for RollingError {
         return match error {
             ClientError::NotFound { message } => Self { message },
             ClientError::PlayerNotFound { message } => Self { message },
-            ClientError::ClientSideError { message } => Self { message },
-            ClientError::ServerSideError { message } => Self { message },
+            ClientError::ClientSideError { message, .. } => Self { message },
+            ClientError::ServerSideError { .. } | ClientError::RetriesExhausted { .. } => Self {
+                message: ClientError::get_message(&error),
+            },
             ClientError::RequestError { message } => Self { message },
+            ClientError::ConnectionError { message } => Self { message },
+            ClientError::Timeout { message } => Self { message },
             ClientError::UnknownError { message } => Self { message },
             ClientError::Unauthorized => Self {
                 message: "Unauthorized".to_string(),
@@ -38,3 +43,11 @@ impl From<ClientError> for RollingError {
         };
     }
 }
+
+impl From<ZoneBootstrapError> for RollingError {
+    fn from(error: ZoneBootstrapError) -> Self {
+        Self {
+            message: format!("{}", error),
+        }
+    }
+}
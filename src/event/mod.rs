@@ -1,13 +1,15 @@
 use crate::entity::build::Build;
 use crate::error::RollingError;
-use crate::server::client::{ItemModel, ListOfItemModel};
+use crate::server::client::ListOfItemModel;
 use crate::tile::TileId;
+use serde::de::{self, Deserializer};
 use serde::ser::{Serialize, SerializeStruct, Serializer};
 use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
 use serde_derive::{Deserialize as DeserializeDerive, Serialize as SerializeDerive};
 use serde_json;
-use serde_json::Value;
-use std::collections::HashMap;
+use serde_json::{json, Value};
+
+pub mod record;
 
 pub const PLAYER_MOVE: &str = "PLAYER_MOVE";
 pub const CLIENT_WANT_CLOSE: &str = "CLIENT_WANT_CLOSE";
@@ -29,38 +31,67 @@ pub const ZONE_GROUND_STUFF_REMOVE: &str = "ZONE_GROUND_STUFF_REMOVE";
 pub const ZONE_GROUND_RESOURCE_APPEAR: &str = "ZONE_GROUND_RESOURCE_APPEAR";
 pub const ZONE_GROUND_STUFF_APPEAR: &str = "ZONE_GROUND_STUFF_APPEAR";
 
-#[derive(SerializeDerive, DeserializeDerive, Debug)]
-#[serde(untagged)]
+pub const EVENT_TYPES: [&str; 19] = [
+    PLAYER_MOVE,
+    CLIENT_WANT_CLOSE,
+    SERVER_PERMIT_CLOSE,
+    CHARACTER_ENTER_ZONE,
+    CHARACTER_EXIT_ZONE,
+    CLIENT_REQUIRE_AROUND,
+    THERE_IS_AROUND,
+    CLICK_ACTION_EVENT,
+    NEW_RESUME_TEXT,
+    NEW_BUILD,
+    REQUEST_CHAT,
+    NEW_CHAT_MESSAGE,
+    ANIMATED_CORPSE_MOVE,
+    TOP_BAR_MESSAGE,
+    ZONE_TILE_REPLACE,
+    ZONE_GROUND_RESOURCE_REMOVE,
+    ZONE_GROUND_STUFF_REMOVE,
+    ZONE_GROUND_RESOURCE_APPEAR,
+    ZONE_GROUND_STUFF_APPEAR,
+];
+
+#[derive(SerializeDerive, DeserializeDerive, Debug, Clone)]
 pub enum TopBarMessageType {
-    NORMAL,
     ERROR,
+    // Unknown message types are displayed as normal ones
+    #[serde(other)]
+    NORMAL,
 }
 
-#[derive(SerializeDerive, DeserializeDerive, Debug)]
-#[serde(untagged)]
+/// Event data, (de)serialized as `{"type": "<EVENT_TYPE>", "data": {...}}`
+#[derive(SerializeDerive, DeserializeDerive, Debug, Clone)]
+#[serde(tag = "type", content = "data")]
 pub enum ZoneEventType {
     // FIXME rename into ClientClosing
+    #[serde(rename = "CLIENT_WANT_CLOSE")]
     ClientWantClose,
     // FIXME rename into ClientClosingAcknowledge
+    #[serde(rename = "SERVER_PERMIT_CLOSE")]
     ServerPermitClose,
+    #[serde(rename = "PLAYER_MOVE")]
     PlayerMove {
         to_row_i: i32,
         to_col_i: i32,
         character_id: String,
     },
+    #[serde(rename = "CHARACTER_ENTER_ZONE")]
     CharacterEnter {
         zone_row_i: i32,
         zone_col_i: i32,
         character_id: String,
     },
-    CharacterExit {
-        character_id: String,
-    },
+    #[serde(rename = "CHARACTER_EXIT_ZONE")]
+    CharacterExit { character_id: String },
+    #[serde(rename = "CLIENT_REQUIRE_AROUND")]
     ClientRequireAround {
         zone_row_i: i32,
         zone_col_i: i32,
         character_id: String,
     },
+    #[serde(rename = "THERE_IS_AROUND")]
     ThereIsAround {
         stuff_count: i32,
         resource_count: i32,
@@ -68,18 +99,18 @@ pub enum ZoneEventType {
         character_count: i32,
         quick_actions: Vec<CharacterActionLink>,
     },
+    #[serde(rename = "CLICK_ACTION_EVENT")]
     ClickActionEvent {
         action_type: String,
         action_description_id: String,
         row_i: i16,
         col_i: i16,
     },
-    NewResumeText {
-        resume: Vec<ItemModel>,
-    },
-    NewBuild {
-        build: Build,
-    },
+    #[serde(rename = "NEW_RESUME_TEXT")]
+    NewResumeText { resume: ListOfItemModel },
+    #[serde(rename = "NEW_BUILD")]
+    NewBuild { build: Build },
+    #[serde(rename = "REQUEST_CHAT")]
     RequestChat {
         character_id: String,
         previous_conversation_id: Option<i32>,
@@ -87,57 +118,65 @@ pub enum ZoneEventType {
         next: bool,
         previous: bool,
     },
+    #[serde(rename = "NEW_CHAT_MESSAGE")]
     NewChatMessage {
         character_id: String,
         conversation_id: Option<i32>,
         conversation_title: Option<String>,
         message: String,
     },
+    #[serde(rename = "ANIMATED_CORPSE_MOVE")]
     AnimatedCorpseMove {
         to_row_i: i32,
         to_col_i: i32,
         animated_corpse_id: i32,
     },
+    #[serde(rename = "TOP_BAR_MESSAGE")]
     TopBarMessage {
         message: String,
         type_: TopBarMessageType,
     },
+    #[serde(rename = "ZONE_TILE_REPLACE")]
     ZoneTileReplace {
+        #[serde(rename = "zone_row_i")]
         row_i: i16,
+        #[serde(rename = "zone_col_i")]
         col_i: i16,
         new_tile_id: TileId,
     },
+    #[serde(rename = "ZONE_GROUND_RESOURCE_REMOVE")]
     ZoneGroundResourceRemoved {
+        #[serde(rename = "zone_row_i")]
         row_i: i32,
+        #[serde(rename = "zone_col_i")]
         col_i: i32,
         resource_id: String,
     },
-    ZoneGroundStuffRemoved {
-        stuff_id: i32,
-    },
+    #[serde(rename = "ZONE_GROUND_STUFF_REMOVE")]
+    ZoneGroundStuffRemoved { stuff_id: i32 },
+    #[serde(rename = "ZONE_GROUND_RESOURCE_APPEAR")]
     ZoneGroundResourceAdded {
+        #[serde(rename = "zone_row_i")]
         row_i: i32,
+        #[serde(rename = "zone_col_i")]
         col_i: i32,
         resource_id: String,
     },
+    #[serde(rename = "ZONE_GROUND_STUFF_APPEAR")]
     ZoneGroundStuffAdded {
+        #[serde(rename = "id")]
         id_: i32,
         stuff_id: String,
         zone_row_i: i32,
         zone_col_i: i32,
         classes: Vec<String>,
     },
+    /// Event type unknown by this client version (server is newer), kept as is
+    #[serde(skip)]
+    Unknown { type_name: String, data: Value },
 }
 
-#[derive(SerdeSerialize, SerdeDeserialize, Debug)]
-pub struct NewChatMessage {
-    pub conversation_id: Option<i32>,
-    pub conversation_title: Option<String>,
-    pub message: String,
-    pub character_id: String,
-}
-
-#[derive(SerdeSerialize, SerdeDeserialize, Debug)]
+#[derive(SerdeSerialize, SerdeDeserialize, Debug, Clone)]
 pub struct CharacterActionLink {
     pub name: String,
     pub link: String,
@@ -145,199 +184,51 @@ pub struct CharacterActionLink {
     pub classes2: Vec<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ZoneEvent {
     pub event_type: ZoneEventType,
     pub event_type_name: String,
 }
 
+#[derive(DeserializeDerive)]
+struct RawZoneEvent {
+    #[serde(rename = "type")]
+    type_name: String,
+    #[serde(default)]
+    data: Value,
+}
+
 impl ZoneEvent {
-    // TODO: by hand for now ... how to do automatic ?
     pub fn from_value(value: Value) -> Result<Self, RollingError> {
-        let type_ = value["type"].as_str().unwrap();
-        let data = value.get("data").unwrap();
+        let raw: RawZoneEvent = serde_json::from_value(value).map_err(|error| RollingError {
+            message: format!("Invalid event: {}", error),
+        })?;
 
-        match &type_ {
-            &PLAYER_MOVE => Ok(ZoneEvent {
-                event_type_name: String::from(PLAYER_MOVE),
-                event_type: ZoneEventType::PlayerMove {
-                    to_row_i: data["to_row_i"].as_i64().unwrap() as i32,
-                    to_col_i: data["to_col_i"].as_i64().unwrap() as i32,
-                    character_id: String::from(data["character_id"].as_str().unwrap()),
-                },
-            }),
-            &CLIENT_WANT_CLOSE => Ok(ZoneEvent {
-                event_type_name: String::from(CLIENT_WANT_CLOSE),
-                event_type: ZoneEventType::ClientWantClose,
-            }),
-            &SERVER_PERMIT_CLOSE => Ok(ZoneEvent {
-                event_type_name: String::from(SERVER_PERMIT_CLOSE),
-                event_type: ZoneEventType::ServerPermitClose,
-            }),
-            &CHARACTER_ENTER_ZONE => Ok(ZoneEvent {
-                event_type_name: String::from(CHARACTER_ENTER_ZONE),
-                event_type: ZoneEventType::CharacterEnter {
-                    zone_row_i: data["zone_row_i"].as_i64().unwrap() as i32,
-                    zone_col_i: data["zone_col_i"].as_i64().unwrap() as i32,
-                    character_id: String::from(data["character_id"].as_str().unwrap()),
+        if !EVENT_TYPES.contains(&raw.type_name.as_str()) {
+            return Ok(ZoneEvent {
+                event_type_name: raw.type_name.clone(),
+                event_type: ZoneEventType::Unknown {
+                    type_name: raw.type_name,
+                    data: raw.data,
                 },
-            }),
-            &CHARACTER_EXIT_ZONE => Ok(ZoneEvent {
-                event_type_name: String::from(CHARACTER_EXIT_ZONE),
-                event_type: ZoneEventType::CharacterExit {
-                    character_id: String::from(data["character_id"].as_str().unwrap()),
-                },
-            }),
-            &THERE_IS_AROUND => {
-                let stuff_count: i32 = data["stuff_count"].as_i64().unwrap() as i32;
-                let resource_count: i32 = data["resource_count"].as_i64().unwrap() as i32;
-                let build_count: i32 = data["build_count"].as_i64().unwrap() as i32;
-                let character_count: i32 = data["character_count"].as_i64().unwrap() as i32;
-                let quick_actions: Vec<CharacterActionLink> =
-                    serde_json::from_value(data["quick_actions"].clone()).unwrap();
-
-                Ok(ZoneEvent {
-                    event_type_name: String::from(THERE_IS_AROUND),
-                    event_type: ZoneEventType::ThereIsAround {
-                        stuff_count,
-                        resource_count,
-                        build_count,
-                        character_count,
-                        quick_actions,
-                    },
-                })
-            }
-            &NEW_RESUME_TEXT => {
-                let list_of_items: ListOfItemModel =
-                    serde_json::from_value(data.get("resume").unwrap().clone()).unwrap();
-                Ok(ZoneEvent {
-                    event_type_name: String::from(NEW_RESUME_TEXT),
-                    // FIXME BS NOW
-                    event_type: ZoneEventType::NewResumeText {
-                        resume: list_of_items.items,
-                    },
-                })
-            }
-            &NEW_BUILD => {
-                let build_data = data["build"].as_object().unwrap();
-                let mut classes: Vec<String> = vec![];
-                for value in build_data["classes"].as_array().unwrap() {
-                    let class = value.as_str().unwrap();
-                    classes.push(class.to_string());
-                }
-                let mut traversable: HashMap<String, bool> = HashMap::new();
-                traversable.insert(
-                    "WALKING".to_string(),
-                    build_data["traversable"]
-                        .as_object()
-                        .unwrap()
-                        .get("WALKING")
-                        .unwrap()
-                        .as_bool()
-                        .unwrap(),
-                );
+            });
+        }
 
-                Ok(ZoneEvent {
-                    event_type_name: String::from(NEW_BUILD),
-                    event_type: ZoneEventType::NewBuild {
-                        build: Build {
-                            id: build_data["id"].as_i64().unwrap() as i32,
-                            build_id: build_data["build_id"].as_str().unwrap().to_string(),
-                            row_i: build_data["row_i"].as_i64().unwrap() as i32,
-                            col_i: build_data["col_i"].as_i64().unwrap() as i32,
-                            classes,
-                            traversable,
-                            is_floor: build_data["is_floor"].as_bool().unwrap(),
-                        },
-                    },
-                })
-            }
-            &ZONE_TILE_REPLACE => {
-                let new_tile_id = data["new_tile_id"].as_str().unwrap();
-                let zone_row_i = data["zone_row_i"].as_i64().unwrap() as i16;
-                let zone_col_i = data["zone_col_i"].as_i64().unwrap() as i16;
+        // Events without data can be received with null or empty data
+        let tagged = match &raw.data {
+            Value::Null => json!({ "type": raw.type_name }),
+            Value::Object(data) if data.is_empty() => json!({ "type": raw.type_name }),
+            data => json!({ "type": raw.type_name, "data": data }),
+        };
+        let event_type: ZoneEventType =
+            serde_json::from_value(tagged).map_err(|error| RollingError {
+                message: format!("Invalid {} event: {}", raw.type_name, error),
+            })?;
 
-                Ok(ZoneEvent {
-                    event_type_name: String::from(ZONE_TILE_REPLACE),
-                    event_type: ZoneEventType::ZoneTileReplace {
-                        row_i: zone_row_i,
-                        col_i: zone_col_i,
-                        new_tile_id: new_tile_id.to_string(),
-                    },
-                })
-            }
-            &NEW_CHAT_MESSAGE => {
-                let new_chat_message: NewChatMessage =
-                    serde_json::from_value(data.clone()).unwrap();
-                Ok(ZoneEvent {
-                    event_type_name: String::from(NEW_CHAT_MESSAGE),
-                    event_type: ZoneEventType::NewChatMessage {
-                        conversation_id: new_chat_message.conversation_id,
-                        conversation_title: new_chat_message.conversation_title,
-                        message: new_chat_message.message,
-                        character_id: new_chat_message.character_id,
-                    },
-                })
-            }
-            &ANIMATED_CORPSE_MOVE => Ok(ZoneEvent {
-                event_type_name: String::from(ANIMATED_CORPSE_MOVE),
-                event_type: ZoneEventType::AnimatedCorpseMove {
-                    to_row_i: data["to_row_i"].as_i64().unwrap() as i32,
-                    to_col_i: data["to_col_i"].as_i64().unwrap() as i32,
-                    animated_corpse_id: data["animated_corpse_id"].as_i64().unwrap() as i32,
-                },
-            }),
-            &TOP_BAR_MESSAGE => Ok(ZoneEvent {
-                event_type_name: String::from(TOP_BAR_MESSAGE),
-                event_type: ZoneEventType::TopBarMessage {
-                    message: data["message"].as_str().unwrap().to_string(),
-                    type_: match data["type_"].as_str().unwrap() {
-                        "ERROR" => TopBarMessageType::ERROR,
-                        _ => TopBarMessageType::NORMAL,
-                    },
-                },
-            }),
-            &ZONE_GROUND_RESOURCE_REMOVE => Ok(ZoneEvent {
-                event_type_name: String::from(ZONE_GROUND_RESOURCE_REMOVE),
-                event_type: ZoneEventType::ZoneGroundResourceRemoved {
-                    row_i: data["zone_row_i"].as_i64().unwrap() as i32,
-                    col_i: data["zone_col_i"].as_i64().unwrap() as i32,
-                    resource_id: data["resource_id"].as_str().unwrap().to_string(),
-                },
-            }),
-            &ZONE_GROUND_STUFF_REMOVE => Ok(ZoneEvent {
-                event_type_name: String::from(ZONE_GROUND_STUFF_REMOVE),
-                event_type: ZoneEventType::ZoneGroundStuffRemoved {
-                    stuff_id: data["stuff_id"].as_i64().unwrap() as i32,
-                },
-            }),
-            &ZONE_GROUND_RESOURCE_APPEAR => Ok(ZoneEvent {
-                event_type_name: String::from(ZONE_GROUND_RESOURCE_APPEAR),
-                event_type: ZoneEventType::ZoneGroundResourceAdded {
-                    row_i: data["zone_row_i"].as_i64().unwrap() as i32,
-                    col_i: data["zone_col_i"].as_i64().unwrap() as i32,
-                    resource_id: data["resource_id"].as_str().unwrap().to_string(),
-                },
-            }),
-            &ZONE_GROUND_STUFF_APPEAR => Ok(ZoneEvent {
-                event_type_name: String::from(ZONE_GROUND_STUFF_APPEAR),
-                event_type: ZoneEventType::ZoneGroundStuffAdded {
-                    id_: data["id"].as_i64().unwrap() as i32,
-                    stuff_id: data["stuff_id"].as_str().unwrap().to_string(),
-                    zone_row_i: data["zone_row_i"].as_i64().unwrap() as i32,
-                    zone_col_i: data["zone_col_i"].as_i64().unwrap() as i32,
-                    classes: data["classes"]
-                        .as_array()
-                        .unwrap()
-                        .iter()
-                        .map(|x| x.as_str().unwrap().to_string())
-                        .collect(),
-                },
-            }),
-            _ => Err(RollingError {
-                message: format!("Unknown event {}", &type_),
-            }),
-        }
+        Ok(ZoneEvent {
+            event_type_name: raw.type_name,
+            event_type,
+        })
     }
 }
 
@@ -346,9 +237,172 @@ impl Serialize for ZoneEvent {
     where
         S: Serializer,
     {
+        let data = match &self.event_type {
+            ZoneEventType::Unknown { data, .. } => data,
+            // Server expect data even for events without it
+            ZoneEventType::ClientWantClose | ZoneEventType::ServerPermitClose => &Value::Null,
+            event_type => return event_type.serialize(serializer),
+        };
+
         let mut state = serializer.serialize_struct("ZoneEvent", 2)?;
         state.serialize_field("type", &self.event_type_name)?;
-        state.serialize_field("data", &self.event_type)?;
+        state.serialize_field("data", data)?;
         state.end()
     }
 }
+
+impl<'de> SerdeDeserialize<'de> for ZoneEvent {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        ZoneEvent::from_value(value).map_err(|error| de::Error::custom(error.message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event_data(event_type_name: &str) -> Value {
+        match event_type_name {
+            PLAYER_MOVE => json!({"to_row_i": 1, "to_col_i": 2, "character_id": "abc"}),
+            CLIENT_WANT_CLOSE | SERVER_PERMIT_CLOSE => Value::Null,
+            CHARACTER_ENTER_ZONE | CLIENT_REQUIRE_AROUND => {
+                json!({"zone_row_i": 1, "zone_col_i": 2, "character_id": "abc"})
+            }
+            CHARACTER_EXIT_ZONE => json!({"character_id": "abc"}),
+            THERE_IS_AROUND => json!({
+                "stuff_count": 1,
+                "resource_count": 2,
+                "build_count": 3,
+                "character_count": 4,
+                "quick_actions": [
+                    {"name": "Manger", "link": "/eat", "classes1": ["EAT"], "classes2": []}
+                ],
+            }),
+            CLICK_ACTION_EVENT => json!({
+                "action_type": "BUILD",
+                "action_description_id": "BUILD_WALL",
+                "row_i": 1,
+                "col_i": 2,
+            }),
+            NEW_RESUME_TEXT => json!({"resume": {"items": [{
+                "name": "PV",
+                "value_is_str": false,
+                "value_is_float": true,
+                "value_str": null,
+                "value_float": 1.0,
+                "url": null,
+                "classes": ["percent"],
+            }]}}),
+            NEW_BUILD => json!({"build": {
+                "id": 1,
+                "build_id": "CAMPFIRE",
+                "row_i": 1,
+                "col_i": 2,
+                "classes": ["CAMPFIRE"],
+                "traversable": {"WALKING": false},
+                "is_floor": false,
+            }}),
+            REQUEST_CHAT => json!({
+                "character_id": "abc",
+                "previous_conversation_id": null,
+                "message_count": 15,
+                "next": false,
+                "previous": true,
+            }),
+            NEW_CHAT_MESSAGE => json!({
+                "character_id": "abc",
+                "conversation_id": 1,
+                "conversation_title": "Discussion",
+                "message": "Hello",
+            }),
+            ANIMATED_CORPSE_MOVE => {
+                json!({"to_row_i": 1, "to_col_i": 2, "animated_corpse_id": 3})
+            }
+            TOP_BAR_MESSAGE => json!({"message": "Hello", "type_": "ERROR"}),
+            ZONE_TILE_REPLACE => json!({"zone_row_i": 1, "zone_col_i": 2, "new_tile_id": "SAND"}),
+            ZONE_GROUND_RESOURCE_REMOVE | ZONE_GROUND_RESOURCE_APPEAR => {
+                json!({"zone_row_i": 1, "zone_col_i": 2, "resource_id": "WOOD"})
+            }
+            ZONE_GROUND_STUFF_REMOVE => json!({"stuff_id": 1}),
+            ZONE_GROUND_STUFF_APPEAR => json!({
+                "id": 1,
+                "stuff_id": "STONE_HAXE",
+                "zone_row_i": 1,
+                "zone_col_i": 2,
+                "classes": ["HAXE"],
+            }),
+            _ => panic!("No test data for {}", event_type_name),
+        }
+    }
+
+    #[test]
+    fn test_round_trip_all_event_types() {
+        for event_type_name in EVENT_TYPES.iter() {
+            let value = json!({"type": event_type_name, "data": event_data(event_type_name)});
+            let event = ZoneEvent::from_value(value.clone()).unwrap();
+
+            assert_eq!(*event_type_name, event.event_type_name);
+            if let ZoneEventType::Unknown { .. } = event.event_type {
+                panic!("{} decoded as unknown event", event_type_name)
+            }
+            assert_eq!(value, serde_json::to_value(&event).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_event_without_data() {
+        let event =
+            ZoneEvent::from_value(json!({"type": SERVER_PERMIT_CLOSE, "data": {}})).unwrap();
+        match event.event_type {
+            ZoneEventType::ServerPermitClose => {}
+            _ => panic!("Unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_unknown_event_type() {
+        let value = json!({"type": "NEW_FANCY_EVENT", "data": {"foo": [1, 2]}});
+        let event = ZoneEvent::from_value(value.clone()).unwrap();
+
+        match &event.event_type {
+            ZoneEventType::Unknown { type_name, data } => {
+                assert_eq!("NEW_FANCY_EVENT", type_name);
+                assert_eq!(&json!({"foo": [1, 2]}), data);
+            }
+            _ => panic!("Unexpected event {:?}", event),
+        }
+        assert_eq!(value, serde_json::to_value(&event).unwrap());
+    }
+
+    #[test]
+    fn test_unknown_top_bar_message_type() {
+        let event = ZoneEvent::from_value(json!({
+            "type": TOP_BAR_MESSAGE,
+            "data": {"message": "Hello", "type_": "FANCY"},
+        }))
+        .unwrap();
+
+        match event.event_type {
+            ZoneEventType::TopBarMessage {
+                type_: TopBarMessageType::NORMAL,
+                ..
+            } => {}
+            _ => panic!("Unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_invalid_event() {
+        assert!(ZoneEvent::from_value(json!({"data": {}})).is_err());
+        assert!(ZoneEvent::from_value(json!({"type": PLAYER_MOVE, "data": {}})).is_err());
+        assert!(ZoneEvent::from_value(json!({
+            "type": PLAYER_MOVE,
+            "data": {"to_row_i": "1", "to_col_i": 2, "character_id": "abc"},
+        }))
+        .is_err());
+    }
+}
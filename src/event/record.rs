@@ -0,0 +1,227 @@
+use crate::entity::player::Player;
+use crate::error::RollingError;
+use crate::event::ZoneEvent;
+use crate::server::bootstrap::ZoneBootstrapData;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+pub const RECORDINGS_FOLDER: &str = "recordings";
+const SNAPSHOT_FILE_NAME: &str = "snapshot.json";
+const EVENTS_FILE_NAME: &str = "events.jsonl";
+
+/// Everything required to build zone engine without server
+#[derive(Deserialize)]
+pub struct ZoneSnapshot {
+    pub world_source: String,
+    pub player: Player,
+    pub zone: ZoneBootstrapData,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum EventDirection {
+    #[serde(rename = "in")]
+    Incoming,
+    #[serde(rename = "out")]
+    Outgoing,
+}
+
+/// One line of events file
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RecordedEvent {
+    /// Milliseconds since recording start
+    pub elapsed_ms: u64,
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    pub direction: EventDirection,
+    pub event: ZoneEvent,
+}
+
+/// Write zone snapshot and each incoming/outgoing zone event of a zone session in a
+/// recording folder (<recordings folder>/<timestamp>/)
+pub struct EventRecorder {
+    started_at: Instant,
+    writer: Mutex<LineWriter<fs::File>>,
+}
+
+impl EventRecorder {
+    /// Create recording folder and write zone snapshot in it. Events elapsed time is
+    /// computed from this call.
+    pub fn start(
+        recordings_folder: &Path,
+        world_source: &str,
+        player: &Player,
+        zone: &ZoneBootstrapData,
+    ) -> Result<Self, RollingError> {
+        let folder = recordings_folder.join(format!("{}", timestamp_ms()));
+        fs::create_dir_all(&folder).map_err(|error| {
+            RollingError::new(format!(
+                "Unable to create recording folder {}: {}",
+                folder.display(),
+                error
+            ))
+        })?;
+
+        let snapshot_file = fs::File::create(folder.join(SNAPSHOT_FILE_NAME))
+            .map_err(|error| RollingError::new(format!("Unable to create snapshot: {}", error)))?;
+        let snapshot = json!({
+            "world_source": world_source,
+            "player": player,
+            "zone": zone,
+        });
+        serde_json::to_writer(snapshot_file, &snapshot)
+            .map_err(|error| RollingError::new(format!("Unable to write snapshot: {}", error)))?;
+
+        let events_file = fs::File::create(folder.join(EVENTS_FILE_NAME)).map_err(|error| {
+            RollingError::new(format!("Unable to create events file: {}", error))
+        })?;
+
+        println!("Record zone events in {}", folder.display());
+        Ok(Self {
+            started_at: Instant::now(),
+            writer: Mutex::new(LineWriter::new(events_file)),
+        })
+    }
+
+    pub fn record(&self, direction: EventDirection, event: &ZoneEvent) {
+        let line = json!({
+            "elapsed_ms": self.started_at.elapsed().as_millis() as u64,
+            "timestamp_ms": timestamp_ms(),
+            "direction": direction,
+            "event": event,
+        });
+
+        let mut writer = self.writer.lock().unwrap();
+        if let Err(error) = writeln!(writer, "{}", line) {
+            eprintln!("Unable to record zone event: {}", error);
+        }
+    }
+}
+
+/// Load recording folder previously written by `EventRecorder`
+pub fn load_recording(folder: &Path) -> Result<(ZoneSnapshot, Vec<RecordedEvent>), RollingError> {
+    let snapshot_file = fs::File::open(folder.join(SNAPSHOT_FILE_NAME))
+        .map_err(|error| RollingError::new(format!("Unable to open snapshot: {}", error)))?;
+    let snapshot: ZoneSnapshot = serde_json::from_reader(BufReader::new(snapshot_file))
+        .map_err(|error| RollingError::new(format!("Unable to read snapshot: {}", error)))?;
+
+    let events_file = fs::File::open(folder.join(EVENTS_FILE_NAME))
+        .map_err(|error| RollingError::new(format!("Unable to open events file: {}", error)))?;
+    let mut events = vec![];
+    for (line_i, line) in BufReader::new(events_file).lines().enumerate() {
+        let line = line
+            .map_err(|error| RollingError::new(format!("Unable to read events file: {}", error)))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        events.push(serde_json::from_str(&line).map_err(|error| {
+            RollingError::new(format!("Invalid event at line {}: {}", line_i + 1, error))
+        })?);
+    }
+
+    Ok((snapshot, events))
+}
+
+fn timestamp_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::{self, ZoneEventType};
+    use crate::tile::TransportType;
+    use std::collections::HashMap;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_record_and_load() {
+        let recordings_folder =
+            std::env::temp_dir().join(format!("rollgui-recordings-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&recordings_folder);
+        let player = Player::new(
+            "abc",
+            "Alice",
+            (1, 2),
+            (3, 4),
+            10.0,
+            10.0,
+            24.0,
+            20.0,
+            20.0,
+            false,
+            false,
+            false,
+            false,
+            false,
+            0,
+            None,
+            false,
+            TransportType::Walking,
+        );
+        let zone = ZoneBootstrapData {
+            tiles_data: json!([]),
+            zone_data: json!({"raw_source": ""}),
+            resume_text: vec![],
+            characters: HashMap::new(),
+            stuffs: HashMap::new(),
+            resources: vec![],
+            builds: HashMap::new(),
+            animated_corpses: HashMap::new(),
+            avatars: vec!["avatar".to_string()],
+        };
+
+        let recorder = EventRecorder::start(&recordings_folder, "WORLD", &player, &zone).unwrap();
+        recorder.record(
+            EventDirection::Outgoing,
+            &ZoneEvent {
+                event_type_name: event::PLAYER_MOVE.to_string(),
+                event_type: ZoneEventType::PlayerMove {
+                    to_row_i: 1,
+                    to_col_i: 3,
+                    character_id: "abc".to_string(),
+                },
+            },
+        );
+        thread::sleep(Duration::from_millis(50));
+        recorder.record(
+            EventDirection::Incoming,
+            &ZoneEvent {
+                event_type_name: event::CHARACTER_EXIT_ZONE.to_string(),
+                event_type: ZoneEventType::CharacterExit {
+                    character_id: "bob".to_string(),
+                },
+            },
+        );
+        drop(recorder);
+
+        let folder = fs::read_dir(&recordings_folder)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let (snapshot, events) = load_recording(&folder).unwrap();
+        fs::remove_dir_all(&recordings_folder).unwrap();
+
+        assert_eq!("WORLD", snapshot.world_source);
+        assert_eq!("abc", snapshot.player.id);
+        assert_eq!((3, 4), snapshot.player.world_position);
+        assert_eq!(vec!["avatar".to_string()], snapshot.zone.avatars);
+        assert_eq!(2, events.len());
+        assert_eq!(EventDirection::Outgoing, events[0].direction);
+        assert_eq!(event::PLAYER_MOVE, events[0].event.event_type_name);
+        assert_eq!(EventDirection::Incoming, events[1].direction);
+        assert_eq!(event::CHARACTER_EXIT_ZONE, events[1].event.event_type_name);
+        assert!(events[1].elapsed_ms >= events[0].elapsed_ms + 50);
+        assert!(events[0].timestamp_ms > 0);
+    }
+}
@@ -1,27 +1,41 @@
 use crate::args;
 use crate::engine::description::DescriptionEngine;
+use crate::engine::error::ErrorEngine;
 use crate::engine::exit::ExitEngine;
 use crate::engine::login::LoginEngine;
+use crate::engine::server_picker::ServerPickerEngine;
 use crate::engine::upgrade::UpgradeEngine;
 use crate::engine::world::WorldEngine;
 use crate::engine::zone::ZoneEngine;
 use crate::engine::Engine;
-use crate::entity::build::Build;
-use crate::entity::character::Character;
-use crate::entity::corpse::AnimatedCorpse;
 use crate::entity::player::Player;
-use crate::entity::resource::Resource;
-use crate::entity::stuff::Stuff;
-use crate::gui::lang::model::RequestClicks;
+use crate::error::RollingError;
+use crate::event::record::{self, EventRecorder, RecordedEvent, ZoneSnapshot};
+use crate::gui::lang::model::{Description, RequestClicks};
 use crate::input::MyGameInput;
 use crate::level::Level;
 use crate::message::{MainMessage, Message};
+use crate::movement::MovementSettings;
+use crate::server::bootstrap::{
+    ZoneBootstrap, ZoneBootstrapData, ZoneResource, ZoneResourceStatus,
+};
+use crate::server::client::ClientError;
+use crate::server::credentials::{self, CredentialsKey, StoredCredentials};
+use crate::server::http_cache::{self, ResponseCache};
+use crate::server::media_cache::{MediaCache, MediaKind};
+use crate::server::network::NetworkConfig;
+use crate::server::policy::RequestPolicy;
+use crate::server::profile::ServerProfile;
+use crate::server::request::PendingRequest;
 use crate::sheet::TileSheet;
 use crate::socket::ZoneSocket;
+use crate::state::ZoneState;
 use crate::tile::zone::Tiles as ZoneTiles;
 use crate::ui::renderer::Renderer;
+use crate::ui::widget::progress_bar;
+use crate::ui::widget::progress_bar::ProgressBar;
 use crate::ui::widget::text::Text;
-use crate::ui::{Column, Element};
+use crate::ui::{Column, Element, Row};
 use crate::util::get_conf;
 use crate::{event, server, util};
 use glob::glob;
@@ -34,8 +48,7 @@ use coffee::{graphics, Game, Timer};
 use dialog::DialogBox;
 use ini::Ini;
 use pickledb::{PickleDb, PickleDbDumpPolicy};
-use std::collections::HashMap;
-use std::error::Error;
+use std::path::{Path, PathBuf};
 use std::process::exit;
 use std::thread;
 use std::time::SystemTime;
@@ -54,11 +67,19 @@ pub struct MyGame {
     engine: Option<Box<dyn Engine>>,
     tile_sheet_image: graphics::Image,
     db: PickleDb,
-    server: server::Server,
+    key_file_path: PathBuf,
+    media_cache: MediaCache,
+    request_policy: RequestPolicy,
+    network_config: NetworkConfig,
+    movement_settings: MovementSettings,
+    profiles: Vec<ServerProfile>,
+    profile: ServerProfile,
+    server: Option<server::Server>,
     player: Option<Player>,
     exit_requested: bool,
-    pending_action: Option<MainMessage>,
-    loading_displayed: bool,
+    // Server requests of a main message, with this message (to retry it on error)
+    pending_setup: Option<(PendingRequest<Setup>, MainMessage)>,
+    zone_bootstrap: Option<(ZoneBootstrap, Option<RequestClicks>)>,
     last_tick: SystemTime,
     pending_illustration: Option<String>,
     illustration: Option<graphics::Image>,
@@ -67,6 +88,8 @@ pub struct MyGame {
     home_image: Option<graphics::Image>,
     loading_image_to_set: bool,
     loading_image: Option<graphics::Image>,
+    record_zone_events: bool,
+    replay: Option<(ZoneSnapshot, Vec<RecordedEvent>, f32)>,
 }
 
 fn get_db(db_file_path: &str) -> PickleDb {
@@ -77,8 +100,227 @@ fn get_db(db_file_path: &str) -> PickleDb {
     PickleDb::new_json(&db_file_path, PickleDbDumpPolicy::AutoDump)
 }
 
+fn main_message_context(main_message: &MainMessage) -> &'static str {
+    match main_message {
+        MainMessage::StartupToZone { .. }
+        | MainMessage::ServerProfileSelected { .. }
+        | MainMessage::ForgetCredentials { .. }
+        | MainMessage::Logout
+        | MainMessage::ToStartup
+        | MainMessage::AccountCreated => "Démarrage",
+        MainMessage::CreateAccount { .. } => "Création de compte",
+        MainMessage::EnterServer { .. } | MainMessage::NewCharacterId { .. } => {
+            "Connexion au serveur"
+        }
+        MainMessage::DescriptionToZone { .. } => "Chargement de la zone",
+        MainMessage::ToDescriptionWithDescription { .. }
+        | MainMessage::ToDescriptionWithUrl { .. } => "Chargement de la page",
+        MainMessage::ToWorld => "Chargement de la carte du monde",
+        MainMessage::ToServerPicker => "Choix du serveur",
+        MainMessage::ToExit | MainMessage::ExitRequested => "Fermeture",
+    }
+}
+
+/// Upgrade to propose at startup
+struct Upgrade {
+    version: (u8, u8, u8),
+    mandatory: bool,
+    releases_url: String,
+}
+
+/// Result of server requests needed by a main message. Requests are executed by request workers
+/// then next engine is set up from this result.
+enum Setup {
+    /// Server of profile is loaded and its version checked
+    ServerSelected {
+        profile: ServerProfile,
+        server: server::Server,
+        upgrade: Option<Upgrade>,
+    },
+    /// Server version is checked, with upgrade to propose if any
+    Startup(Option<Upgrade>),
+    /// Remembered session token is invalidated
+    LoggedOut,
+    /// Description page to display. Startup ones (account or character creation, post mortem)
+    /// are displayed without player.
+    Description {
+        description: Description,
+        client: server::client::Client,
+        back_url: Option<String>,
+        startup: bool,
+    },
+    /// Player is alive on server, its zone can be loaded
+    Zone {
+        player: Player,
+        request_clicks: Option<RequestClicks>,
+    },
+}
+
+/// Messages which need a selected server are replaced, when none is (e.g. its selection failed),
+/// by server picker or selection of the single profile
+fn route_main_message(
+    main_message: MainMessage,
+    server_selected: bool,
+    profiles: &[ServerProfile],
+) -> MainMessage {
+    let requires_server = !matches!(
+        main_message,
+        MainMessage::ServerProfileSelected { .. }
+            | MainMessage::ToServerPicker
+            | MainMessage::CreateAccount { .. }
+            | MainMessage::ToDescriptionWithDescription { .. }
+            | MainMessage::ToExit
+            | MainMessage::ExitRequested
+    );
+    if server_selected || !requires_server {
+        return main_message;
+    }
+
+    if profiles.len() > 1 {
+        MainMessage::ToServerPicker
+    } else {
+        MainMessage::ServerProfileSelected {
+            profile_id: profiles[0].id.clone(),
+        }
+    }
+}
+
+fn describe(
+    client: &server::client::Client,
+    url: &str,
+    back_url: Option<String>,
+    startup: bool,
+) -> Result<Setup, ClientError> {
+    Ok(Setup::Description {
+        description: client.describe(url, None, None)?,
+        client: client.clone(),
+        back_url,
+        startup,
+    })
+}
+
+/// Compare server and client versions, with last compatible version from releases index
+fn check_version(
+    client: &server::client::Client,
+    profile: &ServerProfile,
+    network_config: &NetworkConfig,
+) -> Result<Option<Upgrade>, ClientError> {
+    let server_version = client.get_version()?;
+    let client_version = util::str_version_to_tuple(VERSION);
+    let releases_url = match profile.releases_url.clone() {
+        Some(releases_url) => releases_url,
+        None => {
+            return Err(ClientError::UnknownError {
+                message: format!("Missing releases_url in {} server config", profile.name),
+            })
+        }
+    };
+    let last_compatible_version = util::get_last_compatible_version(
+        server_version,
+        &releases_url,
+        &network_config.http_client(),
+    )
+    .map_err(|error| ClientError::UnknownError {
+        message: format!("Unable to retrieve releases index: {}", error),
+    })?;
+
+    println!("Check is compatible");
+    if !util::is_compatible_versions(server_version, client_version) {
+        println!("Version is not compatible");
+        return Ok(Some(Upgrade {
+            version: last_compatible_version,
+            mandatory: true,
+            releases_url,
+        }));
+    }
+    println!("Version is compatible");
+    println!(
+        "Is there newer version ? ({:?} != {:?})",
+        last_compatible_version, client_version
+    );
+    if last_compatible_version != client_version {
+        return Ok(Some(Upgrade {
+            version: last_compatible_version,
+            mandatory: false,
+            releases_url,
+        }));
+    }
+    Ok(None)
+}
+
+fn find_player(server: &server::Server) -> Result<Option<Player>, ClientError> {
+    println!("Try to create Player with local data?");
+    if let Some(character_id) = &server.character_id {
+        println!("Character '{}' locally found", character_id);
+        return match server.client.get_player(character_id) {
+            Ok(player) => {
+                println!("Player found on server");
+                Ok(Some(player))
+            }
+            Err(ClientError::PlayerNotFound { message: _ }) => {
+                println!("Player NOT found on server");
+                Ok(None)
+            }
+            Err(client_error) => Err(client_error),
+        };
+    }
+
+    println!("No local player found");
+    Ok(None)
+}
+
+/// Load zone of player if found on server. Otherwise, display its post mortem (if dead) or
+/// character creation page.
+fn startup_to_zone(
+    server: &server::Server,
+    request_clicks: Option<RequestClicks>,
+) -> Result<Setup, ClientError> {
+    if let Some(player) = find_player(server)? {
+        return Ok(Setup::Zone {
+            player,
+            request_clicks,
+        });
+    }
+
+    if let Some(character_id) = &server.character_id {
+        println!("Maybe character is dead ?");
+        if server.client.player_is_dead(character_id)? {
+            println!("Yes, it is dead");
+            return describe(
+                &server.client,
+                &format!("/character/{}/post_mortem", character_id),
+                None,
+                true,
+            );
+        }
+    }
+    describe(&server.client, "/_describe/character/create", None, true)
+}
+
 impl MyGame {
-    fn get_server_last_username(&self, address: server::ServerAddress) -> String {
+    /// Server must be selected before calling this method
+    fn server(&self) -> &server::Server {
+        self.server
+            .as_ref()
+            .expect("Server profile must be selected")
+    }
+
+    fn server_mut(&mut self) -> &mut server::Server {
+        self.server
+            .as_mut()
+            .expect("Server profile must be selected")
+    }
+
+    fn get_server_last_username(&self) -> String {
+        if let Some(last_username) = self
+            .db
+            .get::<String>(format!("server_profile_{}_last_username", self.profile.id).as_str())
+        {
+            return last_username;
+        }
+
+        // Previous versions stored last username by server address
+        let address = &self.profile.address;
         if let Some(last_username) = self
             .db
             .get::<String>(format!("server_{}_{}", address.host, address.port).as_str())
@@ -90,164 +332,195 @@ impl MyGame {
     }
 
     fn set_server_last_username(&mut self) {
+        let last_username = self.server().client.auth.login().to_string();
         self.db
             .set(
-                format!(
-                    "server_{}_{}",
-                    self.server.address.host, self.server.address.port
-                )
-                .as_str(),
-                &self.server.client.credentials.0,
+                format!("server_profile_{}_last_username", self.profile.id).as_str(),
+                &last_username,
             )
             .unwrap();
     }
 
-    fn setup_startup_to_zone_engine(&mut self, request_clicks: Option<RequestClicks>) {
-        println!("setup_startup_to_zone_engine");
-        let server = self.server.clone();
+    fn credentials_db_key(&self) -> String {
+        format!("server_profile_{}_credentials", self.profile.id)
+    }
 
-        // FIXME BS: manage error cases
-        if let Some(player) = self.create_player().unwrap() {
-            self.player = Some(player);
-            self.setup_zone_engine(request_clicks);
-            return;
+    /// Credentials remembered for current server profile, if any and readable
+    fn get_remembered_auth(&self) -> Option<server::client::Auth> {
+        let stored_credentials = self
+            .db
+            .get::<StoredCredentials>(&self.credentials_db_key())?;
+        let result = CredentialsKey::load(&self.key_file_path).and_then(|key| match key {
+            Some(key) => stored_credentials.open(&key).map(Some),
+            None => Ok(None),
+        });
+        match result {
+            Ok(auth) => auth,
+            Err(error) => {
+                eprintln!("Unable to read remembered credentials: {}", error);
+                None
+            }
         }
+    }
 
-        if let Some(character_id) = server.character_id.clone() {
-            println!("Maybe character is dead ?");
-            // FIXME: manage error case
-            if server.client.player_is_dead(&character_id).unwrap() {
-                println!("Yes, it is dead");
-                let description = server
-                    .client
-                    .describe(
-                        format!("/character/{}/post_mortem", character_id).as_str(),
-                        None,
-                        None,
-                    )
-                    .unwrap();
-                self.setup_no_home_image();
-                self.engine = Some(Box::new(DescriptionEngine::new(
-                    None,
-                    // TODO: manage error cases
-                    description.clone(),
-                    server.client.clone(),
-                    None,
-                    true,
-                    TileSheet::new(self.tile_sheet_image.clone(), TILE_WIDTH, TILE_HEIGHT),
-                )));
-                self.pending_illustration = description.illustration_name;
-                self.illustration = None;
-                self.illustration_bg = None;
+    /// Session token is remembered, or password if server don't deliver tokens
+    fn remember_credentials(&mut self) {
+        let key = match CredentialsKey::load_or_create(&self.key_file_path) {
+            Ok(key) => key,
+            Err(error) => {
+                eprintln!("Unable to remember credentials: {}", error);
                 return;
             }
+        };
+        match StoredCredentials::seal(&self.server().client.auth, &key) {
+            Some(stored_credentials) => {
+                let db_key = self.credentials_db_key();
+                if let Err(error) = self.db.set(&db_key, &stored_credentials) {
+                    eprintln!("Unable to remember credentials: {}", error);
+                }
+            }
+            None => self.forget_credentials(),
         }
-        let description = server
-            .client
-            .describe("/_describe/character/create", None, None)
-            .unwrap();
-        self.setup_no_home_image();
-        self.engine = Some(Box::new(DescriptionEngine::new(
-            None,
-            // FIXME: manage error cases
-            description.clone(),
-            server.client.clone(),
-            None,
-            true,
-            TileSheet::new(self.tile_sheet_image.clone(), TILE_WIDTH, TILE_HEIGHT),
-        )));
-        self.pending_illustration = description.illustration_name;
-        self.illustration = None;
-        self.illustration_bg = None;
     }
 
-    fn setup_create_account(&mut self, address: server::ServerAddress) {
-        println!("setup_create_account");
-        let client = server::client::Client::new(address, ("".to_string(), "".to_string()));
-        let description = client.describe("/account/create", None, None).unwrap();
-        self.setup_home_image_background();
-        self.engine = Some(Box::new(DescriptionEngine::new(
-            None,
-            description.clone(),
-            client.clone(),
-            None,
-            true,
-            TileSheet::new(self.tile_sheet_image.clone(), TILE_WIDTH, TILE_HEIGHT),
-        )));
-        self.pending_illustration = description.illustration_name;
-        self.illustration = None;
-        self.illustration_bg = None;
+    fn forget_credentials(&mut self) {
+        let db_key = self.credentials_db_key();
+        if let Err(error) = self.db.rem(&db_key) {
+            eprintln!("Unable to forget credentials: {}", error);
+        }
     }
 
-    fn setup_create_character(&mut self) {
-        println!("setup_create_character");
-        let server = self.server.clone();
-        let description = server
-            .client
-            .describe("/_describe/character/create", None, None)
-            .unwrap();
-        self.setup_home_image_background();
-        self.engine = Some(Box::new(DescriptionEngine::new(
-            None,
-            description.clone(),
-            server.client.clone(),
-            None,
-            true,
-            TileSheet::new(self.tile_sheet_image.clone(), TILE_WIDTH, TILE_HEIGHT),
-        )));
-        self.pending_illustration = description.illustration_name;
-        self.illustration = None;
-        self.illustration_bg = None;
+    /// Invalidate remembered session token on server (in request workers) and forget it
+    fn logout(&mut self, main_message: MainMessage) {
+        let remembered_auth = self.get_remembered_auth();
+        self.forget_credentials();
+
+        match remembered_auth {
+            Some(auth) => {
+                self.pending_setup = Some((
+                    self.server().client.with_auth(auth).spawn(|client| {
+                        // Credentials are locally forgotten anyway
+                        if let Err(error) = client.logout() {
+                            eprintln!("Unable to logout from server: {}", error);
+                        }
+                        Ok(Setup::LoggedOut)
+                    }),
+                    main_message,
+                ))
+            }
+            None => self.setup_login_engine(None),
+        }
     }
 
-    fn create_player(&self) -> Result<Option<Player>, Box<dyn Error>> {
-        println!("create_player");
-        // Server must exist at this step
-        let server = self.server.clone();
+    /// Load server of profile and check its version in request workers
+    fn request_server_profile(
+        &mut self,
+        profile_id: &str,
+        main_message: MainMessage,
+    ) -> Result<(), RollingError> {
+        let profile = match self
+            .profiles
+            .iter()
+            .find(|profile| profile.id == profile_id)
+        {
+            Some(profile) => profile.clone(),
+            None => {
+                return Err(RollingError::new(format!(
+                    "Unknown server profile {}",
+                    profile_id
+                )))
+            }
+        };
+        println!("Select server {} ({})", profile.name, profile.address);
+
+        let client = self
+            .new_client(profile.address.clone())
+            .with_response_cache(ResponseCache::new(http_cache::RESPONSE_CACHE_FOLDER));
+        let network_config = self.network_config.clone();
+        self.pending_setup = Some((
+            client.spawn(move |client| {
+                let server = server::Server::new(client.clone(), profile.address.clone(), None)
+                    .map_err(|error| ClientError::UnknownError {
+                        message: format!("Connexion error : {}", error),
+                    })?;
+                let upgrade = check_version(client, &profile, &network_config)?;
+                Ok(Setup::ServerSelected {
+                    profile,
+                    server,
+                    upgrade,
+                })
+            }),
+            main_message,
+        ));
+        Ok(())
+    }
 
-        println!("Try to create Player with local data?");
-        if let Some(character_id) = &server.character_id {
-            println!("Character '{}' locally found", character_id);
-            return match server.client.get_player(character_id) {
-                Ok(player) => {
-                    println!("Player found on server");
-                    Ok(Some(player))
-                }
-                Err(server::client::ClientError::PlayerNotFound { message: _ }) => {
-                    println!("Player NOT found on server");
-                    Ok(None)
-                }
-                Err(client_error) => Err(Box::new(client_error)),
-            };
-        }
+    /// Anonymous client configured with network, timeouts and cache settings
+    fn new_client(&self, address: server::ServerAddress) -> server::client::Client {
+        server::client::Client::new(address, server::client::Auth::Anonymous)
+            .with_policy(self.request_policy.clone())
+            .with_network_config(self.network_config.clone())
+            .with_media_cache(self.media_cache.clone())
+    }
 
-        println!("No local player found");
-        return Ok(None);
+    fn setup_server_picker_engine(&mut self) {
+        println!("setup_server_picker_engine");
+        self.setup_home_image();
+        let client = self.new_client(self.profiles[0].address.clone());
+        self.engine = Some(Box::new(ServerPickerEngine::new(
+            self.profiles.clone(),
+            client,
+        )));
     }
 
-    fn create_upgrade_engine(&mut self, version: (u8, u8, u8), mandatory: bool) -> Box<dyn Engine> {
+    fn setup_error_engine(
+        &mut self,
+        error: RollingError,
+        context: &str,
+        retry: Option<MainMessage>,
+    ) {
+        self.setup_home_image_background();
+        self.pending_illustration = None;
+        self.illustration = None;
+        self.illustration_bg = None;
+        self.engine = Some(Box::new(ErrorEngine::new(error, context, retry)));
+    }
+
+    fn create_upgrade_engine(&self, upgrade: Upgrade) -> Box<dyn Engine> {
         println!("setup_upgrade_engine");
         Box::new(UpgradeEngine::new(
-            version,
-            mandatory,
-            self.server.address.clone(),
+            upgrade.version,
+            upgrade.mandatory,
+            self.server()
+                .client
+                .with_auth(server::client::Auth::Anonymous),
             self.conf.clone(),
+            upgrade.releases_url,
+        ))
+    }
+
+    fn create_login_engine(
+        &self,
+        message: Option<String>,
+        remembered_auth: Option<server::client::Auth>,
+    ) -> Box<dyn Engine> {
+        Box::new(LoginEngine::new(
+            self.server()
+                .client
+                .with_auth(server::client::Auth::Anonymous),
+            message,
+            self.get_server_last_username(),
+            self.profiles.len() > 1,
+            remembered_auth,
         ))
     }
 
     fn setup_home_image_background(&mut self) {
-        self.pending_home_image = match self.conf.get_from(Some("design"), "home_image_background")
-        {
-            None => None,
-            Some(home_image_background) => Some(String::from(home_image_background)),
-        }
+        self.pending_home_image = self.profile.home_image_background.clone()
     }
 
     fn setup_home_image(&mut self) {
-        self.pending_home_image = match self.conf.get_from(Some("design"), "home_image") {
-            None => None,
-            Some(home_image_background) => Some(String::from(home_image_background)),
-        }
+        self.pending_home_image = self.profile.home_image.clone()
     }
 
     fn setup_no_home_image(&mut self) {
@@ -255,84 +528,164 @@ impl MyGame {
         self.home_image = None;
     }
 
-    fn create_startup_engine(&mut self, disable_version_check: bool) -> Box<dyn Engine> {
-        println!("create startup engine");
+    fn setup_login_engine(&mut self, message: Option<String>) {
+        self.setup_home_image();
+        self.engine = Some(self.create_login_engine(message, None));
+    }
 
-        if !disable_version_check {
-            let server_version = self.server.client.get_version().unwrap();
-            let client_version = util::str_version_to_tuple(VERSION);
-            println!("Check is compatible");
-            if !util::is_compatible_versions(server_version, client_version) {
-                println!("Version is not compatible");
-                let last_compatible_version = util::get_last_compatible_version(
-                    server_version,
-                    self.conf.get_from(Some("server"), "releases_url").unwrap(),
-                );
+    /// Upgrade engine if an upgrade is proposed, login engine otherwise
+    fn setup_startup_engine(&mut self, upgrade: Option<Upgrade>) {
+        println!("create startup engine");
+        self.engine = Some(match upgrade {
+            Some(upgrade) => {
                 self.setup_home_image_background();
-                return self.create_upgrade_engine(last_compatible_version, true);
-            } else {
-                println!("Version is compatible");
-                let last_compatible_version = util::get_last_compatible_version(
-                    server_version,
-                    self.conf.get_from(Some("server"), "releases_url").unwrap(),
-                );
-                println!(
-                    "Is there newer version ? ({:?} != {:?})",
-                    last_compatible_version, client_version
-                );
-                if last_compatible_version != client_version {
-                    self.setup_home_image_background();
-                    return self.create_upgrade_engine(last_compatible_version, false);
-                }
+                self.create_upgrade_engine(upgrade)
             }
-        }
+            None => {
+                self.setup_home_image();
+                self.create_login_engine(None, self.get_remembered_auth())
+            }
+        });
+    }
 
-        Box::new(LoginEngine::new(
-            self.server.address.clone(),
-            None,
-            self.get_server_last_username(self.server.address.clone()),
-        ))
+    fn request_version_check(&mut self, main_message: MainMessage) {
+        let profile = self.profile.clone();
+        let network_config = self.network_config.clone();
+        self.pending_setup = Some((
+            self.server().client.spawn(move |client| {
+                check_version(client, &profile, &network_config).map(Setup::Startup)
+            }),
+            main_message,
+        ));
+    }
+
+    fn request_startup_to_zone(
+        &mut self,
+        request_clicks: Option<RequestClicks>,
+        main_message: MainMessage,
+    ) {
+        let server = self.server().clone();
+        self.pending_setup = Some((
+            self.server()
+                .client
+                .spawn(move |_| startup_to_zone(&server, request_clicks)),
+            main_message,
+        ));
+    }
+
+    fn request_description(
+        &mut self,
+        url: String,
+        back_url: Option<String>,
+        main_message: MainMessage,
+    ) {
+        self.pending_setup = Some((
+            self.server()
+                .client
+                .spawn(move |client| describe(client, &url, back_url, false)),
+            main_message,
+        ));
     }
 
     fn setup_zone_engine(&mut self, request_clicks: Option<RequestClicks>) {
         println!("setup_zone_engine");
         // Player and Server must exist at this step
-        let server = self.server.clone();
         let player = self.player.as_ref().unwrap();
+        self.zone_bootstrap = Some((
+            ZoneBootstrap::start(&self.server().client, &player.id, player.world_position),
+            request_clicks,
+        ));
+    }
+
+    fn proceed_zone_bootstrap(&mut self) {
+        let result = match self.zone_bootstrap.as_mut() {
+            Some((zone_bootstrap, _)) => match zone_bootstrap.poll() {
+                Some(result) => result,
+                None => return,
+            },
+            None => return,
+        };
+        let (_, request_clicks) = self.zone_bootstrap.take().unwrap();
+
+        let result = result
+            .map_err(RollingError::from)
+            .and_then(|data| self.create_zone_engine(data, request_clicks.clone(), None));
+        if let Err(error) = result {
+            self.setup_error_engine(
+                error,
+                "Chargement de la zone",
+                Some(MainMessage::DescriptionToZone { request_clicks }),
+            );
+        }
+    }
+
+    fn setup_replay_engine(
+        &mut self,
+        snapshot: ZoneSnapshot,
+        events: Vec<RecordedEvent>,
+        speed: f32,
+    ) -> Result<(), RollingError> {
+        println!("Replay {} recorded zone events", events.len());
+        self.player = Some(snapshot.player);
+        self.create_zone_engine(snapshot.zone, None, Some((events, speed)))
+    }
+
+    /// Create zone engine from bootstrap data. If `replay` is given, zone events come from
+    /// these recorded events (played at given speed) instead of server.
+    fn create_zone_engine(
+        &mut self,
+        data: ZoneBootstrapData,
+        request_clicks: Option<RequestClicks>,
+        replay: Option<(Vec<RecordedEvent>, f32)>,
+    ) -> Result<(), RollingError> {
+        // Player and Server must exist at this step
+        let server = self.server().clone();
+        let player = self.player.as_ref().unwrap();
+
+        let recorder = if replay.is_none() && self.record_zone_events {
+            match EventRecorder::start(
+                Path::new(record::RECORDINGS_FOLDER),
+                &server.world_source,
+                player,
+                &data,
+            ) {
+                Ok(recorder) => Some(recorder),
+                Err(error) => {
+                    eprintln!("Unable to record zone events: {}", error);
+                    None
+                }
+            }
+        } else {
+            None
+        };
 
         let tile_sheet_image = self.tile_sheet_image.clone();
-        // FIXME BS: manage error case
-        let server_tiles_data = server.client.get_tiles_data().unwrap();
-        let tiles = ZoneTiles::new(server_tiles_data);
+        let tiles = ZoneTiles::new(data.tiles_data);
         let tile_width: i16 = TILE_WIDTH;
         let tile_height: i16 = TILE_HEIGHT;
 
-        // FIXME BS: manage error
-        let zone_data = server
-            .client
-            .get_zone_data(player.world_position.0, player.world_position.1)
-            .unwrap();
-        // FIXME BS: manage error
-        let zone_raw = zone_data["raw_source"].as_str().unwrap();
-        let zone_raw = util::extract_block_from_source(util::BLOCK_GEO, zone_raw).unwrap();
-        let world_tile_type_id = self.server.world.rows[player.world_position.0 as usize].cols
+        let world_tile_type_id = self.server().world.rows[player.world_position.0 as usize].cols
             [player.world_position.1 as usize]
             .clone();
-        let level = Level::new(&zone_raw, &tiles, world_tile_type_id).unwrap();
-
-        let mut socket = ZoneSocket::new(format!(
-            "{}/ws/zones/{}/{}/events?character_id={}",
-            server
-                .address
-                .with_credentials(&server.client.credentials.0, &server.client.credentials.1,),
-            player.world_position.0,
-            player.world_position.1,
-            player.id
-        ));
-        if server.address.secure {
-            socket.connect();
-        } else {
-            socket.connect_unsecure();
+        let level = Level::from_zone_data(&data.zone_data, &tiles, world_tile_type_id)?;
+
+        let socket = match replay {
+            Some((events, speed)) => ZoneSocket::replay(events, speed),
+            None => {
+                let mut socket = ZoneSocket::new(
+                    format!(
+                        "{}/ws/zones/{}/{}/events?character_id={}",
+                        server.address, player.world_position.0, player.world_position.1, player.id
+                    ),
+                    server.client.websocket_headers(),
+                );
+                if let Some(recorder) = recorder {
+                    socket.set_recorder(recorder);
+                }
+                socket.set_network_config(server.client.network_config().clone());
+                socket.connect(server.address.secure);
+                socket
+            }
         };
 
         socket.send(event::ZoneEvent {
@@ -343,114 +696,262 @@ impl MyGame {
                 character_id: String::from(player.id.as_str()),
             },
         });
-        // FIXME: Manage errors
-        let resume_text = server
-            .client
-            .get_character_resume_texts(&player.id)
-            .unwrap();
 
-        // CHARACTERS
-        // FIXME: Manage errors
-        let all_characters = server
-            .client
-            .get_zone_characters(player.world_position.0, player.world_position.1)
-            .unwrap();
-        let mut characters: HashMap<String, Character> = HashMap::new();
-        for character in all_characters.into_iter() {
-            characters.insert(character.id.clone(), character);
-        }
-
-        // STUFFS
-        // FIXME: Manage errors
-        let stuffs_list = server
-            .client
-            .get_zone_stuffs(player.world_position.0, player.world_position.1)
-            .unwrap();
-        let mut stuffs: HashMap<String, Stuff> = HashMap::new();
-        for stuff in stuffs_list.into_iter() {
-            stuffs.insert(stuff.id.to_string().clone(), stuff);
-        }
+        let state = ZoneState::new(player.id.clone(), tiles, level)
+            .with_characters(data.characters)
+            .with_stuffs(data.stuffs)
+            .with_resources(data.resources)
+            .with_builds(data.builds)
+            .with_animated_corpses(data.animated_corpses);
+        self.engine = Some(Box::new(ZoneEngine::new(
+            tile_sheet_image,
+            data.avatars,
+            tile_width,
+            tile_height,
+            player.clone(),
+            server.clone(),
+            state,
+            socket,
+            data.resume_text,
+            request_clicks,
+            self.movement_settings.clone(),
+        )));
+        self.setup_no_home_image();
+        Ok(())
+    }
 
-        // RESOURCES
-        // FIXME: Manage errors
-        let resources: Vec<Resource> = server
-            .client
-            .get_zone_resources(player.world_position.0, player.world_position.1)
-            .unwrap();
+    fn zone_bootstrap_layout<'a>(&self, column: Column<'a>) -> Column<'a> {
+        let zone_bootstrap = match self.zone_bootstrap.as_ref() {
+            Some((zone_bootstrap, _)) => zone_bootstrap,
+            None => return column,
+        };
 
-        // BUILDS
-        // FIXME: Manage errors
-        let builds_list = server
-            .client
-            .get_zone_builds(player.world_position.0, player.world_position.1)
-            .unwrap();
-        let mut builds: HashMap<i32, Build> = HashMap::new();
-        for build in builds_list.into_iter() {
-            builds.insert(build.id, build);
+        let mut column = column;
+        for resource in ZoneResource::all() {
+            let color_class = match zone_bootstrap.status(resource) {
+                ZoneResourceStatus::Failed => progress_bar::ColorClass::Red,
+                ZoneResourceStatus::Pending => progress_bar::ColorClass::Yellow,
+                ZoneResourceStatus::Done => progress_bar::ColorClass::Green,
+            };
+            column = column.push(
+                Row::new()
+                    .width(400)
+                    .push(Text::new(resource.label()).width(250))
+                    .push(
+                        ProgressBar::new(
+                            zone_bootstrap.progress(resource),
+                            progress_bar::Class::SimpleThin,
+                            color_class,
+                        )
+                        .width(150),
+                    ),
+            );
         }
 
-        // ANIMATED CORPSES
-        let animated_corpses_list = server
-            .client
-            .get_animated_corpses(player.world_position.0, player.world_position.1)
-            .unwrap();
-        let mut animated_corpses: HashMap<i32, AnimatedCorpse> = HashMap::new();
-        for animated_corpse in animated_corpses_list.into_iter() {
-            animated_corpses.insert(animated_corpse.id, animated_corpse);
+        column
+    }
+
+    fn proceed_main_message(&mut self, main_message: MainMessage) {
+        if let Err(error) = self.start_main_message(main_message.clone()) {
+            let context = main_message_context(&main_message);
+            self.setup_error_engine(error, context, Some(main_message));
         }
+    }
 
-        let mut avatars: Vec<String> = vec![];
-        for (_, character) in &characters {
-            let avatar_uuid = if character.avatar_is_validated {
-                if let Some(avatar_uuid) = &character.avatar_uuid {
-                    avatar_uuid.to_string()
+    /// Set up next engine, or start its server requests (loading screen is displayed until
+    /// `Setup` is received)
+    fn start_main_message(&mut self, main_message: MainMessage) -> Result<(), RollingError> {
+        let main_message = route_main_message(main_message, self.server.is_some(), &self.profiles);
+        let retry = main_message.clone();
+        match main_message {
+            MainMessage::StartupToZone {
+                disable_version_check,
+            } => {
+                println!("Set startup engine");
+                if disable_version_check {
+                    self.setup_startup_engine(None);
                 } else {
-                    "0000".to_string()
+                    self.request_version_check(retry);
                 }
-            } else {
-                "0000".to_string()
-            };
-            match server.client.cache_media(&format!(
-                "character_avatar__zone_thumb__{}.png",
-                avatar_uuid
-            )) {
-                Ok(_) => {
-                    if !avatars.contains(&avatar_uuid) {
-                        avatars.push(avatar_uuid)
-                    }
+            }
+            MainMessage::ServerProfileSelected { profile_id } => {
+                self.request_server_profile(&profile_id, retry)?
+            }
+            MainMessage::ToServerPicker => self.setup_server_picker_engine(),
+            MainMessage::ForgetCredentials { message } => {
+                self.forget_credentials();
+                self.setup_login_engine(message);
+            }
+            MainMessage::Logout => self.logout(retry),
+            MainMessage::ToDescriptionWithDescription {
+                description,
+                back_url,
+                client,
+            } => {
+                self.apply_setup(Setup::Description {
+                    description,
+                    client,
+                    back_url,
+                    startup: false,
+                });
+            }
+            MainMessage::CreateAccount { address } => {
+                println!("setup_create_account");
+                self.pending_setup = Some((
+                    self.new_client(address)
+                        .spawn(|client| describe(client, "/account/create", None, true)),
+                    retry,
+                ));
+            }
+            MainMessage::AccountCreated | MainMessage::ToStartup => self.setup_startup_engine(None),
+            MainMessage::NewCharacterId { character_id } => {
+                self.server_mut().character_id = Some(character_id);
+                self.request_startup_to_zone(None, retry);
+            }
+            MainMessage::EnterServer {
+                auth,
+                remember,
+                character_id,
+            } => {
+                println!("Enter server");
+                self.server_mut().client.auth = auth;
+                self.server_mut().character_id = character_id;
+                self.set_server_last_username();
+                if remember {
+                    self.remember_credentials();
+                } else {
+                    self.forget_credentials();
                 }
-                Err(error) => {
-                    eprintln!("Error when get avatar {} : {}", avatar_uuid, error)
+
+                if self.server().character_id.is_none() {
+                    println!("setup_create_character");
+                    self.pending_setup = Some((
+                        self.server().client.spawn(|client| {
+                            describe(client, "/_describe/character/create", None, true)
+                        }),
+                        retry,
+                    ));
+                } else {
+                    self.request_startup_to_zone(None, retry);
                 }
-            };
+            }
+            MainMessage::ToDescriptionWithUrl { url, back_url } => {
+                self.request_description(url, back_url, retry)
+            }
+            MainMessage::DescriptionToZone { request_clicks } => {
+                self.request_startup_to_zone(request_clicks, retry)
+            }
+            MainMessage::ToExit => {
+                self.setup_home_image_background();
+                self.engine = Some(Box::new(ExitEngine::new()));
+            }
+            MainMessage::ExitRequested => self.exit_requested = true,
+            MainMessage::ToWorld => {
+                self.setup_no_home_image();
+                self.engine = Some(Box::new(WorldEngine::new(
+                    self.server().clone(),
+                    self.tile_sheet_image.clone(),
+                    self.player.as_ref().unwrap().clone(),
+                )));
+                self.loading_image_to_set = true;
+            }
         }
 
-        self.engine = Some(Box::new(ZoneEngine::new(
-            tiles,
-            tile_sheet_image,
-            avatars,
-            tile_width,
-            tile_height,
-            player.clone(),
-            server.clone(),
-            level,
-            socket,
-            resume_text,
-            characters,
-            stuffs,
-            resources,
-            builds,
-            animated_corpses,
-            request_clicks,
-        )));
-        self.setup_no_home_image();
+        Ok(())
     }
 
-    fn proceed_main_message(&mut self, main_message: MainMessage) {
-        self.pending_action = Some(main_message);
+    fn apply_setup(&mut self, setup: Setup) {
+        match setup {
+            Setup::ServerSelected {
+                profile,
+                server,
+                upgrade,
+            } => {
+                start_loading_medias_download(&server);
+                self.server = Some(server);
+                self.profile = profile;
+                self.player = None;
+                self.setup_startup_engine(upgrade);
+            }
+            Setup::Startup(upgrade) => self.setup_startup_engine(upgrade),
+            Setup::LoggedOut => self.setup_login_engine(None),
+            Setup::Description {
+                description,
+                client,
+                back_url,
+                startup,
+            } => {
+                let player = if startup { None } else { self.player.clone() };
+                if startup {
+                    self.setup_home_image_background();
+                } else {
+                    self.setup_no_home_image();
+                }
+                self.engine = Some(Box::new(DescriptionEngine::new(
+                    player,
+                    description.clone(),
+                    client,
+                    back_url,
+                    startup,
+                    TileSheet::new(self.tile_sheet_image.clone(), TILE_WIDTH, TILE_HEIGHT),
+                )));
+                self.pending_illustration = description.illustration_name;
+                self.illustration = None;
+                self.illustration_bg = None;
+            }
+            Setup::Zone {
+                player,
+                request_clicks,
+            } => {
+                self.player = Some(player);
+                self.setup_zone_engine(request_clicks);
+            }
+        }
+        self.loading_image_to_set = true;
+    }
+
+    fn proceed_pending_setup(&mut self) {
+        let result = match self.pending_setup.as_ref() {
+            Some((pending_request, _)) => match pending_request.try_take() {
+                Some(result) => result,
+                None => return,
+            },
+            None => return,
+        };
+        let (_, main_message) = self.pending_setup.take().unwrap();
+
+        match result {
+            Ok(setup) => self.apply_setup(setup),
+            Err(error) => {
+                let context = main_message_context(&main_message);
+                self.setup_error_engine(RollingError::from(error), context, Some(main_message));
+            }
+        }
     }
 }
 
+/// Start background task of loading screen downloads
+fn start_loading_medias_download(server: &server::Server) {
+    let server = server.clone();
+    thread::spawn(move || {
+        match server.client.get_loading_media_names() {
+            Ok(loading_media_names) => {
+                for loading_media_name in loading_media_names {
+                    if let Err(error) = server.client.cache_media(&loading_media_name) {
+                        eprintln!(
+                            "Error when download loading media {} : {}",
+                            loading_media_name, error
+                        );
+                    }
+                }
+            }
+            Err(error) => {
+                eprintln!("Error when get loading media names : {}", error);
+            }
+        };
+    });
+}
+
 impl Game for MyGame {
     type Input = MyGameInput;
     type LoadingScreen = loading_screen::ProgressBar;
@@ -466,74 +967,113 @@ impl Game for MyGame {
             .parent()
             .unwrap()
             .join("client.db");
-        let db = if db_in_parent_folder_path.is_file() {
-            get_db(db_in_parent_folder_path.to_str().unwrap())
+        let (db, key_file_path) = if db_in_parent_folder_path.is_file() {
+            (
+                get_db(db_in_parent_folder_path.to_str().unwrap()),
+                db_in_parent_folder_path.with_file_name(credentials::KEY_FILE_NAME),
+            )
         } else {
-            get_db("client.db")
+            (
+                get_db("client.db"),
+                PathBuf::from(credentials::KEY_FILE_NAME),
+            )
         };
 
         let opt = args::Opt::from_args();
         let conf = get_conf(&opt.config_file_path);
-        let pending_home_image: Option<String> = match conf.get_from(Some("design"), "home_image") {
-            None => None,
-            Some(file_path) => Some(String::from(file_path)),
-        };
-        let server_hostname = conf.get_from(Some("server"), "server_hostname").unwrap();
-        let server_port = conf
-            .get_from(Some("server"), "server_port")
-            .unwrap()
-            .parse::<u16>()
-            .unwrap();
-        let server_unsecure = match conf.get_from(Some("server"), "unsecure").unwrap_or("false") {
-            "true" | "True" | "1" => true,
-            _ => false,
+        let media_cache = MediaCache::from_conf(&conf);
+        let request_policy = RequestPolicy::from_conf(&conf);
+        let movement_settings = MovementSettings::from_conf(&conf);
+        let network_config = match NetworkConfig::from_conf(&conf) {
+            Ok(network_config) => network_config,
+            Err(error) => {
+                eprintln!("Config error : {}", error);
+                dialog::Message::new(format!("Configuration invalide : {}", error))
+                    .title("Erreur")
+                    .show()
+                    .expect("Could not display dialog box");
+                exit(1)
+            }
         };
-        let server_address = if server_unsecure {
-            server::ServerAddress::unsecure(server_hostname, server_port)
-        } else {
-            server::ServerAddress::new(server_hostname, server_port)
+        let record_zone_events = opt.record_zone_events
+            || matches!(
+                conf.get_from(Some("debug"), "record_zone_events"),
+                Some("true") | Some("True") | Some("1")
+            );
+        let replay = match &opt.replay {
+            Some(recording_folder) => match record::load_recording(recording_folder) {
+                Ok((snapshot, events)) => Some((snapshot, events, opt.replay_speed)),
+                Err(error) => {
+                    eprintln!("Recording error : {}", error);
+                    dialog::Message::new(format!("Enregistrement illisible : {}", error))
+                        .title("Erreur")
+                        .show()
+                        .expect("Could not display dialog box");
+                    exit(1)
+                }
+            },
+            None => None,
         };
-        let server = match server::Server::new(
-            server::client::Client::new(server_address.clone(), ("".to_string(), "".to_string())),
-            server_address,
-            None,
-        ) {
-            Ok(server) => server,
-            Err(err) => {
-                eprintln!("Connexion error : {}", err);
-                dialog::Message::new("Erreur de connexion")
+        let profiles = match ServerProfile::load_all(&conf) {
+            Ok(profiles) => profiles,
+            Err(error) => {
+                eprintln!("Config error : {}", error);
+                dialog::Message::new(format!("Configuration invalide : {}", error))
                     .title("Erreur")
                     .show()
                     .expect("Could not display dialog box");
                 exit(1)
             }
         };
-
-        // Start background task of loading screen downloads
-        let server_ = server.clone();
-        thread::spawn(move || {
-            match server_.client.get_loading_media_names() {
-                Ok(loading_media_names) => {
-                    for loading_media_name in loading_media_names {
-                        server_.client.cache_media(&loading_media_name).unwrap();
+        // Until player choose one, first profile is used for design
+        let profile = profiles[0].clone();
+        let pending_home_image = profile.home_image.clone();
+
+        // Replay don't need server: world comes from recording
+        let server = match &replay {
+            Some((snapshot, _, _)) => {
+                let client = server::client::Client::new(
+                    profile.address.clone(),
+                    server::client::Auth::Anonymous,
+                )
+                .with_media_cache(media_cache.clone());
+                match server::Server::from_world_source(
+                    client,
+                    profile.address.clone(),
+                    Some(snapshot.player.id.clone()),
+                    snapshot.world_source.clone(),
+                ) {
+                    Ok(server) => Some(server),
+                    Err(err) => {
+                        eprintln!("Recording error : {}", err);
+                        dialog::Message::new("Enregistrement illisible")
+                            .title("Erreur")
+                            .show()
+                            .expect("Could not display dialog box");
+                        exit(1)
                     }
                 }
-                Err(error) => {
-                    eprintln!("Error when get loading media names : {}", error);
-                }
-            };
-        });
+            }
+            None => None,
+        };
 
-        graphics::Image::load("resources/graphics.png").map(|image| MyGame {
+        graphics::Image::load("resources/graphics.png").map(move |image| MyGame {
             conf,
             engine: None,
             tile_sheet_image: image,
             db,
+            key_file_path,
+            media_cache,
+            request_policy,
+            network_config,
+            movement_settings,
+            profiles,
+            profile,
             server,
             player: None,
             exit_requested: false,
-            pending_action: None,
-            loading_displayed: false,
+            pending_setup: None,
+            zone_bootstrap: None,
             last_tick: SystemTime::now(),
             pending_illustration: None,
             illustration: None,
@@ -542,6 +1082,8 @@ impl Game for MyGame {
             home_image: None,
             loading_image_to_set: false,
             loading_image: None,
+            record_zone_events,
+            replay,
         })
     }
 
@@ -555,7 +1097,10 @@ impl Game for MyGame {
             self.illustration = None;
             self.illustration_bg = None;
 
-            match graphics::Image::new(window.gpu(), format!("cache/{}", &pending_illustration)) {
+            let illustration_path = self
+                .media_cache
+                .path(MediaKind::Media, &pending_illustration);
+            match graphics::Image::new(window.gpu(), illustration_path) {
                 Ok(image) => self.illustration = Some(image),
                 Err(error) => {
                     eprintln!(
@@ -565,8 +1110,10 @@ impl Game for MyGame {
                 }
             };
 
-            match graphics::Image::new(window.gpu(), format!("cache/bg/{}", &pending_illustration))
-            {
+            let illustration_bg_path = self
+                .media_cache
+                .path(MediaKind::MediaBackground, &pending_illustration);
+            match graphics::Image::new(window.gpu(), illustration_bg_path) {
                 Ok(image) => self.illustration_bg = Some(image),
                 Err(error) => {
                     eprintln!(
@@ -596,7 +1143,10 @@ impl Game for MyGame {
         if self.loading_image_to_set {
             self.loading_image_to_set = false;
             let mut loadings: Vec<String> = vec![];
-            for path in glob("cache/loading__*.png").expect("Failed to read glob pattern") {
+            let loadings_pattern = self.media_cache.path(MediaKind::Media, "loading__*.png");
+            for path in
+                glob(&loadings_pattern.to_string_lossy()).expect("Failed to read glob pattern")
+            {
                 match path {
                     Ok(path) => match path.to_str() {
                         Some(path_) => loadings.push(path_.to_string()),
@@ -620,6 +1170,10 @@ impl Game for MyGame {
             }
         }
 
+        if self.pending_setup.is_some() || self.zone_bootstrap.is_some() {
+            return;
+        }
+
         match self.engine.as_mut().unwrap().interact(input, window) {
             Some(main_message) => self.proceed_main_message(main_message),
             None => {}
@@ -627,121 +1181,32 @@ impl Game for MyGame {
     }
 
     fn update(&mut self, window: &Window) {
-        if self.engine.is_none() {
-            self.setup_home_image();
-            self.engine = Some(self.create_startup_engine(false));
-        }
-
-        if self.loading_displayed {
-            let main_message = self.pending_action.as_ref().unwrap().clone();
-            self.pending_action = None;
-            self.loading_displayed = false;
-
-            match main_message {
-                MainMessage::StartupToZone {
-                    disable_version_check,
-                } => {
-                    println!("Set startup engine");
-                    self.setup_home_image();
-                    self.engine = Some(self.create_startup_engine(disable_version_check));
-                    self.loading_image_to_set = true;
-                }
-                MainMessage::ToDescriptionWithDescription {
-                    description,
-                    back_url,
-                    client,
-                } => {
-                    let player = match self.player.as_ref() {
-                        None => None,
-                        Some(player) => Some(player.clone()),
-                    };
-                    self.setup_no_home_image();
-                    self.engine = Some(Box::new(DescriptionEngine::new(
-                        player,
-                        description.clone(),
-                        client,
-                        back_url.clone(),
-                        false,
-                        TileSheet::new(self.tile_sheet_image.clone(), TILE_WIDTH, TILE_HEIGHT),
-                    )));
-                    self.pending_illustration = description.illustration_name;
-                    self.illustration = None;
-                    self.illustration_bg = None;
-                    self.loading_image_to_set = true;
-                }
-                MainMessage::CreateAccount { address } => {
-                    self.setup_create_account(address);
-                }
-                MainMessage::AccountCreated => {
-                    self.setup_home_image();
-                    self.engine = Some(self.create_startup_engine(true));
-                }
-                MainMessage::NewCharacterId { character_id } => {
-                    self.server.character_id = Some(character_id.clone());
-                    self.setup_startup_to_zone_engine(None);
-                }
-                MainMessage::EnterServer {
-                    credentials,
-                    character_id,
-                } => {
-                    println!("Enter server");
-                    self.server.client.credentials = credentials;
-                    self.server.character_id = character_id;
-                    self.set_server_last_username();
-
-                    if self.server.character_id.is_none() {
-                        self.setup_create_character();
-                    } else {
-                        self.setup_startup_to_zone_engine(None);
-                    }
-                }
-                MainMessage::ToDescriptionWithUrl { url, back_url } => {
-                    // FIXME: manage errors
-                    let description = self.server.client.describe(&url, None, None).unwrap();
-                    let player = if let Some(player) = self.player.as_ref() {
-                        Some(player.clone())
-                    } else {
-                        None
-                    };
-                    self.setup_no_home_image();
-                    self.engine = Some(Box::new(DescriptionEngine::new(
-                        player,
-                        description.clone(),
-                        self.server.client.clone(),
-                        back_url.clone(),
-                        false,
-                        TileSheet::new(self.tile_sheet_image.clone(), TILE_WIDTH, TILE_HEIGHT),
-                    )));
-                    self.pending_illustration = description.illustration_name;
-                    self.illustration = None;
-                    self.illustration_bg = None;
-                    self.loading_image_to_set = true;
-                }
-                MainMessage::DescriptionToZone { request_clicks } => {
-                    self.setup_startup_to_zone_engine(request_clicks);
-                    self.loading_image_to_set = true;
-                }
-                MainMessage::ToStartup => {
-                    self.setup_home_image();
-                    self.engine = Some(self.create_startup_engine(true));
-                }
-                MainMessage::ToExit => {
-                    self.setup_home_image_background();
-                    self.engine = Some(Box::new(ExitEngine::new()));
-                }
-                MainMessage::ExitRequested => self.exit_requested = true,
-                MainMessage::ToWorld => {
-                    self.setup_no_home_image();
-                    self.engine = Some(Box::new(WorldEngine::new(
-                        self.server.clone(),
-                        self.tile_sheet_image.clone(),
-                        self.player.as_ref().unwrap().clone(),
-                    )));
-                    self.loading_image_to_set = true;
-                }
+        if let Some((snapshot, events, speed)) = self.replay.take() {
+            if let Err(error) = self.setup_replay_engine(snapshot, events, speed) {
+                self.setup_error_engine(error, "Lecture de l'enregistrement", None);
+            }
+        }
+
+        if self.engine.is_none() && self.pending_setup.is_none() {
+            if self.profiles.len() > 1 {
+                self.setup_server_picker_engine();
+            } else {
+                self.proceed_main_message(MainMessage::ServerProfileSelected {
+                    profile_id: self.profiles[0].id.clone(),
+                });
             }
         }
 
+        if self.pending_setup.is_some() {
+            self.proceed_pending_setup();
+            return;
+        }
+
+        if self.zone_bootstrap.is_some() {
+            self.proceed_zone_bootstrap();
+            return;
+        }
+
         match self.engine.as_mut().unwrap().update(window) {
             Some(main_message) => self.proceed_main_message(main_message),
             None => {}
@@ -749,7 +1214,7 @@ impl Game for MyGame {
     }
 
     fn draw(&mut self, frame: &mut Frame, timer: &Timer) {
-        if self.engine.is_none() {
+        if self.engine.is_none() && self.pending_setup.is_none() {
             return;
         }
 
@@ -757,7 +1222,7 @@ impl Game for MyGame {
         util::sleep_if_required(TARGET_FRAME_DURATION_MS, &self.last_tick);
         self.last_tick = SystemTime::now();
 
-        if self.pending_action.is_some() {
+        if self.pending_setup.is_some() || self.zone_bootstrap.is_some() {
             frame.clear(Color::BLACK);
         } else {
             if self.illustration_bg.is_some() {
@@ -806,12 +1271,10 @@ impl UserInterface for MyGame {
 
     fn layout(&mut self, window: &Window) -> Element {
         if self.engine.is_none()
-            || self.pending_action.is_some()
+            || self.pending_setup.is_some()
+            || self.zone_bootstrap.is_some()
             || self.pending_illustration.is_some()
         {
-            if self.pending_action.is_some() {
-                self.loading_displayed = true;
-            }
             let mut column = Column::new()
                 .width(window.width() as u32)
                 .height(window.height() as u32)
@@ -819,9 +1282,14 @@ impl UserInterface for MyGame {
                 .justify_content(Justify::Center)
                 .spacing(20);
 
+            // Keep place for zone loading progress
+            let loading_image_height = if self.zone_bootstrap.is_some() {
+                window.height() * 0.4
+            } else {
+                window.height() * 0.8
+            };
             if let Some(loading_image) = &self.loading_image {
-                column =
-                    column.push(Image::new(loading_image).height((window.height() * 0.8) as u32));
+                column = column.push(Image::new(loading_image).height(loading_image_height as u32));
             }
 
             column = column.push(
@@ -831,6 +1299,7 @@ impl UserInterface for MyGame {
                     .horizontal_alignment(HorizontalAlignment::Center)
                     .vertical_alignment(VerticalAlignment::Center),
             );
+            column = self.zone_bootstrap_layout(column);
 
             column.into()
         } else {
@@ -841,3 +1310,50 @@ impl UserInterface for MyGame {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn profile(id: &str) -> ServerProfile {
+        ServerProfile {
+            id: id.to_string(),
+            name: id.to_string(),
+            address: server::ServerAddress::new("127.0.0.1", 5000),
+            releases_url: None,
+            home_image: None,
+            home_image_background: None,
+        }
+    }
+
+    #[test]
+    fn test_route_main_message_without_server() {
+        let single = vec![profile("default")];
+        let several = vec![profile("default"), profile("s2")];
+
+        assert!(matches!(
+            route_main_message(MainMessage::ToStartup, false, &single),
+            MainMessage::ServerProfileSelected { profile_id } if profile_id == "default"
+        ));
+        assert!(matches!(
+            route_main_message(
+                MainMessage::ForgetCredentials { message: None },
+                false,
+                &several
+            ),
+            MainMessage::ToServerPicker
+        ));
+        assert!(matches!(
+            route_main_message(MainMessage::ToExit, false, &several),
+            MainMessage::ToExit
+        ));
+    }
+
+    #[test]
+    fn test_route_main_message_with_server() {
+        assert!(matches!(
+            route_main_message(MainMessage::ToStartup, true, &[profile("default")]),
+            MainMessage::ToStartup
+        ));
+    }
+}
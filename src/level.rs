@@ -1,6 +1,7 @@
 use crate::error::RollingError;
 use crate::tile::zone::Tiles;
 use crate::util;
+use serde_json::Value;
 
 #[derive(Debug)]
 pub struct LevelRow {
@@ -52,6 +53,20 @@ impl Level {
         })
     }
 
+    /// Level of zone data given by server (its `raw_source` GEO block)
+    pub fn from_zone_data(
+        zone_data: &Value,
+        tiles: &Tiles,
+        world_tile_type_id: String,
+    ) -> Result<Self, RollingError> {
+        let zone_raw = match zone_data["raw_source"].as_str() {
+            Some(zone_raw) => zone_raw,
+            None => return Err(RollingError::new("Zone data has no raw_source".to_string())),
+        };
+        let zone_raw = util::extract_block_from_source(util::BLOCK_GEO, zone_raw)?;
+        Self::new(&zone_raw, tiles, world_tile_type_id)
+    }
+
     // pub fn render(
     //     &mut self,
     //     api: &mut dyn DoryenApi,
@@ -124,30 +139,4 @@ impl Level {
 
         row.cols[col_i as usize].clone()
     }
-
-    pub fn get_successors(&self, tiles: &Tiles, row_i: i16, col_i: i16) -> Vec<((i16, i16), u32)> {
-        let mut successors = vec![];
-
-        for (modifier_row_i, modifier_col_i) in [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            // no center pos
-            (0, 1),
-            (1, 1),
-            (1, -1),
-            (1, 0),
-        ]
-        .iter()
-        {
-            let new_row_i = row_i + *modifier_row_i;
-            let new_col_i = col_i + *modifier_col_i;
-            if tiles.browseable(&self.tile_id(new_row_i, new_col_i)) {
-                successors.push(((new_row_i, new_col_i), 1));
-            }
-        }
-
-        successors
-    }
 }
@@ -1,3 +1,4 @@
+use crate::server::media_cache::MediaCache;
 use crate::util::get_conf;
 use coffee::graphics::WindowSettings;
 use coffee::ui::UserInterface;
@@ -5,6 +6,8 @@ use coffee::Result;
 use structopt::StructOpt;
 
 pub mod args;
+pub mod camera;
+pub mod context_menu;
 pub mod engine;
 pub mod entity;
 pub mod error;
@@ -14,17 +17,65 @@ pub mod gui;
 pub mod input;
 pub mod level;
 pub mod message;
+pub mod minimap;
+pub mod movement;
 pub mod server;
 pub mod sheet;
 pub mod socket;
+pub mod state;
 pub mod tile;
+pub mod tooltip;
 pub mod ui;
 pub mod util;
 pub mod world;
 
+/// Execute media cache command line actions. Return true if one was executed.
+fn run_cache_commands(opt: &args::Opt, media_cache: &MediaCache) -> bool {
+    if opt.cache_info {
+        let entries = media_cache.entries();
+        for (path, entry) in entries.iter() {
+            println!(
+                "{:>10} {}{}",
+                entry.size,
+                path,
+                if entry.hash.is_none() {
+                    " (unverified)"
+                } else {
+                    ""
+                }
+            );
+        }
+        println!(
+            "{} medias in {}, {:.1} MB used of {:.1} MB",
+            entries.len(),
+            media_cache.folder().display(),
+            media_cache.total_size() as f64 / (1024.0 * 1024.0),
+            media_cache.max_size() as f64 / (1024.0 * 1024.0),
+        );
+    }
+    if opt.cache_verify {
+        let corrupted = media_cache.verify();
+        for path in corrupted.iter() {
+            println!("Removed corrupted media {}", path);
+        }
+        println!("{} corrupted medias", corrupted.len());
+    }
+    if opt.cache_purge {
+        match media_cache.purge() {
+            Ok(count) => println!("{} medias removed", count),
+            Err(error) => eprintln!("Unable to purge media cache: {}", error),
+        }
+    }
+
+    opt.cache_info || opt.cache_verify || opt.cache_purge
+}
+
 pub fn main() -> Result<()> {
     let opt = args::Opt::from_args();
     let conf = get_conf(&opt.config_file_path);
+    if run_cache_commands(&opt, &MediaCache::from_conf(&conf)) {
+        return Ok(());
+    }
     match conf
         .get_from(Some("debug"), "enable_bug_report")
         .unwrap_or("false")
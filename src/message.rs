@@ -4,6 +4,15 @@ use crate::server;
 #[derive(Debug, Clone)]
 pub enum MainMessage {
     ToStartup,
+    ToServerPicker,
+    ServerProfileSelected {
+        profile_id: String,
+    },
+    ForgetCredentials {
+        message: Option<String>,
+    },
+    /// Invalidate remembered session token (if any) then forget credentials
+    Logout,
     ToExit,
     ToWorld,
     ExitRequested,
@@ -30,7 +39,8 @@ pub enum MainMessage {
         back_url: Option<String>,
     },
     EnterServer {
-        credentials: (String, String),
+        auth: server::client::Auth,
+        remember: bool,
         character_id: Option<String>,
     },
 }
@@ -44,8 +54,6 @@ pub enum Message {
     PasswordLostButtonPressed,
     CreateAccountButtonPressed,
     ResetProgressBar,
-    LocalServerPressed,
-    S2BuxFrServerPressed,
     TextInputSelected(i32),
     LoginInputSelected(i32),
     PasswordInputSelected(i32),
@@ -91,8 +99,17 @@ pub enum Message {
     PreviousChatButtonPressed,
     NextChatButtonPressed,
     DismissRequestClicks,
+    ReloadZonePressed,
     DismissChat,
     RequestChat(Option<i32>),
     QuickActionPressed(String),
     QuickActionReleased(String),
+    RetryButtonPressed,
+    CrashReportButtonPressed,
+    ServerProfilePressed(i32),
+    RefreshServersButtonPressed,
+    ChangeServerButtonPressed,
+    RememberMeToggled(bool),
+    RememberedLoginButtonPressed,
+    LogoutButtonPressed,
 }
@@ -0,0 +1,219 @@
+use crate::error::RollingError;
+use crate::state::ZoneState;
+use crate::tile::zone::NOTHING;
+use crate::tile::TransportType;
+use coffee::graphics::{Gpu, Image, Point, Quad, Rectangle, Target};
+
+const MAX_SIZE: f32 = 200.0;
+const WALKABLE_COLOR: [u8; 4] = [86, 140, 70, 255];
+const SWIMMABLE_COLOR: [u8; 4] = [50, 100, 190, 255];
+const BLOCKED_COLOR: [u8; 4] = [90, 90, 90, 255];
+const BUILD_COLOR: [u8; 4] = [150, 110, 60, 255];
+const NOTHING_COLOR: [u8; 4] = [0, 0, 0, 0];
+
+/// Zone overview, one pixel by tile. Its texture is built again only when zone tiles or builds
+/// change.
+pub struct Minimap {
+    image: Option<Image>,
+    outdated: bool,
+    pub visible: bool,
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self {
+            image: None,
+            outdated: true,
+            visible: true,
+        }
+    }
+}
+
+impl Minimap {
+    /// Texture must be built again at next draw
+    pub fn invalidate(&mut self) {
+        self.outdated = true;
+    }
+
+    /// Screen area of minimap, with its bottom right corner at given position
+    pub fn area(state: &ZoneState, bottom_right: Point) -> Rectangle<f32> {
+        let (width, height) = Self::size(state);
+        let tile_size = Self::tile_size(state);
+        Rectangle {
+            x: bottom_right.x - width as f32 * tile_size,
+            y: bottom_right.y - height as f32 * tile_size,
+            width: width as f32 * tile_size,
+            height: height as f32 * tile_size,
+        }
+    }
+
+    /// Zone tile (row_i, col_i) at given screen position, if position is on minimap
+    pub fn tile_at(state: &ZoneState, area: &Rectangle<f32>, point: Point) -> Option<(i16, i16)> {
+        if point.x < area.x
+            || point.y < area.y
+            || point.x >= area.x + area.width
+            || point.y >= area.y + area.height
+        {
+            return None;
+        }
+        let tile_size = Self::tile_size(state);
+        Some((
+            ((point.y - area.y) / tile_size) as i16,
+            ((point.x - area.x) / tile_size) as i16,
+        ))
+    }
+
+    /// Screen position of given zone tile center
+    pub fn tile_center(state: &ZoneState, area: &Rectangle<f32>, tile: (i16, i16)) -> Point {
+        let tile_size = Self::tile_size(state);
+        Point::new(
+            area.x + (tile.1 as f32 + 0.5) * tile_size,
+            area.y + (tile.0 as f32 + 0.5) * tile_size,
+        )
+    }
+
+    /// Minimap pixels size of a tile
+    pub fn tile_size(state: &ZoneState) -> f32 {
+        let (width, height) = Self::size(state);
+        MAX_SIZE / width.max(height).max(1) as f32
+    }
+
+    fn size(state: &ZoneState) -> (u32, u32) {
+        (
+            state.level.width.max(0) as u32,
+            state.level.rows.len() as u32,
+        )
+    }
+
+    /// Rgba pixels of zone tiles and builds, row by row
+    pub fn pixels(state: &ZoneState) -> Vec<u8> {
+        let (width, height) = Self::size(state);
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+
+        for row_i in 0..height as i16 {
+            for col_i in 0..width as i16 {
+                let tile_id = state.level.tile_id(row_i, col_i);
+                let color = if state.has_build_at(row_i, col_i) {
+                    BUILD_COLOR
+                } else if tile_id == NOTHING {
+                    NOTHING_COLOR
+                } else if state.tiles.traversable(&tile_id, TransportType::Walking) {
+                    WALKABLE_COLOR
+                } else if state.tiles.traversable(&tile_id, TransportType::Swimming) {
+                    SWIMMABLE_COLOR
+                } else {
+                    BLOCKED_COLOR
+                };
+                pixels.extend_from_slice(&color);
+            }
+        }
+
+        pixels
+    }
+
+    fn build_image(gpu: &mut Gpu, state: &ZoneState) -> Result<Image, RollingError> {
+        let (width, height) = Self::size(state);
+        let image =
+            image::RgbaImage::from_raw(width, height, Self::pixels(state)).ok_or(RollingError {
+                message: "Invalid minimap size".to_string(),
+            })?;
+        Image::from_image(gpu, &image::DynamicImage::ImageRgba8(image)).map_err(|error| {
+            RollingError {
+                message: format!("Error when create minimap image : {}", error),
+            }
+        })
+    }
+
+    /// Build texture again if outdated
+    pub fn prepare(&mut self, gpu: &mut Gpu, state: &ZoneState) {
+        if self.outdated {
+            self.outdated = false;
+            self.image = match Self::build_image(gpu, state) {
+                Ok(image) => Some(image),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    None
+                }
+            };
+        }
+    }
+
+    /// Draw zone texture in given area
+    pub fn draw(&self, target: &mut Target<'_>, area: &Rectangle<f32>) {
+        if let Some(image) = &self.image {
+            image.draw(
+                Quad {
+                    source: Rectangle {
+                        x: 0.0,
+                        y: 0.0,
+                        width: 1.0,
+                        height: 1.0,
+                    },
+                    position: Point::new(area.x, area.y),
+                    size: (area.width, area.height),
+                },
+                target,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::entity::build::Build;
+    use crate::state::test_state;
+    use std::collections::HashMap;
+
+    fn state() -> ZoneState {
+        let mut builds = HashMap::new();
+        builds.insert(
+            1,
+            Build {
+                id: 1,
+                build_id: "WALL".to_string(),
+                row_i: 1,
+                col_i: 1,
+                classes: vec![],
+                traversable: HashMap::new(),
+                is_floor: false,
+            },
+        );
+        test_state("⡩~#⡩\n⡩⡩").with_builds(builds)
+    }
+
+    #[test]
+    fn test_pixels() {
+        let pixels = Minimap::pixels(&state());
+
+        assert_eq!(4 * 2 * 4, pixels.len());
+        assert_eq!(&WALKABLE_COLOR, &pixels[0..4]);
+        assert_eq!(&SWIMMABLE_COLOR, &pixels[4..8]);
+        assert_eq!(&BLOCKED_COLOR, &pixels[8..12]);
+        assert_eq!(&BUILD_COLOR, &pixels[20..24]);
+        // Second row is shorter than first one
+        assert_eq!(&NOTHING_COLOR, &pixels[28..32]);
+    }
+
+    #[test]
+    fn test_tile_at() {
+        let state = state();
+        let area = Minimap::area(&state, Point::new(1000.0, 500.0));
+
+        assert_eq!(50.0, Minimap::tile_size(&state));
+        assert_eq!(800.0, area.x);
+        assert_eq!(400.0, area.y);
+        assert_eq!(
+            Some((1, 2)),
+            Minimap::tile_at(&state, &area, Point::new(920.0, 460.0))
+        );
+        assert_eq!(
+            None,
+            Minimap::tile_at(&state, &area, Point::new(790.0, 460.0))
+        );
+        assert_eq!(
+            Point::new(925.0, 475.0),
+            Minimap::tile_center(&state, &area, (1, 2))
+        );
+    }
+}
@@ -0,0 +1,399 @@
+use ini::Ini;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+const DEFAULT_PLAYER_SPEED: f32 = 2.0;
+const DEFAULT_RUN_FACTOR: f32 = 3.0;
+const DEFAULT_ENTITY_MOVE_DURATION_MS: u64 = 300;
+// Server may not send back player moves: forget unconfirmed ones after this delay
+const PENDING_MOVE_TIMEOUT_MS: u64 = 5000;
+
+/// Zone movement settings
+#[derive(Debug, Clone)]
+pub struct MovementSettings {
+    /// Player speed, in tiles per second
+    pub player_speed: f32,
+    /// Player speed factor when shift key is pressed
+    pub run_factor: f32,
+    /// Duration of other characters and animated corpses move from a tile to another
+    pub entity_move_duration: Duration,
+}
+
+impl Default for MovementSettings {
+    fn default() -> Self {
+        Self {
+            player_speed: DEFAULT_PLAYER_SPEED,
+            run_factor: DEFAULT_RUN_FACTOR,
+            entity_move_duration: Duration::from_millis(DEFAULT_ENTITY_MOVE_DURATION_MS),
+        }
+    }
+}
+
+impl MovementSettings {
+    /// Read `[movement]` section, missing or invalid values keep their default value
+    pub fn from_conf(conf: &Ini) -> Self {
+        let value = |key: &str, default: f32| -> f32 {
+            match conf.get_from(Some("movement"), key) {
+                Some(value) => match value.parse::<f32>() {
+                    Ok(value) if value > 0.0 => value,
+                    _ => {
+                        eprintln!("Invalid {} in movement config: {}", key, value);
+                        default
+                    }
+                },
+                None => default,
+            }
+        };
+
+        Self {
+            player_speed: value("player_speed", DEFAULT_PLAYER_SPEED),
+            run_factor: value("run_factor", DEFAULT_RUN_FACTOR),
+            entity_move_duration: Duration::from_millis(value(
+                "entity_move_duration_ms",
+                DEFAULT_ENTITY_MOVE_DURATION_MS as f32,
+            ) as u64),
+        }
+    }
+
+    /// Duration of a player step between two tiles (diagonal steps are longer)
+    pub fn player_step_duration(
+        &self,
+        from: (i16, i16),
+        to: (i16, i16),
+        running: bool,
+    ) -> Duration {
+        let speed = if running {
+            self.player_speed * self.run_factor
+        } else {
+            self.player_speed
+        };
+        Duration::from_secs_f32(distance(from, to) / speed)
+    }
+}
+
+fn distance(from: (i16, i16), to: (i16, i16)) -> f32 {
+    let rows = (to.0 - from.0) as f32;
+    let cols = (to.1 - from.1) as f32;
+    (rows * rows + cols * cols).sqrt()
+}
+
+/// Move from a position to a tile during given duration. Position is interpolated from
+/// elapsed time, so move speed doesn't depend on frame rate.
+#[derive(Debug, Clone)]
+pub struct TileStep {
+    // row_i, col_i (can be between two tiles if step started during another one)
+    from: (f32, f32),
+    to: (i16, i16),
+    start: Instant,
+    duration: Duration,
+}
+
+impl TileStep {
+    pub fn new(from: (i16, i16), to: (i16, i16), start: Instant, duration: Duration) -> Self {
+        Self::from_position((from.0 as f32, from.1 as f32), to, start, duration)
+    }
+
+    pub fn from_position(
+        from: (f32, f32),
+        to: (i16, i16),
+        start: Instant,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            start,
+            duration,
+        }
+    }
+
+    pub fn to(&self) -> (i16, i16) {
+        self.to
+    }
+
+    pub fn end(&self) -> Instant {
+        self.start + self.duration
+    }
+
+    pub fn is_finished(&self, now: Instant) -> bool {
+        now >= self.end()
+    }
+
+    /// Step progression, from 0.0 to 1.0
+    pub fn progress(&self, now: Instant) -> f32 {
+        if self.duration.as_nanos() == 0 {
+            return 1.0;
+        }
+        let elapsed = now.saturating_duration_since(self.start);
+        (elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+    }
+
+    /// Interpolated (row_i, col_i) position, in tiles
+    pub fn position(&self, now: Instant) -> (f32, f32) {
+        let progress = self.progress(now);
+        (
+            self.from.0 + (self.to.0 as f32 - self.from.0) * progress,
+            self.from.1 + (self.to.1 as f32 - self.from.1) * progress,
+        )
+    }
+
+    /// Interpolated (x, y) pixel position of tile top left corner
+    pub fn pixel_position(&self, now: Instant, tile_width: i16, tile_height: i16) -> (i16, i16) {
+        let (row_i, col_i) = self.position(now);
+        (
+            (col_i * tile_width as f32).round() as i16,
+            (row_i * tile_height as f32).round() as i16,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Facing {
+    Left,
+    #[default]
+    Right,
+}
+
+impl Facing {
+    /// Facing after a move between given tiles, None for vertical moves
+    pub fn of_move(from: (i16, i16), to: (i16, i16)) -> Option<Self> {
+        if to.1 < from.1 {
+            Some(Facing::Left)
+        } else if to.1 > from.1 {
+            Some(Facing::Right)
+        } else {
+            None
+        }
+    }
+
+    /// Sheet tile id to use for given tile id (left facing tiles are suffixed by `_LEFT`)
+    pub fn tile_id(&self, tile_id: &str) -> String {
+        match self {
+            Facing::Left => format!("{}_LEFT", tile_id),
+            Facing::Right => tile_id.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Motion {
+    step: Option<TileStep>,
+    facing: Facing,
+}
+
+/// Tweened moves of zone entities (other characters, animated corpses), by entity id
+#[derive(Debug, Clone)]
+pub struct Motions<K: Hash + Eq> {
+    motions: HashMap<K, Motion>,
+}
+
+impl<K: Hash + Eq> Default for Motions<K> {
+    fn default() -> Self {
+        Self {
+            motions: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq> Motions<K> {
+    /// Slide entity from its current (maybe interpolated) position to given tile
+    pub fn start(
+        &mut self,
+        id: K,
+        from: (i16, i16),
+        to: (i16, i16),
+        now: Instant,
+        duration: Duration,
+    ) {
+        let motion = self.motions.entry(id).or_default();
+        let from_position = match &motion.step {
+            Some(step) if !step.is_finished(now) => step.position(now),
+            _ => (from.0 as f32, from.1 as f32),
+        };
+        if let Some(facing) = Facing::of_move(from, to) {
+            motion.facing = facing;
+        }
+        motion.step = Some(TileStep::from_position(from_position, to, now, duration));
+    }
+
+    /// Pixel (x, y) position of entity standing (or arriving) on given tile
+    pub fn pixel_position(
+        &self,
+        id: &K,
+        position: (i16, i16),
+        now: Instant,
+        tile_width: i16,
+        tile_height: i16,
+    ) -> (i16, i16) {
+        match self.motions.get(id).and_then(|motion| motion.step.as_ref()) {
+            Some(step) if step.to() == position => {
+                step.pixel_position(now, tile_width, tile_height)
+            }
+            _ => (position.1 * tile_width, position.0 * tile_height),
+        }
+    }
+
+    pub fn facing(&self, id: &K) -> Facing {
+        self.motions
+            .get(id)
+            .map(|motion| motion.facing)
+            .unwrap_or_default()
+    }
+
+    /// Forget motions of entities which are no longer in zone
+    pub fn retain<F: Fn(&K) -> bool>(&mut self, keep: F) {
+        self.motions.retain(|id, _| keep(id));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reconciliation {
+    /// Server position is one of predicted ones
+    Confirmed,
+    /// Server position was not predicted, player must be moved to it
+    Correction,
+}
+
+/// Local player moves sent to server and not yet confirmed by it, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct PendingMoves {
+    moves: VecDeque<((i16, i16), Instant)>,
+}
+
+impl PendingMoves {
+    pub fn push(&mut self, tile: (i16, i16), sent_at: Instant) {
+        self.moves.push_back((tile, sent_at));
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Compare server position of player with pending moves. Confirmed move and older ones
+    /// are acknowledged, all of them are dropped on correction.
+    pub fn reconcile(&mut self, server_tile: (i16, i16), now: Instant) -> Reconciliation {
+        let timeout = Duration::from_millis(PENDING_MOVE_TIMEOUT_MS);
+        self.moves
+            .retain(|(_, sent_at)| now.saturating_duration_since(*sent_at) < timeout);
+
+        match self.moves.iter().position(|(tile, _)| *tile == server_tile) {
+            Some(index) => {
+                self.moves.drain(..=index);
+                Reconciliation::Confirmed
+            }
+            None => {
+                self.moves.clear();
+                Reconciliation::Correction
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tile_step() {
+        let start = Instant::now();
+        let step = TileStep::new((2, 2), (2, 3), start, Duration::from_millis(400));
+
+        assert_eq!((2.0, 2.0), step.position(start));
+        assert_eq!(
+            (2.0, 2.5),
+            step.position(start + Duration::from_millis(200))
+        );
+        assert_eq!((64, 48), step.pixel_position(start, 32, 24));
+        assert_eq!(
+            (80, 48),
+            step.pixel_position(start + Duration::from_millis(200), 32, 24)
+        );
+        assert_eq!(
+            (96, 48),
+            step.pixel_position(start + Duration::from_secs(5), 32, 24)
+        );
+        assert!(!step.is_finished(start + Duration::from_millis(399)));
+        assert!(step.is_finished(start + Duration::from_millis(400)));
+    }
+
+    #[test]
+    fn test_player_step_duration() {
+        let settings = MovementSettings {
+            player_speed: 4.0,
+            run_factor: 2.0,
+            ..MovementSettings::default()
+        };
+
+        assert_eq!(
+            Duration::from_millis(250),
+            settings.player_step_duration((0, 0), (0, 1), false)
+        );
+        assert_eq!(
+            Duration::from_millis(125),
+            settings.player_step_duration((0, 0), (1, 0), true)
+        );
+        assert!(settings.player_step_duration((0, 0), (1, 1), false) > Duration::from_millis(350));
+    }
+
+    #[test]
+    fn test_pending_moves() {
+        let now = Instant::now();
+        let mut pending_moves = PendingMoves::default();
+        pending_moves.push((0, 1), now);
+        pending_moves.push((0, 2), now);
+        pending_moves.push((0, 3), now);
+
+        assert_eq!(
+            Reconciliation::Confirmed,
+            pending_moves.reconcile((0, 2), now)
+        );
+        assert_eq!(1, pending_moves.len());
+        assert_eq!(
+            Reconciliation::Correction,
+            pending_moves.reconcile((1, 2), now)
+        );
+        assert!(pending_moves.is_empty());
+
+        // Unconfirmed moves expire
+        pending_moves.push((0, 1), now);
+        assert_eq!(
+            Reconciliation::Correction,
+            pending_moves.reconcile((0, 1), now + Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn test_motions() {
+        let now = Instant::now();
+        let duration = Duration::from_millis(100);
+        let mut motions: Motions<i32> = Motions::default();
+
+        motions.start(1, (0, 1), (0, 0), now, duration);
+        assert_eq!(Facing::Left, motions.facing(&1));
+        assert_eq!(Facing::Right, motions.facing(&2));
+        assert_eq!(
+            (16, 0),
+            motions.pixel_position(&1, (0, 0), now + Duration::from_millis(50), 32, 32)
+        );
+
+        // New move during previous one start from interpolated position
+        motions.start(1, (0, 0), (1, 0), now + Duration::from_millis(50), duration);
+        assert_eq!(Facing::Left, motions.facing(&1));
+        assert_eq!(
+            (16, 0),
+            motions.pixel_position(&1, (1, 0), now + Duration::from_millis(50), 32, 32)
+        );
+        assert_eq!(
+            (0, 32),
+            motions.pixel_position(&1, (1, 0), now + Duration::from_secs(1), 32, 32)
+        );
+
+        motions.retain(|id| *id != 1);
+        assert_eq!(Facing::Right, motions.facing(&1));
+    }
+}
@@ -0,0 +1,356 @@
+use crate::entity::build::Build;
+use crate::entity::character::Character;
+use crate::entity::corpse::AnimatedCorpse;
+use crate::entity::resource::Resource;
+use crate::entity::stuff::Stuff;
+use crate::server::client::{Client, ClientError, ItemModel};
+use crate::server::request::PendingRequest;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+pub const DEFAULT_AVATAR_UUID: &str = "0000";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZoneResource {
+    Tiles,
+    Zone,
+    ResumeTexts,
+    Characters,
+    Stuffs,
+    Resources,
+    Builds,
+    AnimatedCorpses,
+    Avatars,
+}
+
+impl ZoneResource {
+    pub fn all() -> Vec<ZoneResource> {
+        vec![
+            ZoneResource::Tiles,
+            ZoneResource::Zone,
+            ZoneResource::ResumeTexts,
+            ZoneResource::Characters,
+            ZoneResource::Stuffs,
+            ZoneResource::Resources,
+            ZoneResource::Builds,
+            ZoneResource::AnimatedCorpses,
+            ZoneResource::Avatars,
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ZoneResource::Tiles => "Tuiles",
+            ZoneResource::Zone => "Zone",
+            ZoneResource::ResumeTexts => "Personnage",
+            ZoneResource::Characters => "Personnages de la zone",
+            ZoneResource::Stuffs => "Objets",
+            ZoneResource::Resources => "Ressources",
+            ZoneResource::Builds => "Bâtiments",
+            ZoneResource::AnimatedCorpses => "Créatures",
+            ZoneResource::Avatars => "Avatars",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZoneResourceStatus {
+    Pending,
+    Done,
+    Failed,
+}
+
+#[derive(Debug)]
+pub struct ZoneBootstrapError {
+    pub failures: Vec<(ZoneResource, ClientError)>,
+}
+
+impl std::error::Error for ZoneBootstrapError {}
+
+impl fmt::Display for ZoneBootstrapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let failures: Vec<String> = self
+            .failures
+            .iter()
+            .map(|(resource, error)| format!("{}: {}", resource.label(), error))
+            .collect();
+        write!(f, "Zone loading failed ({})", failures.join(", "))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ZoneBootstrapData {
+    pub tiles_data: Value,
+    pub zone_data: Value,
+    pub resume_text: Vec<ItemModel>,
+    pub characters: HashMap<String, Character>,
+    pub stuffs: HashMap<String, Stuff>,
+    pub resources: Vec<Resource>,
+    pub builds: HashMap<i32, Build>,
+    pub animated_corpses: HashMap<i32, AnimatedCorpse>,
+    pub avatars: Vec<String>,
+}
+
+enum Fetch<T> {
+    Pending(PendingRequest<T>),
+    Done(T),
+    Failed,
+}
+
+impl<T> Fetch<T> {
+    fn poll(&mut self, resource: ZoneResource, failures: &mut Vec<(ZoneResource, ClientError)>) {
+        let result = match self {
+            Fetch::Pending(pending_request) => match pending_request.try_take() {
+                Some(result) => result,
+                None => return,
+            },
+            _ => return,
+        };
+
+        *self = match result {
+            Ok(value) => {
+                println!("Zone bootstrap: {} loaded", resource.label());
+                Fetch::Done(value)
+            }
+            Err(error) => {
+                eprintln!(
+                    "Zone bootstrap: error when loading {}: {}",
+                    resource.label(),
+                    error
+                );
+                failures.push((resource, error));
+                Fetch::Failed
+            }
+        }
+    }
+
+    fn status(&self) -> ZoneResourceStatus {
+        match self {
+            Fetch::Pending(_) => ZoneResourceStatus::Pending,
+            Fetch::Done(_) => ZoneResourceStatus::Done,
+            Fetch::Failed => ZoneResourceStatus::Failed,
+        }
+    }
+
+    fn take(&mut self) -> T {
+        match std::mem::replace(self, Fetch::Failed) {
+            Fetch::Done(value) => value,
+            _ => panic!("Fetch must be done before take its value"),
+        }
+    }
+}
+
+/// Load all data required by zone engine. Requests are made concurrently by client request
+/// workers and must be polled (`poll`) on each tick.
+pub struct ZoneBootstrap {
+    client: Client,
+    tiles_data: Fetch<Value>,
+    zone_data: Fetch<Value>,
+    resume_text: Fetch<Vec<ItemModel>>,
+    characters: Fetch<Vec<Character>>,
+    stuffs: Fetch<Vec<Stuff>>,
+    resources: Fetch<Vec<Resource>>,
+    builds: Fetch<Vec<Build>>,
+    animated_corpses: Fetch<Vec<AnimatedCorpse>>,
+    // Avatars are requested when characters are known
+    avatars: Option<Vec<Fetch<String>>>,
+    failures: Vec<(ZoneResource, ClientError)>,
+}
+
+impl ZoneBootstrap {
+    pub fn start(client: &Client, character_id: &str, world_position: (i32, i32)) -> Self {
+        let (world_row_i, world_col_i) = world_position;
+        let character_id = character_id.to_string();
+
+        Self {
+            client: client.clone(),
+            tiles_data: Fetch::Pending(client.spawn(|client| client.get_tiles_data())),
+            zone_data: Fetch::Pending(
+                client.spawn(move |client| client.get_zone_data(world_row_i, world_col_i)),
+            ),
+            resume_text: Fetch::Pending(
+                client.spawn(move |client| client.get_character_resume_texts(&character_id)),
+            ),
+            characters: Fetch::Pending(
+                client.spawn(move |client| client.get_zone_characters(world_row_i, world_col_i)),
+            ),
+            stuffs: Fetch::Pending(
+                client.spawn(move |client| client.get_zone_stuffs(world_row_i, world_col_i)),
+            ),
+            resources: Fetch::Pending(
+                client.spawn(move |client| client.get_zone_resources(world_row_i, world_col_i)),
+            ),
+            builds: Fetch::Pending(
+                client.spawn(move |client| client.get_zone_builds(world_row_i, world_col_i)),
+            ),
+            animated_corpses: Fetch::Pending(
+                client.spawn(move |client| client.get_animated_corpses(world_row_i, world_col_i)),
+            ),
+            avatars: None,
+            failures: vec![],
+        }
+    }
+
+    fn start_avatars(&mut self) {
+        let characters = match &self.characters {
+            Fetch::Done(characters) => characters,
+            _ => return,
+        };
+
+        let mut avatar_uuids: Vec<String> = vec![];
+        for character in characters.iter() {
+            let avatar_uuid = match (&character.avatar_uuid, character.avatar_is_validated) {
+                (Some(avatar_uuid), true) => avatar_uuid.to_string(),
+                _ => DEFAULT_AVATAR_UUID.to_string(),
+            };
+            if !avatar_uuids.contains(&avatar_uuid) {
+                avatar_uuids.push(avatar_uuid);
+            }
+        }
+
+        self.avatars = Some(
+            avatar_uuids
+                .into_iter()
+                .map(|avatar_uuid| {
+                    Fetch::Pending(self.client.spawn(move |client| {
+                        client.cache_media(&format!(
+                            "character_avatar__zone_thumb__{}.png",
+                            avatar_uuid
+                        ))?;
+                        Ok(avatar_uuid)
+                    }))
+                })
+                .collect(),
+        );
+    }
+
+    pub fn status(&self, resource: ZoneResource) -> ZoneResourceStatus {
+        match resource {
+            ZoneResource::Tiles => self.tiles_data.status(),
+            ZoneResource::Zone => self.zone_data.status(),
+            ZoneResource::ResumeTexts => self.resume_text.status(),
+            ZoneResource::Characters => self.characters.status(),
+            ZoneResource::Stuffs => self.stuffs.status(),
+            ZoneResource::Resources => self.resources.status(),
+            ZoneResource::Builds => self.builds.status(),
+            ZoneResource::AnimatedCorpses => self.animated_corpses.status(),
+            ZoneResource::Avatars => match &self.avatars {
+                None => self.characters.status(),
+                Some(avatars) => {
+                    if avatars
+                        .iter()
+                        .any(|avatar| avatar.status() == ZoneResourceStatus::Pending)
+                    {
+                        ZoneResourceStatus::Pending
+                    } else {
+                        ZoneResourceStatus::Done
+                    }
+                }
+            },
+        }
+    }
+
+    /// Progress of given resource, from 0.0 to 1.0
+    pub fn progress(&self, resource: ZoneResource) -> f32 {
+        if let (ZoneResource::Avatars, Some(avatars)) = (resource, &self.avatars) {
+            if avatars.is_empty() {
+                return 1.0;
+            }
+            let finished = avatars
+                .iter()
+                .filter(|avatar| avatar.status() != ZoneResourceStatus::Pending)
+                .count();
+            return finished as f32 / avatars.len() as f32;
+        }
+
+        match self.status(resource) {
+            ZoneResourceStatus::Pending => 0.0,
+            _ => 1.0,
+        }
+    }
+
+    fn finished(&self) -> bool {
+        ZoneResource::all()
+            .iter()
+            .all(|resource| self.status(*resource) != ZoneResourceStatus::Pending)
+    }
+
+    /// Proceed finished requests. Return None while loading is not finished.
+    pub fn poll(&mut self) -> Option<Result<ZoneBootstrapData, ZoneBootstrapError>> {
+        let failures = &mut self.failures;
+        self.tiles_data.poll(ZoneResource::Tiles, failures);
+        self.zone_data.poll(ZoneResource::Zone, failures);
+        self.resume_text.poll(ZoneResource::ResumeTexts, failures);
+        self.characters.poll(ZoneResource::Characters, failures);
+        self.stuffs.poll(ZoneResource::Stuffs, failures);
+        self.resources.poll(ZoneResource::Resources, failures);
+        self.builds.poll(ZoneResource::Builds, failures);
+        self.animated_corpses
+            .poll(ZoneResource::AnimatedCorpses, failures);
+
+        if self.avatars.is_none() {
+            self.start_avatars();
+        }
+        if let Some(avatars) = self.avatars.as_mut() {
+            // Avatars are not required to display zone: only log errors
+            let mut avatar_failures = vec![];
+            for avatar in avatars.iter_mut() {
+                avatar.poll(ZoneResource::Avatars, &mut avatar_failures);
+            }
+        }
+
+        if !self.finished() {
+            return None;
+        }
+
+        if !self.failures.is_empty() {
+            let failures = std::mem::take(&mut self.failures);
+            return Some(Err(ZoneBootstrapError { failures }));
+        }
+
+        let avatars = self
+            .avatars
+            .take()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|avatar| match avatar {
+                Fetch::Done(avatar_uuid) => Some(avatar_uuid),
+                _ => None,
+            })
+            .collect();
+
+        Some(Ok(ZoneBootstrapData {
+            tiles_data: self.tiles_data.take(),
+            zone_data: self.zone_data.take(),
+            resume_text: self.resume_text.take(),
+            characters: self
+                .characters
+                .take()
+                .into_iter()
+                .map(|character| (character.id.clone(), character))
+                .collect(),
+            stuffs: self
+                .stuffs
+                .take()
+                .into_iter()
+                .map(|stuff| (stuff.id.to_string(), stuff))
+                .collect(),
+            resources: self.resources.take(),
+            builds: self
+                .builds
+                .take()
+                .into_iter()
+                .map(|build| (build.id, build))
+                .collect(),
+            animated_corpses: self
+                .animated_corpses
+                .take()
+                .into_iter()
+                .map(|animated_corpse| (animated_corpse.id, animated_corpse))
+                .collect(),
+            avatars,
+        }))
+    }
+}
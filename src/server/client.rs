@@ -1,5 +1,8 @@
 use reqwest;
-use reqwest::blocking::Response;
+use reqwest::blocking::{RequestBuilder, Response};
+use reqwest::header::{
+    HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
 use url::Url;
 
 use crate::entity::build::Build;
@@ -10,26 +13,64 @@ use crate::entity::resource::Resource;
 use crate::entity::stuff::Stuff;
 use crate::gui::lang::model::Description;
 use crate::gui::lang::model::ErrorResponse;
+use crate::server::http_cache::{CachedResponse, ResponseCache};
+use crate::server::media_cache::{self, MediaCache, MediaKind};
+use crate::server::network::NetworkConfig;
+use crate::server::policy::RequestPolicy;
+use crate::server::request::{PendingRequest, RequestPool, REQUEST_WORKERS_COUNT};
 use crate::server::ServerAddress;
 use crate::util;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Number, Value};
 use std::error::Error;
-use std::fs::File;
-use std::io::Write;
-use std::path::Path;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
 use std::time::Duration;
-use std::{fmt, fs, io};
+use websocket::header::{Authorization, Basic, Bearer, Headers};
+
+/// Gateway errors statuses, GET requests are retried when they happen
+const RETRY_STATUSES: [u16; 3] = [502, 503, 504];
 
 #[derive(Debug)]
 pub enum ClientError {
-    NotFound { message: String },
+    NotFound {
+        message: String,
+    },
     Unauthorized,
-    PlayerNotFound { message: String },
-    ClientSideError { message: String },
-    ServerSideError { message: String },
-    UnknownError { message: String },
-    RequestError { message: String },
+    PlayerNotFound {
+        message: String,
+    },
+    /// 4xx response, with server error body if it could be parsed
+    ClientSideError {
+        status: u16,
+        message: String,
+        response: Option<ErrorResponse>,
+    },
+    /// 5xx response, with server error body if it could be parsed
+    ServerSideError {
+        status: u16,
+        message: String,
+        response: Option<ErrorResponse>,
+    },
+    UnknownError {
+        message: String,
+    },
+    RequestError {
+        message: String,
+    },
+    ConnectionError {
+        message: String,
+    },
+    Timeout {
+        message: String,
+    },
+    /// GET request failed on each attempt, `last_error` is the error of last attempt
+    RetriesExhausted {
+        attempts: u32,
+        last_error: Box<ClientError>,
+    },
 }
 
 impl Error for ClientError {}
@@ -41,25 +82,54 @@ impl ClientError {
             ClientError::PlayerNotFound { message } => {
                 format!("Player not found: {}", message).to_string()
             }
-            ClientError::ClientSideError { message } => format!("{}", message).to_string(),
-            ClientError::ServerSideError { message } => {
-                format!("Server side error: {}", message).to_string()
-            }
+            ClientError::ClientSideError { message, .. } => format!("{}", message).to_string(),
+            ClientError::ServerSideError {
+                status, message, ..
+            } => format!("Server side error ({}): {}", status, message).to_string(),
             ClientError::UnknownError { message } => {
                 format!("Unknown error: {}", message).to_string()
             }
             ClientError::RequestError { message } => {
                 format!("Request error: {}", message).to_string()
             }
+            ClientError::ConnectionError { message } => format!("Connection error: {}", message),
+            ClientError::Timeout { message } => format!("Timeout: {}", message),
+            ClientError::RetriesExhausted {
+                attempts,
+                last_error,
+            } => format!(
+                "{} (after {} attempts)",
+                ClientError::get_message(last_error),
+                attempts
+            ),
             ClientError::Unauthorized => "Unauthorized".to_string(),
         };
     }
+
+    /// Error which can disappear by retrying the request later
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ClientError::ConnectionError { .. }
+            | ClientError::Timeout { .. }
+            | ClientError::RetriesExhausted { .. } => true,
+            ClientError::ServerSideError { status, .. } => RETRY_STATUSES.contains(status),
+            _ => false,
+        }
+    }
 }
 
 impl From<reqwest::Error> for ClientError {
     fn from(error: reqwest::Error) -> Self {
-        ClientError::RequestError {
-            message: format!("{}", error),
+        let mut message = format!("{}", error);
+        if let Some(url) = error.url() {
+            message = message.replace(url.as_str(), &util::redact_url(url.as_str()));
+        }
+        if error.is_timeout() {
+            ClientError::Timeout { message }
+        } else if error.is_connect() {
+            ClientError::ConnectionError { message }
+        } else {
+            ClientError::RequestError { message }
         }
     }
 }
@@ -71,7 +141,7 @@ impl fmt::Display for ClientError {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ItemModel {
     pub name: String,
     pub value_is_str: bool,
@@ -82,16 +152,75 @@ pub struct ItemModel {
     pub classes: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ListOfItemModel {
     pub items: Vec<ItemModel>,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+struct LoginResponse {
+    token: String,
+}
+
+/// How client authenticate its requests. Secrets are never displayed by `Debug`.
+#[derive(Clone)]
+pub enum Auth {
+    Anonymous,
+    /// Used with servers which don't deliver session tokens
+    Basic {
+        login: String,
+        password: String,
+    },
+    /// Session token obtained with `Client::login`
+    Token {
+        login: String,
+        token: String,
+    },
+}
+
+impl Auth {
+    pub fn login(&self) -> &str {
+        match self {
+            Auth::Anonymous => "",
+            Auth::Basic { login, .. } | Auth::Token { login, .. } => login,
+        }
+    }
+}
+
+impl fmt::Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Auth::Anonymous => write!(f, "Anonymous"),
+            Auth::Basic { login, .. } => write!(f, "Basic({}, ***)", login),
+            Auth::Token { login, .. } => write!(f, "Token({}, ***)", login),
+        }
+    }
+}
+
+trait Authenticate {
+    fn authenticate(self, auth: &Auth) -> Self;
+}
+
+impl Authenticate for RequestBuilder {
+    fn authenticate(self, auth: &Auth) -> Self {
+        match auth {
+            Auth::Anonymous => self,
+            Auth::Basic { login, password } => self.basic_auth(login, Some(password)),
+            Auth::Token { token, .. } => self.bearer_auth(token),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Client {
     pub address: ServerAddress,
     client: reqwest::blocking::Client,
-    pub credentials: (String, String),
+    pool: Arc<RequestPool>,
+    pub auth: Auth,
+    policy: RequestPolicy,
+    network_config: NetworkConfig,
+    response_cache: Option<ResponseCache>,
+    media_cache: MediaCache,
 }
 
 impl fmt::Debug for Client {
@@ -103,15 +232,90 @@ impl fmt::Debug for Client {
 }
 
 impl Client {
-    pub fn new(address: ServerAddress, credentials: (String, String)) -> Self {
+    pub fn new(address: ServerAddress, auth: Auth) -> Self {
+        let policy = RequestPolicy::default();
+        let network_config = NetworkConfig::default();
         Self {
             address,
-            client: reqwest::blocking::Client::builder()
-                .pool_idle_timeout(Some(Duration::from_secs(2)))
-                .build()
-                .expect("Fail to build client"),
-            credentials,
+            client: build_http_client(&policy, &network_config),
+            pool: Arc::new(RequestPool::new(REQUEST_WORKERS_COUNT)),
+            auth,
+            policy,
+            network_config,
+            response_cache: None,
+            media_cache: MediaCache::new(
+                media_cache::MEDIA_CACHE_FOLDER,
+                media_cache::DEFAULT_MAX_SIZE_MB * 1024 * 1024,
+            ),
+        }
+    }
+
+    /// Use given timeouts and retries policy instead of default one
+    pub fn with_policy(mut self, policy: RequestPolicy) -> Self {
+        self.client = build_http_client(&policy, &self.network_config);
+        self.policy = policy;
+        self
+    }
+
+    /// Use given proxy and TLS settings instead of direct connection with system certificates
+    pub fn with_network_config(mut self, network_config: NetworkConfig) -> Self {
+        self.client = build_http_client(&self.policy, &network_config);
+        self.network_config = network_config;
+        self
+    }
+
+    pub fn network_config(&self) -> &NetworkConfig {
+        &self.network_config
+    }
+
+    /// Use given media cache (e.g. configured one) instead of default one
+    pub fn with_media_cache(mut self, media_cache: MediaCache) -> Self {
+        self.media_cache = media_cache;
+        self
+    }
+
+    pub fn media_cache(&self) -> &MediaCache {
+        &self.media_cache
+    }
+
+    /// Keep world and zones sources in given cache, revalidated with server on each request
+    pub fn with_response_cache(mut self, response_cache: ResponseCache) -> Self {
+        self.response_cache = Some(response_cache);
+        self
+    }
+
+    /// Same client (sharing request workers) with another authentication
+    pub fn with_auth(&self, auth: Auth) -> Self {
+        let mut client = self.clone();
+        client.auth = auth;
+        client
+    }
+
+    /// Headers to authenticate zone websocket
+    pub fn websocket_headers(&self) -> Headers {
+        let mut headers = Headers::new();
+        match &self.auth {
+            Auth::Anonymous => {}
+            Auth::Basic { login, password } => headers.set(Authorization(Basic {
+                username: login.clone(),
+                password: Some(password.clone()),
+            })),
+            Auth::Token { token, .. } => headers.set(Authorization(Bearer {
+                token: token.clone(),
+            })),
         }
+        headers
+    }
+
+    /// Execute given job in request workers threads. Result must be polled with
+    /// `PendingRequest::try_take`.
+    pub fn spawn<T, F>(&self, job: F) -> PendingRequest<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Client) -> Result<T, ClientError> + Send + 'static,
+    {
+        let client = self.clone();
+        self.pool.execute(move || job(&client))
     }
 
     fn get_base_path(&self) -> String {
@@ -129,74 +333,236 @@ impl Client {
             return Err(ClientError::Unauthorized);
         }
 
-        if response.status().is_client_error() {
-            let error: ErrorResponse = response.json().unwrap();
-            return Err(ClientError::ClientSideError {
-                message: error.message,
+        let status = response.status();
+        if !status.is_success() {
+            let (message, error_response) = match response.json::<ErrorResponse>() {
+                Ok(error_response) => (error_response.message.clone(), Some(error_response)),
+                Err(error) => (format!("Unexpected server response : {}", error), None),
+            };
+            return Err(if status.is_client_error() {
+                ClientError::ClientSideError {
+                    status: status.as_u16(),
+                    message,
+                    response: error_response,
+                }
+            } else {
+                ClientError::ServerSideError {
+                    status: status.as_u16(),
+                    message,
+                    response: error_response,
+                }
             });
         }
 
-        if !response.status().is_success() {
-            match response.json::<ErrorResponse>() {
-                Ok(error) => {
-                    return Err(ClientError::ServerSideError {
-                        message: error.message,
-                    });
+        Ok(response)
+    }
+
+    /// Send GET request (with given additional headers) which must be complete within given
+    /// timeout. Connection errors, timeouts and gateway errors are retried according to request
+    /// policy. Other error statuses are not checked.
+    fn send_get(
+        &self,
+        url: &str,
+        headers: HeaderMap,
+        timeout: Duration,
+    ) -> Result<Response, ClientError> {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let result = self
+                .client
+                .get(url)
+                .timeout(timeout)
+                .headers(headers.clone())
+                .authenticate(&self.auth)
+                .send();
+            let error = match result {
+                Ok(response) if !RETRY_STATUSES.contains(&response.status().as_u16()) => {
+                    return Ok(response)
                 }
-                Err(error) => {
-                    return Err(ClientError::ServerSideError {
-                        message: format!("Unexpected server response : {}", error),
-                    });
+                Ok(response) => match self.check_response(response) {
+                    Ok(response) => return Ok(response),
+                    Err(error) => error,
+                },
+                Err(error) => ClientError::from(error),
+            };
+
+            if !error.is_transient() {
+                return Err(error);
+            }
+            if attempts > self.policy.max_retries {
+                if attempts == 1 {
+                    return Err(error);
+                }
+                return Err(ClientError::RetriesExhausted {
+                    attempts,
+                    last_error: Box::new(error),
+                });
+            }
+
+            let delay = self.policy.retry_delay(attempts);
+            eprintln!(
+                "Request {} failed ({}), retry in {} ms",
+                util::redact_url(url),
+                error,
+                delay.as_millis()
+            );
+            thread::sleep(delay);
+        }
+    }
+
+    /// Send GET request (see `send_get`) and check its response status
+    fn get(&self, url: &str) -> Result<Response, ClientError> {
+        self.check_response(self.send_get(url, HeaderMap::new(), self.policy.request_timeout)?)
+    }
+
+    /// GET given url and return body text. When response cache is enabled, cached body is
+    /// revalidated (ETag/Last-Modified) and served on 304 response or when server is
+    /// unavailable.
+    fn get_text_revalidated(&self, url: &str) -> Result<String, ClientError> {
+        let response_cache = match &self.response_cache {
+            Some(response_cache) => response_cache,
+            None => return Ok(self.get(url)?.text()?),
+        };
+        let cached_response = response_cache.get(url);
+
+        let mut headers = HeaderMap::new();
+        if let Some(cached_response) = &cached_response {
+            let etag = cached_response.etag.as_deref().map(HeaderValue::from_str);
+            if let Some(Ok(etag)) = etag {
+                headers.insert(IF_NONE_MATCH, etag);
+            }
+            let last_modified = cached_response
+                .last_modified
+                .as_deref()
+                .map(HeaderValue::from_str);
+            if let Some(Ok(last_modified)) = last_modified {
+                headers.insert(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = match (
+            self.send_get(url, headers, self.policy.request_timeout),
+            cached_response,
+        ) {
+            (Ok(response), Some(cached_response))
+                if response.status().as_u16() == 304 || response.status().is_server_error() =>
+            {
+                if response.status().is_server_error() {
+                    eprintln!(
+                        "Server error for {}, use cached response",
+                        util::redact_url(url)
+                    );
                 }
+                return Ok(cached_response.body);
+            }
+            (Err(error), Some(cached_response)) if error.is_transient() => {
+                eprintln!(
+                    "Server unavailable for {} ({}), use cached response",
+                    util::redact_url(url),
+                    error
+                );
+                return Ok(cached_response.body);
+            }
+            (response, _) => self.check_response(response?)?,
+        };
+
+        let header_value = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+        let etag = header_value(ETAG);
+        let last_modified = header_value(LAST_MODIFIED);
+        let body = response.text()?;
+        if etag.is_some() || last_modified.is_some() {
+            let cached_response = CachedResponse {
+                url: url.to_string(),
+                etag,
+                last_modified,
+                body: body.clone(),
             };
+            if let Err(error) = response_cache.store(&cached_response) {
+                eprintln!("Unable to cache response: {}", error);
+            }
         }
 
-        Ok(response)
+        Ok(body)
     }
 
-    pub fn get_current_character_id(
-        &self,
-        credentials: (String, String),
-    ) -> Result<String, ClientError> {
+    /// Exchange login and password against a session token. Servers which don't deliver
+    /// tokens give a basic auth.
+    pub fn login(&self, login: &str, password: &str) -> Result<Auth, ClientError> {
+        println!("Login on server as {}", login);
+        let url = format!("{}/account/login", self.get_base_path());
+        let response = self.check_response(
+            self.client
+                .post(url.as_str())
+                .basic_auth(login, Some(password))
+                .send()?,
+        );
+
+        match response {
+            Ok(response) => Ok(Auth::Token {
+                login: login.to_string(),
+                token: response.json::<LoginResponse>()?.token,
+            }),
+            Err(ClientError::NotFound { .. }) => {
+                println!("Server don't deliver session tokens, use basic auth");
+                Ok(Auth::Basic {
+                    login: login.to_string(),
+                    password: password.to_string(),
+                })
+            }
+            Err(client_error) => Err(client_error),
+        }
+    }
+
+    /// Invalidate session token on server. Nothing to do without token or with servers which
+    /// don't deliver them.
+    pub fn logout(&self) -> Result<(), ClientError> {
+        if !matches!(self.auth, Auth::Token { .. }) {
+            return Ok(());
+        }
+
+        let url = format!("{}/account/logout", self.get_base_path());
+        match self.check_response(
+            self.client
+                .post(url.as_str())
+                .authenticate(&self.auth)
+                .send()?,
+        ) {
+            Ok(_) | Err(ClientError::NotFound { .. }) => Ok(()),
+            Err(client_error) => Err(client_error),
+        }
+    }
+
+    pub fn get_current_character_id(&self) -> Result<String, ClientError> {
         println!("Retrieve current character from server");
         let url = format!("{}/account/current_character_id", self.get_base_path());
-        // TODO manage error
-        let response: Response = self
-            .client
-            .get(url.as_str())
-            .basic_auth(credentials.0, Some(credentials.1))
-            .send()
-            .unwrap();
-
-        match self.check_response(response) {
+        match self.get(&url) {
             Err(ClientError::NotFound { message }) => {
                 return Err(ClientError::PlayerNotFound { message })
             }
             Err(client_error) => return Err(client_error),
-            Ok(resp) => return Ok(resp.text().unwrap().clone()),
+            Ok(resp) => return Ok(resp.text()?),
         }
     }
 
     pub fn get_player(&self, id: &str) -> Result<Player, ClientError> {
         println!("Retrieve character '{}' from server", id);
         let url = format!("{}/character/{}", self.get_base_path(), id);
-        // TODO manage error
-        let mut response: Response = self
-            .client
-            .get(url.as_str())
-            .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
-            .send()
-            .unwrap();
-
-        match self.check_response(response) {
+        let response: Response = match self.get(&url) {
             Err(ClientError::NotFound { message }) => {
                 return Err(ClientError::PlayerNotFound { message })
             }
             Err(client_error) => return Err(client_error),
-            Ok(resp) => response = resp,
-        }
+            Ok(resp) => resp,
+        };
 
-        let character: ApiCharacter = response.json::<ApiCharacter>().unwrap();
+        let character: ApiCharacter = response.json::<ApiCharacter>()?;
 
         Ok(Player::new(
             character.id.as_str(),
@@ -216,6 +582,7 @@ impl Client {
             character.pending_actions,
             character.avatar_uuid,
             character.avatar_is_validated,
+            character.transport_type,
         ))
     }
 
@@ -244,11 +611,11 @@ impl Client {
             self.client
                 .post(url.as_str())
                 .json(&data)
-                .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
+                .authenticate(&self.auth)
                 .send()?,
         )?;
 
-        let character: ApiCharacter = response.json::<ApiCharacter>().unwrap();
+        let character: ApiCharacter = response.json::<ApiCharacter>()?;
 
         Ok(Player::new(
             character.id.as_str(),
@@ -268,20 +635,16 @@ impl Client {
             character.pending_actions,
             character.avatar_uuid,
             character.avatar_is_validated,
+            character.transport_type,
         ))
     }
 
     pub fn get_tiles_data(&self) -> Result<Value, ClientError> {
         println!("Retrieve tiles from server");
         let url = format!("{}/zones/tiles", self.get_base_path());
-        let response: Response = self.check_response(
-            self.client
-                .get(url.as_str())
-                .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
-                .send()?,
-        )?;
+        let body = self.get_text_revalidated(&url)?;
 
-        Ok(response.json::<Value>().unwrap())
+        parse_json(&body)
     }
 
     pub fn get_zone_data(&self, world_row_i: i32, world_col_i: i32) -> Result<Value, ClientError> {
@@ -292,14 +655,9 @@ impl Client {
             world_row_i,
             world_col_i
         );
-        let response: Response = self.check_response(
-            self.client
-                .get(url.as_str())
-                .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
-                .send()?,
-        )?;
+        let body = self.get_text_revalidated(&url)?;
 
-        Ok(response.json::<Value>().unwrap())
+        parse_json(&body)
     }
 
     pub fn get_zone_characters(
@@ -314,14 +672,9 @@ impl Client {
             world_row_i,
             world_col_i
         );
-        let response: Response = self.check_response(
-            self.client
-                .get(url.as_str())
-                .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
-                .send()?,
-        )?;
+        let response: Response = self.get(&url)?;
 
-        Ok(response.json::<Vec<Character>>().unwrap())
+        Ok(response.json::<Vec<Character>>()?)
     }
 
     pub fn get_zone_stuffs(
@@ -336,14 +689,9 @@ impl Client {
             world_row_i,
             world_col_i
         );
-        let response: Response = self.check_response(
-            self.client
-                .get(url.as_str())
-                .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
-                .send()?,
-        )?;
+        let response: Response = self.get(&url)?;
 
-        Ok(response.json::<Vec<Stuff>>().unwrap())
+        Ok(response.json::<Vec<Stuff>>()?)
     }
 
     pub fn get_zone_resources(
@@ -358,14 +706,9 @@ impl Client {
             world_row_i,
             world_col_i
         );
-        let response: Response = self.check_response(
-            self.client
-                .get(url.as_str())
-                .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
-                .send()?,
-        )?;
+        let response: Response = self.get(&url)?;
 
-        Ok(response.json::<Vec<Resource>>().unwrap())
+        Ok(response.json::<Vec<Resource>>()?)
     }
 
     pub fn get_zone_builds(
@@ -380,27 +723,15 @@ impl Client {
             world_row_i,
             world_col_i
         );
-        let response: Response = self.check_response(
-            self.client
-                .get(url.as_str())
-                .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
-                .send()?,
-        )?;
+        let response: Response = self.get(&url)?;
 
-        Ok(response.json::<Vec<Build>>().unwrap())
+        Ok(response.json::<Vec<Build>>()?)
     }
 
     pub fn get_world_source(&self) -> Result<String, ClientError> {
         println!("Retrieve world source from server");
         let url = format!("{}/world/source", self.get_base_path());
-        let response: Response = self.check_response(
-            self.client
-                .get(url.as_str())
-                .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
-                .send()?,
-        )?;
-
-        Ok(response.text().unwrap().to_string())
+        self.get_text_revalidated(&url)
     }
 
     pub fn describe(
@@ -415,19 +746,15 @@ impl Client {
             url.to_string()
         };
         let url = self.url_with_query(url, query);
-        println!("Describe with url {}", url);
+        println!("Describe with url {}", util::redact_url(&url));
 
         let mut request = self.client.post(url.as_str());
         if let Some(data_) = data {
             request = request.json(&data_);
         }
 
-        let response: Response = self.check_response(
-            request
-                .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
-                .send()?,
-        )?;
-        let mut description = response.json::<Description>().unwrap();
+        let response: Response = self.check_response(request.authenticate(&self.auth).send()?)?;
+        let mut description = response.json::<Description>()?;
         description.origin_url = Some(url);
 
         if let Some(illustration_name) = &description.illustration_name {
@@ -469,14 +796,9 @@ impl Client {
             self.get_base_path(),
             character_id
         );
-        let response: Response = self.check_response(
-            self.client
-                .get(url.as_str())
-                .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
-                .send()?,
-        )?;
+        let response: Response = self.get(&url)?;
 
-        Ok(response.json::<ListOfItemModel>().unwrap().items)
+        Ok(response.json::<ListOfItemModel>()?.items)
     }
 
     fn url_with_query(&self, url: String, query: Option<Map<String, Value>>) -> String {
@@ -508,47 +830,35 @@ impl Client {
 
     pub fn player_is_dead(&self, character_id: &str) -> Result<bool, ClientError> {
         let url = format!("{}/character/{}/dead", self.get_base_path(), character_id);
-        let result = self.check_response(
-            self.client
-                .get(url.as_str())
-                .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
-                .send()?,
-        );
+        let result = self.get(&url);
         if let Err(ClientError::NotFound { message: _ }) = result {
             return Ok(false);
         }
         let response: Response = result?;
 
-        if response.text().unwrap() == "1" {
+        if response.text()? == "1" {
             return Ok(true);
         }
         Ok(false)
     }
 
-    pub fn download_image(&self, image_id: i32, image_extension: &str) -> Result<(), ClientError> {
+    pub fn download_image(
+        &self,
+        image_id: i32,
+        image_extension: &str,
+    ) -> Result<PathBuf, ClientError> {
         let url = format!("{}/image/{}", self.get_base_path(), image_id);
-        let mut response: Response = self.check_response(
-            self.client
-                .get(url.as_str())
-                .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
-                .send()?,
-        )?;
-        // FIXME BS: user dir or (MUST BE IN STATIC)
-        let file_path = format!("static/cache/{}{}", image_id, image_extension);
-        fs::create_dir_all("static/cache").unwrap();
-        let mut out = File::create(Path::new(&file_path))
-            .expect(format!("failed to create file {}", &file_path).as_str());
-        io::copy(&mut response, &mut out)
-            .expect(format!("failed to copy content into {}", &file_path).as_str());
-
-        Ok(())
+        self.download_media(
+            &url,
+            MediaKind::Image,
+            &format!("{}{}", image_id, image_extension),
+        )
     }
 
     pub fn get_version(&self) -> Result<(u8, u8, u8), ClientError> {
         let url = format!("{}/system/version", self.get_base_path());
-        let response: Response =
-            self.check_response(self.client.get(url.as_str()).send().unwrap())?;
-        let text_response: String = response.text().unwrap();
+        let response: Response = self.get(&url)?;
+        let text_response: String = response.text()?;
         Ok(util::str_version_to_tuple(&text_response))
     }
 
@@ -563,72 +873,84 @@ impl Client {
             world_row_i,
             world_col_i
         );
-        let response: Response = self.check_response(
-            self.client
-                .get(url.as_str())
-                .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
-                .send()?,
-        )?;
+        let response: Response = self.get(&url)?;
 
-        let value = response.json::<Value>().unwrap();
-        let mut animated_corpses: Vec<AnimatedCorpse> = vec![];
-        for item in value.as_array().unwrap().iter() {
-            let animated_corpse: AnimatedCorpse = serde_json::from_value(item.clone()).unwrap();
-            animated_corpses.push(animated_corpse);
-        }
-        Ok(animated_corpses)
+        Ok(response.json::<Vec<AnimatedCorpse>>()?)
     }
 
     pub fn get_loading_media_names(&self) -> Result<Vec<String>, ClientError> {
         let url = format!("{}/system/loadings", self.get_base_path(),);
-        let response: Response = self.check_response(self.client.get(url.as_str()).send()?)?;
-        Ok(response.json::<Vec<String>>().unwrap())
+        let response: Response = self.get(&url)?;
+        Ok(response.json::<Vec<String>>()?)
     }
 
-    pub fn cache_media(&self, media_name: &str) -> Result<(), ClientError> {
-        if Path::new(&format!("cache/{}", media_name)).exists() {
-            println!("Media {} already in cache", media_name);
-            return Ok(());
+    /// Download media into media cache (if not already cached) and return its path
+    fn download_media(
+        &self,
+        url: &str,
+        kind: MediaKind,
+        media_name: &str,
+    ) -> Result<PathBuf, ClientError> {
+        if let Some(path) = self.media_cache.get(kind, media_name) {
+            return Ok(path);
         }
         println!("Download media {}", media_name);
-        let url = format!("{}/media/{}", self.get_base_path(), media_name);
-        let mut response: Response = self.check_response(
-            self.client
-                .get(url.as_str())
-                .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
-                .send()?,
-        )?;
-        let mut buf: Vec<u8> = vec![];
-        response.copy_to(&mut buf).unwrap();
-        fs::create_dir_all(Path::new("cache")).unwrap();
-        File::create(Path::new(&format!("cache/{}", media_name)))
-            .unwrap()
-            .write(&buf)
-            .unwrap();
-        Ok(())
-    }
-
-    pub fn cache_media_bg(&self, media_name: &str) -> Result<(), ClientError> {
-        if Path::new(&format!("cache/bg/{}", media_name)).exists() {
-            println!("Media bg {} already in cache", media_name);
-            return Ok(());
+
+        let mut response: Response = self.check_response(self.send_get(
+            url,
+            HeaderMap::new(),
+            self.policy.download_timeout,
+        )?)?;
+        let expected_length = response.content_length();
+        let mut content: Vec<u8> = vec![];
+        response.copy_to(&mut content)?;
+        if let Some(expected_length) = expected_length {
+            if content.len() as u64 != expected_length {
+                return Err(ClientError::RequestError {
+                    message: format!(
+                        "Incomplete download of {} ({} of {} bytes)",
+                        media_name,
+                        content.len(),
+                        expected_length
+                    ),
+                });
+            }
         }
-        println!("Download media bg {}", media_name);
 
+        self.media_cache
+            .store(kind, media_name, &content)
+            .map_err(|error| ClientError::UnknownError {
+                message: format!("Unable to cache media {}: {}", media_name, error),
+            })
+    }
+
+    pub fn cache_media(&self, media_name: &str) -> Result<PathBuf, ClientError> {
+        let url = format!("{}/media/{}", self.get_base_path(), media_name);
+        self.download_media(&url, MediaKind::Media, media_name)
+    }
+
+    pub fn cache_media_bg(&self, media_name: &str) -> Result<PathBuf, ClientError> {
         let url = format!("{}/media_bg/{}", self.get_base_path(), media_name);
-        let mut response: Response = self.check_response(
-            self.client
-                .get(url.as_str())
-                .basic_auth(self.credentials.0.clone(), Some(self.credentials.1.clone()))
-                .send()?,
-        )?;
-        let mut buf: Vec<u8> = vec![];
-        response.copy_to(&mut buf).unwrap();
-        fs::create_dir_all(Path::new("cache/bg")).unwrap();
-        File::create(Path::new(&format!("cache/bg/{}", media_name)))
-            .unwrap()
-            .write(&buf)
-            .unwrap();
-        Ok(())
+        self.download_media(&url, MediaKind::MediaBackground, media_name)
     }
 }
+
+fn parse_json(body: &str) -> Result<Value, ClientError> {
+    serde_json::from_str(body).map_err(|error| ClientError::UnknownError {
+        message: format!("Unexpected server response : {}", error),
+    })
+}
+
+fn build_http_client(
+    policy: &RequestPolicy,
+    network_config: &NetworkConfig,
+) -> reqwest::blocking::Client {
+    let builder = reqwest::blocking::Client::builder()
+        .pool_idle_timeout(Some(Duration::from_secs(2)))
+        .connect_timeout(policy.connect_timeout)
+        .timeout(policy.request_timeout);
+    network_config
+        .apply_to_server_client(builder)
+        .build()
+        .expect("Fail to build client")
+}
@@ -0,0 +1,225 @@
+use crate::error::RollingError;
+use crate::server::client::Auth;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::Rng;
+use serde_derive::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+pub const KEY_FILE_NAME: &str = "client.key";
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+
+/// Locally generated key used to encrypt remembered secrets
+pub struct CredentialsKey {
+    key: [u8; KEY_LENGTH],
+}
+
+impl CredentialsKey {
+    /// Read key file, None if it doesn't exist
+    pub fn load(file_path: &Path) -> Result<Option<Self>, RollingError> {
+        if !file_path.is_file() {
+            return Ok(None);
+        }
+
+        let bytes = fs::read(file_path)
+            .map_err(|error| RollingError::new(format!("Unable to read key file: {}", error)))?;
+        if bytes.len() != KEY_LENGTH {
+            return Err(RollingError::new(format!(
+                "Invalid key file {}",
+                file_path.display()
+            )));
+        }
+        let mut key = [0; KEY_LENGTH];
+        key.copy_from_slice(&bytes);
+        Ok(Some(Self { key }))
+    }
+
+    /// Read key file, or create it with a random key if it doesn't exist
+    pub fn load_or_create(file_path: &Path) -> Result<Self, RollingError> {
+        if let Some(key) = Self::load(file_path)? {
+            return Ok(key);
+        }
+
+        let mut key = [0; KEY_LENGTH];
+        rand::thread_rng().fill(&mut key);
+        fs::write(file_path, key)
+            .map_err(|error| RollingError::new(format!("Unable to write key file: {}", error)))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if let Err(error) = fs::set_permissions(file_path, fs::Permissions::from_mode(0o600)) {
+                eprintln!("Unable to restrict key file permissions: {}", error);
+            }
+        }
+
+        Ok(Self { key })
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(&self.key))
+    }
+
+    pub fn encrypt(&self, plain_text: &str) -> String {
+        let nonce: [u8; NONCE_LENGTH] = rand::thread_rng().gen();
+        let encrypted = self
+            .cipher()
+            .encrypt(Nonce::from_slice(&nonce), plain_text.as_bytes())
+            .expect("Unable to encrypt value");
+        base64::encode([&nonce[..], &encrypted].concat())
+    }
+
+    /// Fail if value has been encrypted with another key or altered
+    pub fn decrypt(&self, encrypted: &str) -> Result<String, RollingError> {
+        let bytes = base64::decode(encrypted)
+            .map_err(|error| RollingError::new(format!("Invalid encrypted value: {}", error)))?;
+        if bytes.len() < NONCE_LENGTH {
+            return Err(RollingError::new("Encrypted value too short".to_string()));
+        }
+
+        let (nonce, encrypted) = bytes.split_at(NONCE_LENGTH);
+        let data = self
+            .cipher()
+            .decrypt(Nonce::from_slice(nonce), encrypted)
+            .map_err(|_| {
+                RollingError::new("Unable to decrypt value (key changed ?)".to_string())
+            })?;
+        String::from_utf8(data)
+            .map_err(|error| RollingError::new(format!("Invalid decrypted value: {}", error)))
+    }
+}
+
+/// Credentials remembered for a server profile, encrypted with `CredentialsKey`. Session token
+/// is remembered when server deliver one, password otherwise.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StoredCredentials {
+    pub login: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    password: Option<String>,
+}
+
+impl StoredCredentials {
+    /// Return None for anonymous auth
+    pub fn seal(auth: &Auth, key: &CredentialsKey) -> Option<Self> {
+        match auth {
+            Auth::Token { login, token } => Some(Self {
+                login: login.clone(),
+                token: Some(key.encrypt(token)),
+                password: None,
+            }),
+            Auth::Basic { login, password } => Some(Self {
+                login: login.clone(),
+                token: None,
+                password: Some(key.encrypt(password)),
+            }),
+            Auth::Anonymous => None,
+        }
+    }
+
+    pub fn open(&self, key: &CredentialsKey) -> Result<Auth, RollingError> {
+        match (&self.token, &self.password) {
+            (Some(token), _) => Ok(Auth::Token {
+                login: self.login.clone(),
+                token: key.decrypt(token)?,
+            }),
+            (None, Some(password)) => Ok(Auth::Basic {
+                login: self.login.clone(),
+                password: key.decrypt(password)?,
+            }),
+            (None, None) => Err(RollingError::new(
+                "No token or password in remembered credentials".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let key = CredentialsKey {
+            key: [42; KEY_LENGTH],
+        };
+        let other_key = CredentialsKey {
+            key: [43; KEY_LENGTH],
+        };
+
+        let encrypted = key.encrypt("my password");
+
+        assert!(!encrypted.contains("my password"));
+        assert_ne!(encrypted, key.encrypt("my password"));
+        assert_eq!(key.decrypt(&encrypted).unwrap(), "my password");
+        assert!(other_key.decrypt(&encrypted).is_err());
+        assert!(key.decrypt("abc").is_err());
+
+        let mut altered = base64::decode(&encrypted).unwrap();
+        let last = altered.len() - 1;
+        altered[last] ^= 1;
+        assert!(key.decrypt(&base64::encode(altered)).is_err());
+    }
+
+    #[test]
+    fn test_seal_open() {
+        let key = CredentialsKey {
+            key: [7; KEY_LENGTH],
+        };
+        let auth = Auth::Token {
+            login: "bob".to_string(),
+            token: "abcdef".to_string(),
+        };
+
+        let credentials = StoredCredentials::seal(&auth, &key).unwrap();
+        let value = serde_json::to_value(&credentials).unwrap();
+
+        assert_eq!(value["login"], "bob");
+        assert!(!value.to_string().contains("abcdef"));
+        match credentials.open(&key).unwrap() {
+            Auth::Token { login, token } => {
+                assert_eq!(login, "bob");
+                assert_eq!(token, "abcdef");
+            }
+            auth => panic!("Unexpected auth {:?}", auth),
+        }
+        assert!(StoredCredentials::seal(&Auth::Anonymous, &key).is_none());
+
+        let basic = Auth::Basic {
+            login: "bob".to_string(),
+            password: "hunter2".to_string(),
+        };
+        let credentials = StoredCredentials::seal(&basic, &key).unwrap();
+        let value = serde_json::to_value(&credentials).unwrap();
+
+        assert!(value.get("token").is_none());
+        assert!(!value.to_string().contains("hunter2"));
+        match credentials.open(&key).unwrap() {
+            Auth::Basic { login, password } => {
+                assert_eq!(login, "bob");
+                assert_eq!(password, "hunter2");
+            }
+            auth => panic!("Unexpected auth {:?}", auth),
+        }
+    }
+
+    #[test]
+    fn test_load_key() {
+        let file_path = std::env::temp_dir().join(format!(
+            "rollgui-test-{}-{}",
+            KEY_FILE_NAME,
+            std::process::id()
+        ));
+        fs::remove_file(&file_path).ok();
+
+        assert!(CredentialsKey::load(&file_path).unwrap().is_none());
+        assert!(!file_path.exists());
+        let key = CredentialsKey::load_or_create(&file_path).unwrap();
+        let loaded = CredentialsKey::load(&file_path).unwrap().unwrap();
+        assert_eq!(key.key, loaded.key);
+
+        fs::remove_file(&file_path).ok();
+    }
+}
@@ -0,0 +1,57 @@
+use crate::util;
+use serde_derive::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+pub const RESPONSE_CACHE_FOLDER: &str = "cache/http";
+
+/// Response body with its validators, as stored on disk
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CachedResponse {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: String,
+}
+
+/// On disk cache of http responses, keyed by url. One json file by url.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    folder: PathBuf,
+}
+
+impl ResponseCache {
+    pub fn new<P: Into<PathBuf>>(folder: P) -> Self {
+        Self {
+            folder: folder.into(),
+        }
+    }
+
+    fn file_path(&self, url: &str) -> PathBuf {
+        self.folder
+            .join(format!("{:016x}.json", util::fnv1a_hash(url.as_bytes())))
+    }
+
+    /// Unreadable entries (or entries of another url with same hash) are ignored
+    pub fn get(&self, url: &str) -> Option<CachedResponse> {
+        let content = fs::read(self.file_path(url)).ok()?;
+        match serde_json::from_slice::<CachedResponse>(&content) {
+            Ok(cached_response) if cached_response.url == url => Some(cached_response),
+            Ok(_) => None,
+            Err(error) => {
+                eprintln!("Ignore invalid cached response of {}: {}", url, error);
+                None
+            }
+        }
+    }
+
+    /// Write is done in a temporary file then renamed to never leave a partial entry
+    pub fn store(&self, cached_response: &CachedResponse) -> io::Result<()> {
+        fs::create_dir_all(&self.folder)?;
+        let file_path = self.file_path(&cached_response.url);
+        let tmp_file_path = file_path.with_extension("tmp");
+        fs::write(&tmp_file_path, serde_json::to_vec(cached_response)?)?;
+        fs::rename(&tmp_file_path, &file_path)
+    }
+}
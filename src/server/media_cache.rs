@@ -0,0 +1,527 @@
+use crate::util;
+use ini::Ini;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const MEDIA_CACHE_FOLDER: &str = "cache/media";
+pub const DEFAULT_MAX_SIZE_MB: u64 = 200;
+const INDEX_FILE_NAME: &str = "index.json";
+const TMP_EXTENSION: &str = "tmp";
+/// Folders where medias were downloaded before media cache existed, relative to working
+/// directory like `MEDIA_CACHE_FOLDER`. Sub folders first: `cache` contains them.
+const LEGACY_FOLDERS: [(&str, MediaKind); 3] = [
+    ("cache/bg", MediaKind::MediaBackground),
+    ("static/cache", MediaKind::Image),
+    ("cache", MediaKind::Media),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MediaKind {
+    /// Illustrations, loading screens, avatars
+    Media,
+    /// Blurred background version of illustrations
+    MediaBackground,
+    /// Images downloaded by id
+    Image,
+}
+
+impl MediaKind {
+    fn folder_name(&self) -> &'static str {
+        match self {
+            MediaKind::Media => "",
+            MediaKind::MediaBackground => "bg",
+            MediaKind::Image => "images",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MediaEntry {
+    pub size: u64,
+    /// Content hash, None for files found in cache folder but not written by this cache
+    pub hash: Option<String>,
+    /// Unix timestamp of last use, in seconds
+    pub last_access: u64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct MediaIndex {
+    /// Entries by path relative to cache folder
+    entries: BTreeMap<String, MediaEntry>,
+    #[serde(skip)]
+    folder: PathBuf,
+    /// Accesses are only written with next change, or when index is dropped
+    #[serde(skip)]
+    unsaved_accesses: bool,
+}
+
+impl MediaIndex {
+    fn total_size(&self) -> u64 {
+        self.entries.values().map(|entry| entry.size).sum()
+    }
+
+    fn save(&mut self) {
+        let result = fs::create_dir_all(&self.folder).and_then(|_| {
+            let index_path = self.folder.join(INDEX_FILE_NAME);
+            let tmp_index_path = index_path.with_extension(TMP_EXTENSION);
+            fs::write(&tmp_index_path, serde_json::to_vec(self)?)?;
+            fs::rename(&tmp_index_path, &index_path)
+        });
+        match result {
+            Ok(_) => self.unsaved_accesses = false,
+            Err(error) => eprintln!("Unable to write media cache index: {}", error),
+        }
+    }
+}
+
+impl Drop for MediaIndex {
+    fn drop(&mut self) {
+        if self.unsaved_accesses {
+            self.save();
+        }
+    }
+}
+
+/// Downloaded medias, stored in one folder and limited in size: least recently used medias
+/// are removed when cache exceed its max size. Clones share the same index.
+#[derive(Clone)]
+pub struct MediaCache {
+    folder: PathBuf,
+    max_size: u64,
+    index: Arc<Mutex<MediaIndex>>,
+}
+
+impl MediaCache {
+    pub fn new<P: Into<PathBuf>>(folder: P, max_size: u64) -> Self {
+        let folder = folder.into();
+        let index = load_index(&folder);
+        let cache = Self {
+            folder,
+            max_size,
+            index: Arc::new(Mutex::new(index)),
+        };
+
+        // Adopted files can exceed max size
+        {
+            let mut index = cache.index.lock().unwrap();
+            if index.total_size() > cache.max_size {
+                cache.evict(&mut index, "");
+                index.save();
+            }
+        }
+        cache
+    }
+
+    /// Cache in default folder with max size from `media_max_size_mb` of `[cache]` section
+    pub fn from_conf(conf: &Ini) -> Self {
+        let max_size_mb = match conf.get_from(Some("cache"), "media_max_size_mb") {
+            Some(value) => value.parse::<u64>().unwrap_or_else(|_| {
+                eprintln!("Invalid media_max_size_mb in config: {}", value);
+                DEFAULT_MAX_SIZE_MB
+            }),
+            None => DEFAULT_MAX_SIZE_MB,
+        };
+        migrate_legacy_folders(Path::new(""), Path::new(MEDIA_CACHE_FOLDER));
+        Self::new(MEDIA_CACHE_FOLDER, max_size_mb * 1024 * 1024)
+    }
+
+    pub fn folder(&self) -> &Path {
+        &self.folder
+    }
+
+    pub fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    fn key(kind: MediaKind, name: &str) -> io::Result<String> {
+        if name.is_empty()
+            || name.contains('/')
+            || name.contains('\\')
+            || name.starts_with('.')
+            || name == INDEX_FILE_NAME
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid media name \"{}\"", name),
+            ));
+        }
+        Ok(match kind.folder_name() {
+            "" => name.to_string(),
+            folder_name => format!("{}/{}", folder_name, name),
+        })
+    }
+
+    /// Where media is (or would be) stored
+    pub fn path(&self, kind: MediaKind, name: &str) -> PathBuf {
+        self.folder.join(kind.folder_name()).join(name)
+    }
+
+    /// Path of cached media if it is present and has its expected size
+    pub fn get(&self, kind: MediaKind, name: &str) -> Option<PathBuf> {
+        let key = Self::key(kind, name).ok()?;
+        let path = self.path(kind, name);
+        let mut index = self.index.lock().unwrap();
+        let expected_size = index.entries.get(&key)?.size;
+
+        match fs::metadata(&path) {
+            Ok(metadata) if metadata.len() == expected_size => {}
+            _ => {
+                eprintln!("Cached media {} is missing or corrupted", key);
+                index.entries.remove(&key);
+                let _ = fs::remove_file(&path);
+                index.save();
+                return None;
+            }
+        }
+
+        if let Some(entry) = index.entries.get_mut(&key) {
+            entry.last_access = now();
+            index.unsaved_accesses = true;
+        }
+        Some(path)
+    }
+
+    /// Write media (in a temporary file, then renamed) and remove least recently used medias
+    /// if cache is now too big
+    pub fn store(&self, kind: MediaKind, name: &str, content: &[u8]) -> io::Result<PathBuf> {
+        let key = Self::key(kind, name)?;
+        let path = self.path(kind, name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Random part avoid conflicts when same media is downloaded by several threads
+        let tmp_path = self.folder.join(format!(
+            "{}.{}.{}",
+            name,
+            util::rand_string(8),
+            TMP_EXTENSION
+        ));
+        fs::write(&tmp_path, content)?;
+        fs::rename(&tmp_path, &path)?;
+
+        let mut index = self.index.lock().unwrap();
+        index.entries.insert(
+            key.clone(),
+            MediaEntry {
+                size: content.len() as u64,
+                hash: Some(hash(content)),
+                last_access: now(),
+            },
+        );
+        self.evict(&mut index, &key);
+        index.save();
+        Ok(path)
+    }
+
+    fn evict(&self, index: &mut MediaIndex, keep_key: &str) {
+        while index.total_size() > self.max_size {
+            let oldest_key = index
+                .entries
+                .iter()
+                .filter(|(key, _)| key.as_str() != keep_key)
+                .min_by_key(|(_, entry)| entry.last_access)
+                .map(|(key, _)| key.clone());
+            let oldest_key = match oldest_key {
+                Some(oldest_key) => oldest_key,
+                None => break,
+            };
+            println!("Evict media {} from cache", oldest_key);
+            if let Err(error) = fs::remove_file(self.folder.join(&oldest_key)) {
+                eprintln!("Unable to remove cached media {}: {}", oldest_key, error);
+            }
+            index.entries.remove(&oldest_key);
+        }
+    }
+
+    /// Cached medias (path relative to cache folder and entry), most recently used first
+    pub fn entries(&self) -> Vec<(String, MediaEntry)> {
+        let index = self.index.lock().unwrap();
+        let mut entries: Vec<(String, MediaEntry)> = index
+            .entries
+            .iter()
+            .map(|(key, entry)| (key.clone(), entry.clone()))
+            .collect();
+        entries.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.last_access));
+        entries
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.index.lock().unwrap().total_size()
+    }
+
+    /// Check size and hash of each cached media. Corrupted medias are removed and returned.
+    pub fn verify(&self) -> Vec<String> {
+        let mut index = self.index.lock().unwrap();
+        let mut corrupted = vec![];
+
+        for (key, entry) in index.entries.iter() {
+            let is_valid = match fs::read(self.folder.join(key)) {
+                Ok(content) => {
+                    // Adopted files have no hash, only their size can be checked
+                    let hash_is_valid = match &entry.hash {
+                        Some(entry_hash) => *entry_hash == hash(&content),
+                        None => true,
+                    };
+                    content.len() as u64 == entry.size && hash_is_valid
+                }
+                Err(_) => false,
+            };
+            if !is_valid {
+                corrupted.push(key.clone());
+            }
+        }
+
+        for key in corrupted.iter() {
+            let _ = fs::remove_file(self.folder.join(key));
+            index.entries.remove(key);
+        }
+        index.save();
+        corrupted
+    }
+
+    /// Remove all cached medias, return count of removed medias
+    pub fn purge(&self) -> io::Result<usize> {
+        let mut index = self.index.lock().unwrap();
+        let count = index.entries.len();
+        if self.folder.exists() {
+            fs::remove_dir_all(&self.folder)?;
+        }
+        index.entries.clear();
+        index.unsaved_accesses = false;
+        Ok(count)
+    }
+}
+
+/// Move medias of legacy folders into cache folder, where index adopts them. Legacy folders are
+/// removed once empty.
+fn migrate_legacy_folders(base_folder: &Path, cache_folder: &Path) {
+    for (legacy_folder, kind) in LEGACY_FOLDERS.iter() {
+        let legacy_folder = base_folder.join(legacy_folder);
+        let read_dir = match fs::read_dir(&legacy_folder) {
+            Ok(read_dir) => read_dir,
+            Err(_) => continue,
+        };
+        let kind_folder = base_folder.join(cache_folder).join(kind.folder_name());
+
+        for dir_entry in read_dir.flatten() {
+            let path = dir_entry.path();
+            // Cache folders (media, http, ...) are in legacy `cache` folder
+            if !path.is_file() {
+                continue;
+            }
+            let result = fs::create_dir_all(&kind_folder).and_then(|_| {
+                let cache_path = kind_folder.join(dir_entry.file_name());
+                if cache_path.exists() {
+                    fs::remove_file(&path)
+                } else {
+                    fs::rename(&path, &cache_path)
+                }
+            });
+            match result {
+                Ok(_) => println!("Move legacy media {} in media cache", path.display()),
+                Err(error) => eprintln!(
+                    "Unable to move legacy media {} in media cache: {}",
+                    path.display(),
+                    error
+                ),
+            }
+        }
+        let _ = fs::remove_dir(&legacy_folder);
+    }
+}
+
+/// Read index and synchronize it with cache folder content: entries of missing files are
+/// dropped, unknown files are adopted and temporary files are removed
+fn load_index(folder: &Path) -> MediaIndex {
+    let mut index = match fs::read(folder.join(INDEX_FILE_NAME)) {
+        Ok(content) => serde_json::from_slice(&content).unwrap_or_else(|error| {
+            eprintln!("Invalid media cache index, rebuild it: {}", error);
+            MediaIndex::default()
+        }),
+        Err(_) => MediaIndex::default(),
+    };
+    index.folder = folder.to_path_buf();
+    index.entries.retain(|key, _| folder.join(key).is_file());
+
+    for kind in &[
+        MediaKind::Media,
+        MediaKind::MediaBackground,
+        MediaKind::Image,
+    ] {
+        let kind_folder = folder.join(kind.folder_name());
+        let read_dir = match fs::read_dir(&kind_folder) {
+            Ok(read_dir) => read_dir,
+            Err(_) => continue,
+        };
+        for dir_entry in read_dir.flatten() {
+            let path = dir_entry.path();
+            let metadata = match dir_entry.metadata() {
+                Ok(metadata) if metadata.is_file() => metadata,
+                _ => continue,
+            };
+            if path.extension() == Some(OsStr::new(TMP_EXTENSION)) {
+                let _ = fs::remove_file(&path);
+                continue;
+            }
+            let name = dir_entry.file_name().to_string_lossy().to_string();
+            let key = match MediaCache::key(*kind, &name) {
+                Ok(key) => key,
+                Err(_) => continue,
+            };
+            index.entries.entry(key).or_insert_with(|| MediaEntry {
+                size: metadata.len(),
+                hash: None,
+                last_access: metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map_or(0, |duration| duration.as_secs()),
+            });
+        }
+    }
+
+    index
+}
+
+fn hash(content: &[u8]) -> String {
+    format!("{:016x}", util::fnv1a_hash(content))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temporary_cache(name: &str, max_size: u64) -> MediaCache {
+        let folder =
+            std::env::temp_dir().join(format!("rollgui-media-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        MediaCache::new(folder, max_size)
+    }
+
+    #[test]
+    fn test_store_and_get() {
+        let cache = temporary_cache("store", 1024);
+
+        assert!(cache.get(MediaKind::Media, "a.png").is_none());
+        let path = cache.store(MediaKind::Media, "a.png", b"aaaa").unwrap();
+        cache
+            .store(MediaKind::MediaBackground, "a.png", b"bb")
+            .unwrap();
+
+        assert_eq!(Some(path.clone()), cache.get(MediaKind::Media, "a.png"));
+        assert_eq!(b"aaaa".to_vec(), fs::read(&path).unwrap());
+        assert_eq!(6, cache.total_size());
+        assert!(cache.store(MediaKind::Media, "../a.png", b"").is_err());
+
+        // Index is persisted
+        let reloaded = MediaCache::new(cache.folder(), 1024);
+        assert_eq!(6, reloaded.total_size());
+
+        // Truncated file is not served
+        fs::write(&path, b"aa").unwrap();
+        assert!(cache.get(MediaKind::Media, "a.png").is_none());
+        assert!(!path.exists());
+
+        assert_eq!(1, cache.purge().unwrap());
+        assert!(!cache.folder().exists());
+    }
+
+    #[test]
+    fn test_access_saved_on_drop() {
+        let cache = temporary_cache("access", 1024);
+        cache.store(MediaKind::Media, "a", b"aaaa").unwrap();
+        {
+            let mut index = cache.index.lock().unwrap();
+            index.entries.get_mut("a").unwrap().last_access = 0;
+            index.save();
+        }
+        let index_path = cache.folder().join(INDEX_FILE_NAME);
+        let index_content = fs::read(&index_path).unwrap();
+
+        assert!(cache.get(MediaKind::Media, "a").is_some());
+        assert_eq!(index_content, fs::read(&index_path).unwrap());
+
+        let folder = cache.folder().to_path_buf();
+        drop(cache);
+        let reloaded = MediaCache::new(folder, 1024);
+        assert!(reloaded.entries()[0].1.last_access > 0);
+        reloaded.purge().unwrap();
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let cache = temporary_cache("lru", 10);
+        cache.store(MediaKind::Media, "a", b"aaaa").unwrap();
+        cache.store(MediaKind::Media, "b", b"bbbb").unwrap();
+        {
+            // Make "a" more recently used than "b"
+            let mut index = cache.index.lock().unwrap();
+            index.entries.get_mut("a").unwrap().last_access = now() + 1;
+            index.entries.get_mut("b").unwrap().last_access = now() - 1;
+        }
+
+        cache.store(MediaKind::Media, "c", b"cccc").unwrap();
+
+        assert!(cache.get(MediaKind::Media, "a").is_some());
+        assert!(cache.get(MediaKind::Media, "b").is_none());
+        assert!(cache.get(MediaKind::Media, "c").is_some());
+        assert_eq!(8, cache.total_size());
+        cache.purge().unwrap();
+    }
+
+    #[test]
+    fn test_verify() {
+        let cache = temporary_cache("verify", 1024);
+        cache.store(MediaKind::Media, "a", b"aaaa").unwrap();
+        let path = cache.store(MediaKind::Image, "1.png", b"1111").unwrap();
+        fs::write(&path, b"2222").unwrap();
+
+        assert_eq!(vec!["images/1.png".to_string()], cache.verify());
+        assert_eq!(1, cache.entries().len());
+        cache.purge().unwrap();
+    }
+
+    #[test]
+    fn test_migrate_legacy_folders() {
+        let base_folder =
+            std::env::temp_dir().join(format!("rollgui-media-legacy-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base_folder);
+        fs::create_dir_all(base_folder.join("cache/bg")).unwrap();
+        fs::create_dir_all(base_folder.join("cache/http")).unwrap();
+        fs::create_dir_all(base_folder.join("static/cache")).unwrap();
+        fs::write(base_folder.join("cache/a.png"), b"aaaa").unwrap();
+        fs::write(base_folder.join("cache/bg/a.png"), b"bb").unwrap();
+        fs::write(base_folder.join("cache/http/response"), b"r").unwrap();
+        fs::write(base_folder.join("static/cache/1.png"), b"cccccc").unwrap();
+
+        let cache_folder = Path::new("cache/media");
+        migrate_legacy_folders(&base_folder, cache_folder);
+        let cache = MediaCache::new(base_folder.join(cache_folder), 1024);
+
+        let keys: Vec<String> = cache.entries().into_iter().map(|(key, _)| key).collect();
+        assert_eq!(vec!["a.png", "bg/a.png", "images/1.png"], keys);
+        assert_eq!(12, cache.total_size());
+        assert!(!base_folder.join("cache/a.png").exists());
+        assert!(!base_folder.join("cache/bg").exists());
+        assert!(!base_folder.join("static/cache").exists());
+        assert!(base_folder.join("cache/http/response").exists());
+
+        // Adopted medias are bounded by max size
+        let cache = MediaCache::new(base_folder.join(cache_folder), 8);
+        assert!(cache.total_size() <= 8);
+
+        fs::remove_dir_all(&base_folder).unwrap();
+    }
+}
@@ -0,0 +1,791 @@
+//! Local fake rolling server for tests. HTTP endpoints are served from fixture files
+//! (`/zones/tiles` is served from `<fixtures>/zones/tiles.json` or `<fixtures>/zones/tiles`)
+//! and zone websocket emit scripted zone events.
+use crate::event;
+use crate::server::ServerAddress;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use websocket::header::{WebSocketAccept, WebSocketKey};
+use websocket::receiver::Receiver;
+use websocket::sender::Sender;
+use websocket::ws::{Receiver as _, Sender as _};
+use websocket::OwnedMessage;
+
+pub const FIXTURES_FOLDER: &str = "tests/fixtures/mock_server";
+pub const MOCK_TOKEN: &str = "mock-session-token";
+const BASE64_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    credentials: Option<(String, String)>,
+    tokens_disabled: bool,
+    /// Session token invalidated by /account/logout
+    token_revoked: bool,
+    zone_events: Vec<String>,
+    received_events: Vec<String>,
+    requests: Vec<String>,
+    not_modified_responses: usize,
+    /// Remaining failures (count and status) by path
+    failures: HashMap<String, (usize, u16)>,
+}
+
+pub struct MockServer {
+    address: ServerAddress,
+    state: Arc<Mutex<MockState>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl MockServer {
+    /// Start server on a free localhost port, serving files of given fixtures folder
+    pub fn start(fixtures_folder: &str) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Unable to bind mock server");
+        let port = listener.local_addr().unwrap().port();
+        let fixtures_folder = Path::new(env!("CARGO_MANIFEST_DIR")).join(fixtures_folder);
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let state_ = Arc::clone(&state);
+        let stopped_ = Arc::clone(&stopped);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped_.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let fixtures_folder = fixtures_folder.clone();
+                    let state = Arc::clone(&state_);
+                    thread::spawn(move || {
+                        if let Err(error) = handle_connection(stream, &fixtures_folder, &state) {
+                            eprintln!("Mock server: {}", error);
+                        }
+                    });
+                }
+            }
+        });
+
+        Self {
+            address: ServerAddress::unsecure("127.0.0.1", port),
+            state,
+            stopped,
+        }
+    }
+
+    pub fn address(&self) -> ServerAddress {
+        self.address.clone()
+    }
+
+    /// Require authentication (except for /system/ endpoints). Session token is delivered
+    /// by /account/login with basic auth.
+    pub fn set_credentials(&self, login: &str, password: &str) {
+        self.state.lock().unwrap().credentials = Some((login.to_string(), password.to_string()));
+    }
+
+    /// Behave like older servers: no /account/login, basic auth on each request
+    pub fn disable_tokens(&self) {
+        self.state.lock().unwrap().tokens_disabled = true;
+    }
+
+    /// Respond to next `count` requests of given path with given error status
+    pub fn fail_next_requests(&self, path: &str, count: usize, status: u16) {
+        self.state
+            .lock()
+            .unwrap()
+            .failures
+            .insert(path.to_string(), (count, status));
+    }
+
+    /// Event to send to each zone websocket when connected
+    pub fn push_zone_event(&self, event: event::ZoneEvent) {
+        self.state
+            .lock()
+            .unwrap()
+            .zone_events
+            .push(serde_json::to_string(&event).unwrap());
+    }
+
+    /// Events received on zone websockets
+    pub fn received_events(&self) -> Vec<event::ZoneEvent> {
+        self.state
+            .lock()
+            .unwrap()
+            .received_events
+            .iter()
+            .map(|event_str| serde_json::from_str::<event::ZoneEvent>(event_str).unwrap())
+            .collect()
+    }
+
+    /// Received http requests, as "<METHOD> <path>"
+    pub fn requests(&self) -> Vec<String> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Count of 304 responses given to requests with a matching If-None-Match
+    pub fn not_modified_responses(&self) -> usize {
+        self.state.lock().unwrap().not_modified_responses
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wake up listener
+        let _ = TcpStream::connect(("127.0.0.1", self.address.port));
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    fixtures_folder: &Path,
+    state: &Arc<Mutex<MockState>>,
+) -> Result<(), String> {
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    let request = match read_request(&mut reader)? {
+        Some(request) => request,
+        None => return Ok(()),
+    };
+    state
+        .lock()
+        .unwrap()
+        .requests
+        .push(format!("{} {}", request.method, request.path));
+
+    let mut stream = stream;
+    if !is_authorized(&request, state) {
+        return write_response(&mut stream, 401, "application/json", b"{}");
+    }
+
+    if request
+        .header("Upgrade")
+        .map_or(false, |upgrade| upgrade.eq_ignore_ascii_case("websocket"))
+    {
+        return handle_websocket(reader, stream, &request, state);
+    }
+
+    let failure_status = match state.lock().unwrap().failures.get_mut(&request.path) {
+        Some((count, status)) if *count > 0 => {
+            *count -= 1;
+            Some(*status)
+        }
+        _ => None,
+    };
+    if let Some(status) = failure_status {
+        let body = format!(r#"{{"message": "Mock failure {}"}}"#, status);
+        return write_response(&mut stream, status, "application/json", body.as_bytes());
+    }
+
+    if request.path == "/account/login" && !state.lock().unwrap().tokens_disabled {
+        state.lock().unwrap().token_revoked = false;
+        let body = format!(r#"{{"token": "{}"}}"#, MOCK_TOKEN);
+        return write_response(&mut stream, 200, "application/json", body.as_bytes());
+    }
+
+    if request.path == "/account/logout" && !state.lock().unwrap().tokens_disabled {
+        state.lock().unwrap().token_revoked = true;
+        return write_response(&mut stream, 200, "application/json", b"{}");
+    }
+
+    match find_fixture(fixtures_folder, &request.path) {
+        Some(file_path) => {
+            let content_type = match file_path.extension().and_then(|e| e.to_str()) {
+                Some("json") => "application/json",
+                Some("png") => "image/png",
+                _ => "text/plain",
+            };
+            let body = fs::read(&file_path).map_err(|e| e.to_string())?;
+            let mut hasher = DefaultHasher::new();
+            body.hash(&mut hasher);
+            let etag = format!("\"{:x}\"", hasher.finish());
+            if request.header("If-None-Match") == Some(etag.as_str()) {
+                state.lock().unwrap().not_modified_responses += 1;
+                return write_response(&mut stream, 304, content_type, b"");
+            }
+            write_response_with_etag(&mut stream, 200, content_type, &body, Some(&etag))
+        }
+        None => write_response(
+            &mut stream,
+            404,
+            "application/json",
+            br#"{"message": "Not Found"}"#,
+        ),
+    }
+}
+
+fn read_request(reader: &mut BufReader<TcpStream>) -> Result<Option<HttpRequest>, String> {
+    let mut request_line = String::new();
+    if reader
+        .read_line(&mut request_line)
+        .map_err(|e| e.to_string())?
+        == 0
+    {
+        return Ok(None);
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let path = parts.next().unwrap_or("").to_string();
+
+    let mut headers = vec![];
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).map_err(|e| e.to_string())?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(separator_i) = line.find(':') {
+            headers.push((
+                line[..separator_i].trim().to_string(),
+                line[separator_i + 1..].trim().to_string(),
+            ));
+        }
+    }
+
+    let request = HttpRequest {
+        method,
+        path,
+        headers,
+    };
+
+    // Body is not used, but must be consumed
+    let content_length = request
+        .header("Content-Length")
+        .and_then(|length| length.parse::<u64>().ok())
+        .unwrap_or(0);
+    let mut body = vec![];
+    reader
+        .take(content_length)
+        .read_to_end(&mut body)
+        .map_err(|e| e.to_string())?;
+
+    Ok(Some(request))
+}
+
+fn is_authorized(request: &HttpRequest, state: &Arc<Mutex<MockState>>) -> bool {
+    if request.path.starts_with("/system/") {
+        return true;
+    }
+    let state = state.lock().unwrap();
+    match &state.credentials {
+        Some((login, password)) => {
+            let basic = format!(
+                "Basic {}",
+                base64(format!("{}:{}", login, password).as_bytes())
+            );
+            let bearer = format!("Bearer {}", MOCK_TOKEN);
+            match request.header("Authorization") {
+                Some(authorization) if authorization == basic => true,
+                Some(authorization) => {
+                    !state.tokens_disabled && !state.token_revoked && authorization == bearer
+                }
+                None => false,
+            }
+        }
+        None => true,
+    }
+}
+
+fn find_fixture(fixtures_folder: &Path, path: &str) -> Option<PathBuf> {
+    let path = path.split('?').next().unwrap_or("").trim_matches('/');
+    if path.is_empty() || path.split('/').any(|part| part == "..") {
+        return None;
+    }
+
+    let file_path = fixtures_folder.join(path);
+    if file_path.is_file() {
+        return Some(file_path);
+    }
+    let json_file_path = fixtures_folder.join(format!("{}.json", path));
+    if json_file_path.is_file() {
+        return Some(json_file_path);
+    }
+    None
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    content_type: &str,
+    body: &[u8],
+) -> Result<(), String> {
+    write_response_with_etag(stream, status, content_type, body, None)
+}
+
+fn write_response_with_etag(
+    stream: &mut TcpStream,
+    status: u16,
+    content_type: &str,
+    body: &[u8],
+    etag: Option<&str>,
+) -> Result<(), String> {
+    let reason = match status {
+        200 => "OK",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+        status,
+        reason,
+        content_type,
+        body.len(),
+        etag.map_or("".to_string(), |etag| format!("ETag: {}\r\n", etag)),
+    )
+    .and_then(|_| stream.write_all(body))
+    .map_err(|e| e.to_string())
+}
+
+fn handle_websocket(
+    mut reader: BufReader<TcpStream>,
+    mut stream: TcpStream,
+    request: &HttpRequest,
+    state: &Arc<Mutex<MockState>>,
+) -> Result<(), String> {
+    let key: WebSocketKey = request
+        .header("Sec-WebSocket-Key")
+        .ok_or("Missing websocket key")?
+        .parse()
+        .map_err(|e| format!("{}", e))?;
+    // Mock server always accept duplex mode, so client use only one connection
+    let protocol = match request.header("Sec-WebSocket-Protocol") {
+        Some(protocols) if protocols.contains(crate::socket::DUPLEX_PROTOCOL) => format!(
+            "Sec-WebSocket-Protocol: {}\r\n",
+            crate::socket::DUPLEX_PROTOCOL
+        ),
+        _ => "".to_string(),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n{}\r\n",
+        WebSocketAccept::new(&key).serialize(),
+        protocol,
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut sender = Sender::new(false);
+    let mut receiver = Receiver::new(true);
+    let zone_events = state.lock().unwrap().zone_events.clone();
+    for zone_event in zone_events {
+        sender
+            .send_message(&mut stream, &OwnedMessage::Text(zone_event))
+            .map_err(|e| e.to_string())?;
+    }
+
+    loop {
+        match receiver.recv_message(&mut reader) {
+            Ok(OwnedMessage::Text(text)) => {
+                let close_wanted = match serde_json::from_str::<event::ZoneEvent>(&text) {
+                    Ok(event) => event.event_type_name == event::CLIENT_WANT_CLOSE,
+                    Err(_) => false,
+                };
+                state.lock().unwrap().received_events.push(text);
+
+                if close_wanted {
+                    let permit_close = event::ZoneEvent {
+                        event_type_name: event::SERVER_PERMIT_CLOSE.to_string(),
+                        event_type: event::ZoneEventType::ServerPermitClose,
+                    };
+                    let _ = sender.send_message(
+                        &mut stream,
+                        &OwnedMessage::Text(serde_json::to_string(&permit_close).unwrap()),
+                    );
+                    let _ = sender.send_message(&mut stream, &OwnedMessage::Close(None));
+                    // Let client read messages before socket is dropped
+                    thread::sleep(Duration::from_millis(100));
+                    return Ok(());
+                }
+            }
+            Ok(OwnedMessage::Ping(data)) => {
+                let _ = sender.send_message(&mut stream, &OwnedMessage::Pong(data));
+            }
+            Ok(OwnedMessage::Close(_)) | Err(_) => return Ok(()),
+            Ok(_) => {}
+        }
+    }
+}
+
+fn base64(input: &[u8]) -> String {
+    let mut output = String::new();
+    for chunk in input.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let value = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(BASE64_CHARS[(value >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::Level;
+    use crate::server::bootstrap::ZoneBootstrap;
+    use crate::server::client::{Auth, Client, ClientError};
+    use crate::server::http_cache::ResponseCache;
+    use crate::server::media_cache::{MediaCache, MediaKind};
+    use crate::server::policy::RequestPolicy;
+    use crate::server::Server;
+    use crate::socket::{ConnectionState, ZoneSocket};
+    use crate::tile::zone::Tiles as ZoneTiles;
+    use crate::tile::TransportType;
+    use crate::util;
+    use std::time::Instant;
+
+    const TIMEOUT_S: u64 = 10;
+
+    fn logged_client(server: &MockServer) -> Client {
+        let client = Client::new(server.address(), Auth::Anonymous);
+        let auth = client.login("alice", "secret").unwrap();
+        client.with_auth(auth)
+    }
+
+    fn start_server() -> MockServer {
+        let server = MockServer::start(FIXTURES_FOLDER);
+        server.set_credentials("alice", "secret");
+        server
+    }
+
+    fn temporary_folder(name: &str) -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("rollgui-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        folder
+    }
+
+    fn wait_for<T, F: FnMut() -> Option<T>>(mut poll: F) -> T {
+        let started_at = Instant::now();
+        loop {
+            if let Some(value) = poll() {
+                return value;
+            }
+            if started_at.elapsed() > Duration::from_secs(TIMEOUT_S) {
+                panic!("Timeout reached");
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    #[test]
+    fn test_login() {
+        let server = start_server();
+        let client = Client::new(server.address(), Auth::Anonymous);
+
+        assert_eq!((0, 10, 4), client.get_version().unwrap());
+        match client.login("alice", "wrong") {
+            Err(ClientError::Unauthorized) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+        match client.get_current_character_id() {
+            Err(ClientError::Unauthorized) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        let auth = client.login("alice", "secret").unwrap();
+        match &auth {
+            Auth::Token { login, token } => {
+                assert_eq!("alice", login);
+                assert_eq!(MOCK_TOKEN, token);
+            }
+            other => panic!("Unexpected auth {:?}", other),
+        }
+        assert!(!format!("{:?}", auth).contains(MOCK_TOKEN));
+
+        let client = client.with_auth(auth);
+        let character_id = client.get_current_character_id().unwrap();
+        assert_eq!("abc", character_id);
+
+        let player = client.get_player(&character_id).unwrap();
+        assert_eq!((2, 3), player.position);
+        assert_eq!((1, 1), player.world_position);
+        assert!(!client.player_is_dead(&character_id).unwrap());
+
+        let server_ = Server::new(client, server.address(), Some(character_id)).unwrap();
+        assert_eq!((3, 3), (server_.world.width, server_.world.height));
+        assert_eq!(Some("PLAIN".to_string()), server_.world.tile_id(1, 1));
+    }
+
+    #[test]
+    fn test_logout() {
+        let server = start_server();
+        let client = Client::new(server.address(), Auth::Anonymous);
+        let client = client.with_auth(client.login("alice", "secret").unwrap());
+        assert_eq!("abc", client.get_current_character_id().unwrap());
+
+        client.logout().unwrap();
+        match client.get_current_character_id() {
+            Err(ClientError::Unauthorized) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_login_without_tokens() {
+        let server = start_server();
+        server.disable_tokens();
+        let client = Client::new(server.address(), Auth::Anonymous);
+
+        let auth = client.login("alice", "secret").unwrap();
+        match &auth {
+            Auth::Basic { login, .. } => assert_eq!("alice", login),
+            other => panic!("Unexpected auth {:?}", other),
+        }
+        assert!(!format!("{:?}", auth).contains("secret"));
+        assert_eq!(
+            "abc",
+            client.with_auth(auth).get_current_character_id().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_zone_bootstrap() {
+        let server = start_server();
+        let media_folder = temporary_folder("bootstrap-media");
+        let client =
+            logged_client(&server).with_media_cache(MediaCache::new(&media_folder, 1024 * 1024));
+
+        let mut zone_bootstrap = ZoneBootstrap::start(&client, "abc", (1, 1));
+        let data = wait_for(|| zone_bootstrap.poll()).unwrap();
+        assert_eq!(1, data.resume_text.len());
+        assert_eq!(1, data.stuffs.len());
+        assert_eq!(1, data.resources.len());
+        assert!(data.builds.contains_key(&1));
+        assert_eq!(1, data.characters.len());
+        // Validated avatar of zone character is downloaded
+        let avatar_name = "character_avatar__zone_thumb__bob-avatar.png";
+        assert_eq!(vec!["bob-avatar".to_string()], data.avatars);
+        assert!(server
+            .requests()
+            .contains(&format!("GET /media/{}", avatar_name)));
+        let avatar_path = client
+            .media_cache()
+            .get(MediaKind::Media, avatar_name)
+            .unwrap();
+        assert_eq!(
+            fs::read(Path::new(FIXTURES_FOLDER).join("media").join(avatar_name)).unwrap(),
+            fs::read(avatar_path).unwrap()
+        );
+
+        let tiles = ZoneTiles::new(data.tiles_data);
+        let zone_raw = util::extract_block_from_source(
+            util::BLOCK_GEO,
+            data.zone_data["raw_source"].as_str().unwrap(),
+        )
+        .unwrap();
+        let level = Level::new(&zone_raw, &tiles, "PLAIN".to_string()).unwrap();
+        assert_eq!((6, 5), (level.width, level.height));
+        assert!(tiles.traversable(&level.rows[1].cols[1], TransportType::Walking));
+        assert!(!tiles.traversable(&level.rows[2].cols[2], TransportType::Walking));
+
+        // Unknown zone
+        let mut zone_bootstrap = ZoneBootstrap::start(&client, "abc", (9, 9));
+        assert!(wait_for(|| zone_bootstrap.poll()).is_err());
+    }
+
+    #[test]
+    fn test_description() {
+        let server = start_server();
+        let client = logged_client(&server);
+
+        let description = client
+            .describe("/_describe/character/create", None, None)
+            .unwrap();
+        assert_eq!(Some("Créer un personnage".to_string()), description.title);
+        assert!(description.items[0].is_form);
+        assert!(server
+            .requests()
+            .contains(&"POST /_describe/character/create".to_string()));
+
+        match client.describe("/_describe/unknown", None, None) {
+            Err(ClientError::NotFound { .. }) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_zone_socket() {
+        let server = start_server();
+        server.push_zone_event(event::ZoneEvent {
+            event_type_name: event::CHARACTER_EXIT_ZONE.to_string(),
+            event_type: event::ZoneEventType::CharacterExit {
+                character_id: "bob".to_string(),
+            },
+        });
+
+        let client = logged_client(&server);
+        let mut socket = ZoneSocket::new(
+            format!("{}/ws/zones/1/1/events?character_id=abc", server.address()),
+            client.websocket_headers(),
+        );
+        socket.connect(false);
+
+        let events = wait_for(|| {
+            let events = socket.pending_events();
+            if events.is_empty() {
+                None
+            } else {
+                Some(events)
+            }
+        });
+        assert_eq!(event::CHARACTER_EXIT_ZONE, events[0].event_type_name);
+        assert_eq!(ConnectionState::Connected, socket.state());
+
+        socket.send(event::ZoneEvent {
+            event_type_name: event::PLAYER_MOVE.to_string(),
+            event_type: event::ZoneEventType::PlayerMove {
+                to_row_i: 2,
+                to_col_i: 4,
+                character_id: "abc".to_string(),
+            },
+        });
+        socket.close().unwrap();
+        assert_eq!(ConnectionState::Closed, socket.state());
+
+        let received: Vec<String> = server
+            .received_events()
+            .into_iter()
+            .map(|event| event.event_type_name)
+            .collect();
+        assert_eq!(
+            vec![
+                event::PLAYER_MOVE.to_string(),
+                event::CLIENT_WANT_CLOSE.to_string()
+            ],
+            received
+        );
+    }
+
+    #[test]
+    fn test_response_cache() {
+        let server = start_server();
+        let cache_folder = temporary_folder("response-cache");
+        let client = logged_client(&server).with_response_cache(ResponseCache::new(&cache_folder));
+
+        let world_source = client.get_world_source().unwrap();
+        let tiles = client.get_tiles_data().unwrap();
+        assert_eq!(0, server.not_modified_responses());
+
+        // Second requests are revalidated with etag
+        assert_eq!(world_source, client.get_world_source().unwrap());
+        assert_eq!(tiles, client.get_tiles_data().unwrap());
+        assert_eq!(2, server.not_modified_responses());
+
+        // Cached responses are served when server is unavailable
+        let cache_url = format!("{}/world/source", server.address());
+        let cached_response = ResponseCache::new(&cache_folder).get(&cache_url).unwrap();
+        let unavailable_address = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            ServerAddress::unsecure("127.0.0.1", listener.local_addr().unwrap().port())
+        };
+        let mut cached_response = cached_response;
+        cached_response.url = format!("{}/world/source", unavailable_address);
+        ResponseCache::new(&cache_folder)
+            .store(&cached_response)
+            .unwrap();
+        let mut offline_client = client.clone().with_policy(RequestPolicy {
+            max_retries: 0,
+            ..RequestPolicy::default()
+        });
+        offline_client.address = unavailable_address;
+        assert_eq!(world_source, offline_client.get_world_source().unwrap());
+        assert!(offline_client.get_tiles_data().is_err());
+
+        fs::remove_dir_all(&cache_folder).unwrap();
+    }
+
+    #[test]
+    fn test_request_policy() {
+        let server = start_server();
+        let policy = RequestPolicy {
+            max_retries: 2,
+            retry_delay: Duration::from_millis(1),
+            ..RequestPolicy::default()
+        };
+        let client = logged_client(&server).with_policy(policy);
+
+        // GET are retried on gateway errors
+        server.fail_next_requests("/system/version", 2, 503);
+        assert_eq!((0, 10, 4), client.get_version().unwrap());
+
+        server.fail_next_requests("/system/version", 3, 503);
+        match client.get_version() {
+            Err(ClientError::RetriesExhausted {
+                attempts,
+                last_error,
+            }) => {
+                assert_eq!(3, attempts);
+                match *last_error {
+                    ClientError::ServerSideError {
+                        status, response, ..
+                    } => {
+                        assert_eq!(503, status);
+                        assert_eq!("Mock failure 503", response.unwrap().message);
+                    }
+                    other => panic!("Unexpected last error {:?}", other),
+                }
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        // Other errors are not retried
+        server.fail_next_requests("/system/version", 1, 400);
+        match client.get_version() {
+            Err(ClientError::ClientSideError {
+                status, response, ..
+            }) => {
+                assert_eq!(400, status);
+                assert_eq!("Mock failure 400", response.unwrap().message);
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+        assert_eq!((0, 10, 4), client.get_version().unwrap());
+
+        // Form posts are never retried
+        server.fail_next_requests("/_describe/character/create", 1, 503);
+        match client.describe("/_describe/character/create", None, None) {
+            Err(ClientError::ServerSideError { status, .. }) => assert_eq!(503, status),
+            other => panic!("Unexpected result {:?}", other),
+        }
+        let describe_requests = server
+            .requests()
+            .into_iter()
+            .filter(|request| request == "POST /_describe/character/create")
+            .count();
+        assert_eq!(1, describe_requests);
+    }
+}
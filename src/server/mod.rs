@@ -4,7 +4,17 @@ use crate::world;
 use std::error::Error;
 use std::fmt;
 
+pub mod bootstrap;
 pub mod client;
+pub mod credentials;
+pub mod http_cache;
+pub mod media_cache;
+#[cfg(test)]
+pub mod mock;
+pub mod network;
+pub mod policy;
+pub mod profile;
+pub mod request;
 
 #[derive(Clone, Debug)]
 pub struct ServerAddress {
@@ -29,14 +39,6 @@ impl ServerAddress {
             secure: false,
         }
     }
-
-    pub fn with_credentials(&self, login: &str, password: &str) -> String {
-        let protocol = if self.secure { "https" } else { "http" };
-        format!(
-            "{}://{}:{}@{}:{}",
-            protocol, login, password, self.host, self.port
-        )
-    }
 }
 
 impl fmt::Display for ServerAddress {
@@ -53,6 +55,7 @@ pub struct Server {
     pub client: client::Client,
     pub world: world::World,
     pub world_tiles: WorldTiles,
+    pub world_source: String,
 }
 
 impl fmt::Debug for Server {
@@ -72,6 +75,16 @@ impl Server {
         // TODO grab possible moves, etc from server
 
         let world_source = client.get_world_source()?;
+        Self::from_world_source(client, address, character_id, world_source)
+    }
+
+    /// Build server from an already known world source (e.g. from a zone recording)
+    pub fn from_world_source(
+        client: client::Client,
+        address: ServerAddress,
+        character_id: Option<String>,
+        world_source: String,
+    ) -> Result<Self, Box<dyn Error>> {
         let legend = util::extract_block_from_source("LEGEND", world_source.as_str())?;
         let world_raw = util::extract_block_from_source("GEO", world_source.as_str())?;
 
@@ -84,6 +97,7 @@ impl Server {
             client,
             world,
             world_tiles,
+            world_source,
         })
     }
 }
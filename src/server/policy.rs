@@ -0,0 +1,122 @@
+use ini::Ini;
+use rand::Rng;
+use std::time::Duration;
+
+const DEFAULT_CONNECT_TIMEOUT_MS: u64 = 5000;
+const DEFAULT_REQUEST_TIMEOUT_MS: u64 = 30000;
+const DEFAULT_DOWNLOAD_TIMEOUT_MS: u64 = 300000;
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_RETRY_DELAY_MS: u64 = 250;
+const MAX_RETRY_DELAY_MS: u64 = 5000;
+
+/// Timeouts and retries of `Client` requests. Only idempotent GET requests are retried.
+#[derive(Debug, Clone)]
+pub struct RequestPolicy {
+    pub connect_timeout: Duration,
+    /// Max duration of a whole request, response body included
+    pub request_timeout: Duration,
+    /// Max duration of a media download, which can be much longer than other requests
+    pub download_timeout: Duration,
+    /// Retries after first attempt (0 disable retries)
+    pub max_retries: u32,
+    /// Delay before first retry, doubled for each next retry
+    pub retry_delay: Duration,
+}
+
+impl Default for RequestPolicy {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_millis(DEFAULT_CONNECT_TIMEOUT_MS),
+            request_timeout: Duration::from_millis(DEFAULT_REQUEST_TIMEOUT_MS),
+            download_timeout: Duration::from_millis(DEFAULT_DOWNLOAD_TIMEOUT_MS),
+            max_retries: DEFAULT_MAX_RETRIES,
+            retry_delay: Duration::from_millis(DEFAULT_RETRY_DELAY_MS),
+        }
+    }
+}
+
+impl RequestPolicy {
+    /// Read `[network]` section, missing or invalid values keep their default value
+    pub fn from_conf(conf: &Ini) -> Self {
+        let value = |key: &str, default: u64| -> u64 {
+            match conf.get_from(Some("network"), key) {
+                Some(value) => value.parse::<u64>().unwrap_or_else(|_| {
+                    eprintln!("Invalid {} in network config: {}", key, value);
+                    default
+                }),
+                None => default,
+            }
+        };
+
+        Self {
+            connect_timeout: Duration::from_millis(value(
+                "connect_timeout_ms",
+                DEFAULT_CONNECT_TIMEOUT_MS,
+            )),
+            request_timeout: Duration::from_millis(value(
+                "request_timeout_ms",
+                DEFAULT_REQUEST_TIMEOUT_MS,
+            )),
+            download_timeout: Duration::from_millis(value(
+                "download_timeout_ms",
+                DEFAULT_DOWNLOAD_TIMEOUT_MS,
+            )),
+            max_retries: value("max_retries", DEFAULT_MAX_RETRIES as u64) as u32,
+            retry_delay: Duration::from_millis(value("retry_delay_ms", DEFAULT_RETRY_DELAY_MS)),
+        }
+    }
+
+    /// Delay to wait before given retry (starting at 1): exponential backoff with random
+    /// jitter, so clients don't retry all together after a server hiccup
+    pub fn retry_delay(&self, retry: u32) -> Duration {
+        let base_ms = self.retry_delay.as_millis() as u64;
+        let backoff_ms = base_ms
+            .saturating_mul(1 << retry.saturating_sub(1).min(16))
+            .min(MAX_RETRY_DELAY_MS.max(base_ms));
+        let jitter_ms = if base_ms > 0 {
+            rand::thread_rng().gen_range(0, base_ms)
+        } else {
+            0
+        };
+        Duration::from_millis(backoff_ms + jitter_ms)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RequestPolicy {
+            retry_delay: Duration::from_millis(100),
+            ..RequestPolicy::default()
+        };
+
+        for _ in 0..20 {
+            let first = policy.retry_delay(1).as_millis();
+            let third = policy.retry_delay(3).as_millis();
+            assert!((100..200).contains(&first));
+            assert!((400..500).contains(&third));
+            assert!(policy.retry_delay(30).as_millis() < 5100);
+        }
+    }
+
+    #[test]
+    fn test_from_conf() {
+        let conf = Ini::load_from_str(
+            "[network]\nconnect_timeout_ms = 1000\ndownload_timeout_ms = 60000\nmax_retries = abc\n",
+        )
+        .unwrap();
+
+        let policy = RequestPolicy::from_conf(&conf);
+
+        assert_eq!(Duration::from_millis(1000), policy.connect_timeout);
+        assert_eq!(DEFAULT_MAX_RETRIES, policy.max_retries);
+        assert_eq!(Duration::from_millis(60000), policy.download_timeout);
+        assert_eq!(
+            Duration::from_millis(DEFAULT_REQUEST_TIMEOUT_MS),
+            policy.request_timeout
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use crate::error::RollingError;
+use crate::server::ServerAddress;
+use ini::{Ini, Properties};
+
+pub const DEFAULT_PROFILE_ID: &str = "default";
+const PROFILE_SECTION_PREFIX: &str = "server.";
+
+/// A server the player can connect to, from config file. `[server]` section is the "default"
+/// profile and each `[server.<id>]` section is an additional one.
+#[derive(Debug, Clone)]
+pub struct ServerProfile {
+    pub id: String,
+    pub name: String,
+    pub address: ServerAddress,
+    pub releases_url: Option<String>,
+    pub home_image: Option<String>,
+    pub home_image_background: Option<String>,
+}
+
+impl ServerProfile {
+    fn from_section(id: &str, section: &Properties, conf: &Ini) -> Result<Self, RollingError> {
+        let section_name = if id == DEFAULT_PROFILE_ID {
+            "server".to_string()
+        } else {
+            format!("{}{}", PROFILE_SECTION_PREFIX, id)
+        };
+        let hostname = section.get("server_hostname").ok_or_else(|| {
+            RollingError::new(format!("Missing server_hostname in [{}]", section_name))
+        })?;
+        let port = section
+            .get("server_port")
+            .ok_or_else(|| RollingError::new(format!("Missing server_port in [{}]", section_name)))?
+            .parse::<u16>()
+            .map_err(|error| {
+                RollingError::new(format!(
+                    "Invalid server_port in [{}]: {}",
+                    section_name, error
+                ))
+            })?;
+        let address = match section.get("unsecure").unwrap_or("false") {
+            "true" | "True" | "1" => ServerAddress::unsecure(hostname, port),
+            _ => ServerAddress::new(hostname, port),
+        };
+        // Design images can be specific to server, else they come from [design] section
+        let design_value = |key: &str| -> Option<String> {
+            section
+                .get(key)
+                .or_else(|| conf.get_from(Some("design"), key))
+                .map(|value| value.to_string())
+        };
+
+        Ok(Self {
+            id: id.to_string(),
+            name: section.get("name").unwrap_or(hostname).to_string(),
+            address,
+            releases_url: section.get("releases_url").map(|value| value.to_string()),
+            home_image: design_value("home_image"),
+            home_image_background: design_value("home_image_background"),
+        })
+    }
+
+    /// Read all server profiles of config file, in config file order
+    pub fn load_all(conf: &Ini) -> Result<Vec<Self>, RollingError> {
+        let mut profiles = vec![];
+
+        for section_name in conf.sections().flatten() {
+            let id = if section_name == "server" {
+                DEFAULT_PROFILE_ID
+            } else if let Some(id) = section_name.strip_prefix(PROFILE_SECTION_PREFIX) {
+                id
+            } else {
+                continue;
+            };
+            // Section is known to exist because its name has just been listed
+            let section = conf.section(Some(section_name)).unwrap();
+            profiles.push(Self::from_section(id, section, conf)?);
+        }
+
+        if profiles.is_empty() {
+            return Err(RollingError::new(
+                "No server in config file (a [server] section is required)".to_string(),
+            ));
+        }
+        Ok(profiles)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_load_all() {
+        let conf = Ini::load_from_str(
+            "[server]\n\
+             server_hostname = 127.0.0.1\n\
+             server_port = 5000\n\
+             unsecure = true\n\
+             [design]\n\
+             home_image = intro.png\n\
+             [server.s2]\n\
+             name = S2\n\
+             server_hostname = s2.bux.fr\n\
+             server_port = 7431\n\
+             home_image = s2.png\n",
+        )
+        .unwrap();
+
+        let profiles = ServerProfile::load_all(&conf).unwrap();
+
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].id, DEFAULT_PROFILE_ID);
+        assert_eq!(profiles[0].name, "127.0.0.1");
+        assert!(!profiles[0].address.secure);
+        assert_eq!(profiles[0].home_image, Some("intro.png".to_string()));
+        assert_eq!(profiles[1].id, "s2");
+        assert_eq!(profiles[1].name, "S2");
+        assert_eq!(profiles[1].address.port, 7431);
+        assert!(profiles[1].address.secure);
+        assert_eq!(profiles[1].home_image, Some("s2.png".to_string()));
+    }
+
+    #[test]
+    fn test_load_all_invalid_port() {
+        let conf = Ini::load_from_str("[server]\nserver_hostname = 127.0.0.1\nserver_port = abc\n")
+            .unwrap();
+
+        assert!(ServerProfile::load_all(&conf).is_err());
+    }
+}
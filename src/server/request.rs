@@ -0,0 +1,119 @@
+use crate::server::client::ClientError;
+use crossbeam_channel::{bounded, unbounded, Receiver, Sender, TryRecvError};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+
+pub const REQUEST_WORKERS_COUNT: usize = 8;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Pool of threads executing blocking http requests out of the main (game) thread.
+/// Threads stop when the pool (and all its clones senders) are dropped.
+pub struct RequestPool {
+    sender: Sender<Job>,
+}
+
+impl RequestPool {
+    pub fn new(workers_count: usize) -> Self {
+        let (sender, receiver) = unbounded::<Job>();
+
+        for _ in 0..workers_count {
+            let receiver = receiver.clone();
+            thread::spawn(move || {
+                while let Ok(job) = receiver.recv() {
+                    job()
+                }
+            });
+        }
+
+        Self { sender }
+    }
+
+    pub fn execute<T, F>(&self, job: F) -> PendingRequest<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> Result<T, ClientError> + Send + 'static,
+    {
+        let (result_sender, result_receiver) = bounded(1);
+        let job: Job = Box::new(move || {
+            // A panicking job must not kill its worker
+            let result = panic::catch_unwind(AssertUnwindSafe(job)).unwrap_or_else(|payload| {
+                Err(ClientError::UnknownError {
+                    message: format!("Request failed: {}", panic_message(&payload)),
+                })
+            });
+            // Receiver is dropped when engine don't wait result anymore
+            let _ = result_sender.send(result);
+        });
+
+        if let Err(error) = self.sender.send(job) {
+            eprintln!("Error when send request to workers : {}", error)
+        }
+
+        PendingRequest {
+            receiver: result_receiver,
+        }
+    }
+}
+
+fn panic_message(payload: &Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Result of a request executed by `RequestPool`. Engines must poll it (with `try_take`) on each
+/// tick until result is available.
+pub struct PendingRequest<T> {
+    receiver: Receiver<Result<T, ClientError>>,
+}
+
+impl<T> PendingRequest<T> {
+    pub fn try_take(&self) -> Option<Result<T, ClientError>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(ClientError::UnknownError {
+                message: "Request worker stopped before giving response".to_string(),
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn wait<T>(pending: &PendingRequest<T>) -> Result<T, ClientError> {
+        let started_at = Instant::now();
+        loop {
+            if let Some(result) = pending.try_take() {
+                return result;
+            }
+            assert!(
+                started_at.elapsed() < Duration::from_secs(5),
+                "Timeout reached"
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_panicking_job() {
+        let pool = RequestPool::new(1);
+
+        let panicking: PendingRequest<()> = pool.execute(|| panic!("boom"));
+        match wait(&panicking) {
+            Err(ClientError::UnknownError { message }) => assert!(message.contains("boom")),
+            _ => panic!("Panic must be given as error"),
+        }
+        // Worker is still alive
+        assert_eq!(42, wait(&pool.execute(|| Ok(42))).unwrap());
+    }
+}
@@ -16,7 +16,7 @@ pub struct TileSheet {
     tile_height: i16,
 }
 
-const APPEARANCES: [(&str, SheetPositions); 153] = [
+const APPEARANCES: [(&str, SheetPositions); 154] = [
     ("UNKNOWN", [(0, 0), (0, 0), (0, 0), (0, 0), (0, 0), (0, 0)]),
     ("SEA", [(7, 0), (7, 0), (7, 0), (7, 0), (7, 0), (7, 0)]),
     ("JUNGLE", [(7, 4), (7, 4), (7, 4), (7, 4), (7, 4), (7, 4)]),
@@ -90,6 +90,10 @@ const APPEARANCES: [(&str, SheetPositions); 153] = [
         "CHARACTER",
         [(6, 0), (6, 0), (6, 0), (6, 0), (6, 0), (6, 0)],
     ),
+    (
+        "CHARACTER_LEFT",
+        [(6, 1), (6, 1), (6, 1), (6, 1), (6, 1), (6, 1)],
+    ),
     (
         "STUFF_GENERIC",
         [(3, 0), (3, 0), (3, 0), (3, 0), (3, 0), (3, 0)],
@@ -1,243 +1,838 @@
 extern crate websocket;
 
 use self::websocket::OwnedMessage;
-use mpsc::SendError;
+use mpsc::{RecvTimeoutError, SendError, TryRecvError};
 use serde_json::Value;
+use std::collections::VecDeque;
 use std::io::Error;
+use std::io::ErrorKind;
+use std::io::{Cursor, Read};
+use std::net::TcpStream;
 use std::sync::mpsc;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread;
 use std::thread::JoinHandle;
 use std::time::Duration;
-use std::time::SystemTime;
-use websocket::{ClientBuilder, Message, WebSocketError};
+use std::time::{Instant, SystemTime};
+use websocket::client::Url;
+use websocket::dataframe::{DataFrame, Opcode};
+use websocket::header::Headers;
+use websocket::sender::Sender;
+use websocket::stream::sync::{AsTcpStream, NetworkStream};
+use websocket::sync::Client;
+use websocket::ws::{Message as _, Sender as _};
+use websocket::{ClientBuilder, WebSocketError, WebSocketResult};
 
+use crate::error::RollingError;
+use crate::event::record::{EventDirection, EventRecorder, RecordedEvent};
+use crate::server::network::NetworkConfig;
 use crate::{event, util};
 
+const RECONNECT_FIRST_DELAY_MS: u64 = 500;
+const RECONNECT_MAX_DELAY_MS: u64 = 30_000;
+const RECONNECT_MAX_ATTEMPTS: u32 = 10;
+const CONNECT_TIMEOUT_MS: u64 = 10_000;
+const SENDER_POLL_INTERVAL_MS: u64 = 100;
+const CLOSE_TIMEOUT_S: u64 = 5;
+const PING_INTERVAL_MS: u64 = 5_000;
+// Longest wait of incoming bytes before session write pending events and pings
+const IO_POLL_INTERVAL_MS: u64 = 20;
+/// Websocket sub protocol offered to server. If server accept it, only one websocket is used to
+/// read and write events.
+pub const DUPLEX_PROTOCOL: &str = "rolling-duplex";
+const HEARTBEAT_TIMEOUT_MS: u64 = 15_000;
+// Oldest events sent while disconnected are dropped beyond this count
+const PENDING_EVENTS_MAX: usize = 50;
+const LATENCY_SAMPLES_COUNT: usize = 10;
+
+type BoxedStream = Box<dyn NetworkStream + Send>;
+type WebSocketClient = Client<BoxedStream>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    Reconnecting { attempt: u32 },
+    Lost,
+    Closed,
+}
+
+enum Connection {
+    // One websocket used to read and write (server accepted DUPLEX_PROTOCOL)
+    Duplex(WebSocketClient),
+    // One websocket to read and one to write
+    Split {
+        reader: WebSocketClient,
+        writer: WebSocketClient,
+    },
+}
+
+/// Received bytes kept until they make complete frames, so a read can stop anywhere in a frame
+struct FrameBuffer {
+    bytes: Vec<u8>,
+    // Frames of a fragmented message
+    fragments: Vec<DataFrame>,
+}
+
+impl FrameBuffer {
+    fn new(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes,
+            fragments: vec![],
+        }
+    }
+
+    /// Next complete message, None if more bytes are required
+    fn next_message(&mut self) -> WebSocketResult<Option<OwnedMessage>> {
+        loop {
+            let mut cursor = Cursor::new(&self.bytes[..]);
+            let frame = match DataFrame::read_dataframe(&mut cursor, false) {
+                Ok(frame) => frame,
+                // Unexpected end of bytes is reported as no data available
+                Err(WebSocketError::NoDataAvailable) => return Ok(None),
+                Err(error) => return Err(error),
+            };
+            let consumed = cursor.position() as usize;
+            self.bytes.drain(..consumed);
+
+            // Control frames can be interleaved with fragments
+            if frame.opcode as u8 >= 8 {
+                return OwnedMessage::from_dataframes(vec![frame]).map(Some);
+            }
+            if (frame.opcode == Opcode::Continuation) == self.fragments.is_empty() {
+                return Err(WebSocketError::ProtocolError(
+                    "Unexpected data frame opcode",
+                ));
+            }
+            let finished = frame.finished;
+            self.fragments.push(frame);
+            if finished {
+                let fragments = std::mem::take(&mut self.fragments);
+                return OwnedMessage::from_dataframes(fragments).map(Some);
+            }
+        }
+    }
+}
+
+/// Websocket stream owned by session thread, which alternates reads and writes. TLS stream is
+/// never read while blocked: session wait incoming bytes on TCP socket then read TLS stream
+/// without blocking until it has nothing left. Partial TLS records are kept by TLS stream and
+/// partial frames by `FrameBuffer`.
+struct WebSocketStream {
+    stream: BoxedStream,
+    // Clone of stream socket, used to wait incoming bytes and switch blocking mode
+    tcp_stream: TcpStream,
+    incoming: FrameBuffer,
+    eof: bool,
+}
+
+impl WebSocketStream {
+    fn new(ws: WebSocketClient) -> Result<Self, Error> {
+        let tcp_stream = ws.stream_ref().as_tcp().try_clone()?;
+        tcp_stream.set_read_timeout(Some(Duration::from_millis(IO_POLL_INTERVAL_MS)))?;
+        let (stream, buffer) = ws.into_stream();
+        // Bytes already read by client after handshake response
+        let buffered = buffer
+            .map(|(buffer, position, capacity)| buffer[position..capacity].to_vec())
+            .unwrap_or_default();
+
+        let mut ws = Self {
+            stream,
+            tcp_stream,
+            incoming: FrameBuffer::new(buffered),
+            eof: false,
+        };
+        // TLS stream can hold bytes received with handshake response
+        ws.read_available()?;
+        Ok(ws)
+    }
+
+    /// Received complete messages. Wait at most IO_POLL_INTERVAL_MS if there is none.
+    fn read_messages(&mut self) -> WebSocketResult<Vec<OwnedMessage>> {
+        let mut messages = self.buffered_messages()?;
+        if !messages.is_empty() {
+            return Ok(messages);
+        }
+        if self.eof {
+            return Err(WebSocketError::NoDataAvailable);
+        }
+
+        match self.tcp_stream.peek(&mut [0; 1]) {
+            Ok(_) => {}
+            Err(ref error)
+                if error.kind() == ErrorKind::WouldBlock || error.kind() == ErrorKind::TimedOut =>
+            {
+                return Ok(messages)
+            }
+            Err(error) => return Err(error.into()),
+        }
+
+        self.read_available()?;
+        messages.extend(self.buffered_messages()?);
+        Ok(messages)
+    }
+
+    fn read_available(&mut self) -> Result<(), Error> {
+        self.tcp_stream.set_nonblocking(true)?;
+        let read = self.read_until_would_block();
+        self.tcp_stream.set_nonblocking(false)?;
+        read
+    }
+
+    fn read_until_would_block(&mut self) -> Result<(), Error> {
+        let mut buffer = [0; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    self.eof = true;
+                    return Ok(());
+                }
+                Ok(read) => self.incoming.bytes.extend_from_slice(&buffer[..read]),
+                Err(ref error) if error.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(ref error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    fn buffered_messages(&mut self) -> WebSocketResult<Vec<OwnedMessage>> {
+        let mut messages = vec![];
+        while let Some(message) = self.incoming.next_message()? {
+            messages.push(message);
+        }
+        Ok(messages)
+    }
+}
+
+enum Incoming {
+    Continue,
+    // Server permitted close
+    Closed,
+    // Server closed websocket without client request (shutdown, kick, ...)
+    ClosedByServer,
+    Lost,
+}
+
+enum SessionEnd {
+    // Close asked by client or server: don't reconnect
+    Closed,
+    // Server closed websocket: don't reconnect but zone is lost
+    ClosedByServer,
+    Disconnected,
+}
+
+/// Keep event sent while disconnected, dropping oldest one if there is too much of them
+fn queue_pending(pending: &mut Vec<event::ZoneEvent>, event: event::ZoneEvent) {
+    if pending.len() >= PENDING_EVENTS_MAX {
+        let dropped = pending.remove(0);
+        eprintln!(
+            "WebSocket: too much events while disconnected, drop {}",
+            dropped.event_type_name
+        );
+    }
+    pending.push(event);
+}
+
+/// Drop events sent while disconnected which would be stale after reconnection: zone is then
+/// re-synchronized and player moves or actions must not be applied from an outdated position
+fn drop_stale_pending(pending: &mut Vec<event::ZoneEvent>) {
+    pending.retain(|event| {
+        !matches!(
+            event.event_type,
+            event::ZoneEventType::PlayerMove { .. }
+                | event::ZoneEventType::ClickActionEvent { .. }
+                | event::ZoneEventType::ClientRequireAround { .. }
+        )
+    });
+}
+
+/// Delay before reconnection attempt (starting at 1), doubled on each attempt
+fn reconnect_delay(attempt: u32) -> Duration {
+    let factor = 2_u64.saturating_pow(attempt.saturating_sub(1));
+    Duration::from_millis(
+        RECONNECT_FIRST_DELAY_MS
+            .saturating_mul(factor)
+            .min(RECONNECT_MAX_DELAY_MS),
+    )
+}
+
+/// Send pings on reader websocket and keep round trip times of answered ones. Connection is
+/// dead when no pong is received for HEARTBEAT_TIMEOUT_MS.
+struct Heartbeat {
+    ping_id: u64,
+    last_ping_at: Option<Instant>,
+    waiting_pong: Option<(u64, Instant)>,
+    last_pong_at: Instant,
+    samples: VecDeque<Duration>,
+}
+
+impl Heartbeat {
+    fn new() -> Self {
+        Self {
+            ping_id: 0,
+            last_ping_at: None,
+            waiting_pong: None,
+            last_pong_at: Instant::now(),
+            samples: VecDeque::new(),
+        }
+    }
+
+    fn ping_required(&self) -> bool {
+        match self.last_ping_at {
+            Some(last_ping_at) => last_ping_at.elapsed() >= Duration::from_millis(PING_INTERVAL_MS),
+            None => true,
+        }
+    }
+
+    /// Return payload of ping to send
+    fn ping(&mut self) -> Vec<u8> {
+        let now = Instant::now();
+        self.ping_id += 1;
+        self.last_ping_at = Some(now);
+        self.waiting_pong = Some((self.ping_id, now));
+        self.ping_id.to_be_bytes().to_vec()
+    }
+
+    /// Return measured round trip time if pong answer current ping
+    fn pong(&mut self, payload: &[u8]) -> Option<Duration> {
+        // Late pongs still prove connection is alive
+        self.last_pong_at = Instant::now();
+        let (ping_id, ping_at) = self.waiting_pong?;
+        if payload != ping_id.to_be_bytes() {
+            return None;
+        }
+
+        let round_trip = ping_at.elapsed();
+        self.waiting_pong = None;
+        self.samples.push_back(round_trip);
+        if self.samples.len() > LATENCY_SAMPLES_COUNT {
+            self.samples.pop_front();
+        }
+        Some(round_trip)
+    }
+
+    /// Average of last round trip times
+    fn latency(&self) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        Some(self.samples.iter().sum::<Duration>() / self.samples.len() as u32)
+    }
+
+    fn is_dead(&self) -> bool {
+        self.last_pong_at.elapsed() > Duration::from_millis(HEARTBEAT_TIMEOUT_MS)
+    }
+}
+
+fn set_state(state: &Arc<Mutex<ConnectionState>>, new_state: ConnectionState) {
+    *state.lock().unwrap() = new_state;
+}
+
 pub struct ZoneSocket {
     ws_address: String,
+    headers: Headers,
     from_main_sender: mpsc::Sender<event::ZoneEvent>,
     from_main_receiver: Arc<Mutex<mpsc::Receiver<event::ZoneEvent>>>,
     from_websocket_sender: Arc<Mutex<mpsc::Sender<event::ZoneEvent>>>,
     from_websocket_receiver: mpsc::Receiver<event::ZoneEvent>,
-    ws_reader_handle: Option<JoinHandle<()>>,
-    ws_reader_closed: Arc<Mutex<bool>>,
-    ws_sender_handle: Option<JoinHandle<()>>,
-    ws_sender_closed: Arc<Mutex<bool>>,
+    ws_handle: Option<JoinHandle<()>>,
+    state: Arc<Mutex<ConnectionState>>,
+    reconnected: Arc<Mutex<bool>>,
+    latency: Arc<Mutex<Option<Duration>>>,
     closing: bool,
+    recorder: Option<EventRecorder>,
+    network_config: NetworkConfig,
 }
 
 impl ZoneSocket {
-    pub fn new(ws_address: String) -> Self {
+    /// `headers` are sent with websocket handshake (e.g. authentication)
+    pub fn new(ws_address: String, headers: Headers) -> Self {
         let (from_main_sender, from_main_receiver) = mpsc::channel();
         let (from_websocket_sender, from_websocket_receiver) = mpsc::channel();
         let from_main_receiver = Arc::new(Mutex::new(from_main_receiver));
         let from_websocket_sender = Arc::new(Mutex::new(from_websocket_sender));
-        let ws_reader_closed = Arc::new(Mutex::new(false));
-        let ws_sender_closed = Arc::new(Mutex::new(false));
 
         Self {
             ws_address,
+            headers,
             from_main_sender,
             from_main_receiver,
-            ws_reader_handle: None,
-            ws_reader_closed,
-            ws_sender_handle: None,
-            ws_sender_closed,
             from_websocket_sender,
             from_websocket_receiver,
+            ws_handle: None,
+            state: Arc::new(Mutex::new(ConnectionState::Connecting)),
+            reconnected: Arc::new(Mutex::new(false)),
+            latency: Arc::new(Mutex::new(None)),
             closing: false,
+            recorder: None,
+            network_config: NetworkConfig::default(),
         }
     }
 
-    pub fn connect_unsecure(&mut self) {
-        let from_main_receiver = Arc::clone(&self.from_main_receiver);
-        let from_websocket_sender = Arc::clone(&self.from_websocket_sender);
-        let ws_reader_closed = Arc::clone(&self.ws_reader_closed);
-        let ws_sender_closed = Arc::clone(&self.ws_sender_closed);
+    /// Record all incoming and outgoing events with given recorder
+    pub fn set_recorder(&mut self, recorder: EventRecorder) {
+        self.recorder = Some(recorder);
+    }
 
-        let token = util::rand_string(32);
-        let (mut ws_reader, mut ws_writer) = (
-            ClientBuilder::new(&format!("{}&token={}", self.ws_address, token))
-                .unwrap()
-                .connect(None)
-                .unwrap(),
-            ClientBuilder::new(&format!("{}&reader_token={}", self.ws_address, token))
-                .unwrap()
-                .connect(None)
-                .unwrap(),
-        );
+    /// Connect through proxy and with TLS settings of given config (see `Client` one)
+    pub fn set_network_config(&mut self, network_config: NetworkConfig) {
+        self.network_config = network_config;
+    }
+
+    /// Build a socket which don't connect to server but produce incoming events of a
+    /// recording at their original pace multiplied by `speed`. Outgoing events are dropped.
+    pub fn replay(events: Vec<RecordedEvent>, speed: f32) -> Self {
+        let mut socket = Self::new(String::new(), Headers::new());
+        let from_main_receiver = Arc::clone(&socket.from_main_receiver);
+        let from_websocket_sender = Arc::clone(&socket.from_websocket_sender);
+        let state = Arc::clone(&socket.state);
+        let speed = if speed > 0.0 { speed } else { 1.0 };
+        set_state(&state, ConnectionState::Connected);
 
-        // ws reader
-        let ws_reader_handle = thread::spawn(move || {
+        socket.ws_handle = Some(thread::spawn(move || {
+            let started_at = Instant::now();
+            let mut events = events
+                .into_iter()
+                .filter(|recorded| recorded.direction == EventDirection::Incoming)
+                .peekable();
+            let from_main_receiver = from_main_receiver.lock().unwrap();
             let from_websocket_sender = from_websocket_sender.lock().unwrap();
-            for message in ws_reader.incoming_messages() {
-                match message {
-                    Ok(OwnedMessage::Text(msg)) => {
-                        let value: Value = serde_json::from_str(&msg).unwrap();
-                        match event::ZoneEvent::from_value(value) {
-                            Ok(event) => {
-                                let mut break_ = false;
-                                if let event::ZoneEventType::ServerPermitClose = event.event_type {
-                                    break_ = true;
-                                }
-
-                                if let Err(SendError(_e)) = from_websocket_sender.send(event) {
-                                    eprintln!("WebSocket(receiver): Something went wrong during process of received event");
-                                }
-
-                                if break_ {
-                                    println!("WebSocket(receiver): Receive close event");
-                                    break;
-                                }
-                            }
-                            Err(err) => println!("Error while decoding event: {}", err.message),
-                        }
-                    }
-                    Ok(OwnedMessage::Close(_)) => {
-                        println!("WebSocket(receiver): Close");
+
+            loop {
+                let mut wait = Duration::from_millis(SENDER_POLL_INTERVAL_MS);
+                while let Some(recorded) = events.peek() {
+                    let due = Duration::from_millis((recorded.elapsed_ms as f32 / speed) as u64);
+                    let elapsed = started_at.elapsed();
+                    if due > elapsed {
+                        wait = wait.min(due - elapsed);
                         break;
                     }
-                    Err(WebSocketError::NoDataAvailable) => {
-                        println!("WebSocket(receiver): WebSocketError: NoDataAvailable");
-                        break;
+                    let recorded = events.next().unwrap();
+                    if from_websocket_sender.send(recorded.event).is_err() {
+                        set_state(&state, ConnectionState::Closed);
+                        return;
+                    }
+                }
+
+                match from_main_receiver.recv_timeout(wait) {
+                    Ok(event) => {
+                        if let event::ZoneEventType::ClientWantClose = event.event_type {
+                            set_state(&state, ConnectionState::Closed);
+                            return;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => {
+                        set_state(&state, ConnectionState::Closed);
+                        return;
                     }
-                    _ => eprintln!(
-                        "WebSocket(receiver): Unknown websocket message received: {:?}",
-                        message
-                    ), // TODO add ping/pong (OwnedMessage::ping|pong)
                 }
             }
+        }));
 
-            let mut closed = ws_reader_closed.lock().unwrap();
-            *closed = true;
-            println!("WebSocket(receiver): Closing ...");
-        });
+        socket
+    }
+
+    /// Connect (and keep connected) websocket, with TLS if `tls`
+    pub fn connect(&mut self, tls: bool) {
+        let ws_address = self.ws_address.clone();
+        let headers = self.headers.clone();
+        let network_config = self.network_config.clone();
+        let from_main_receiver = Arc::clone(&self.from_main_receiver);
+        let from_websocket_sender = Arc::clone(&self.from_websocket_sender);
+        let state = Arc::clone(&self.state);
+        let reconnected = Arc::clone(&self.reconnected);
+        let latency = Arc::clone(&self.latency);
 
-        // ws sender
-        let ws_sender_handle = thread::spawn(move || {
+        self.ws_handle = Some(thread::spawn(move || {
             let from_main_receiver = from_main_receiver.lock().unwrap();
+            // Events sent by main while connection was down
+            let mut pending: Vec<event::ZoneEvent> = vec![];
+            let mut connected_once = false;
+            let mut attempt: u32 = 0;
 
-            for received in from_main_receiver.iter() {
-                let message_json_str = serde_json::to_string(&received).unwrap();
-                let message = Message::text(message_json_str);
-                ws_writer.send_message(&message).unwrap();
+            loop {
+                match Self::open(&ws_address, &headers, tls, &network_config) {
+                    Ok(connection) => {
+                        if connected_once {
+                            drop_stale_pending(&mut pending);
+                            *reconnected.lock().unwrap() = true;
+                        }
+                        connected_once = true;
+                        attempt = 0;
+                        set_state(&state, ConnectionState::Connected);
 
-                if let event::ZoneEventType::ClientWantClose = received.event_type {
-                    // Get out for loop (and finish thread)
-                    println!("WebSocket(sender): Closing ...");
+                        let from_websocket_sender = from_websocket_sender.lock().unwrap().clone();
+                        let (ws, events_ws) = match connection {
+                            Connection::Duplex(ws) => {
+                                println!("WebSocket: connected (duplex)");
+                                (ws, None)
+                            }
+                            Connection::Split { reader, writer } => {
+                                println!("WebSocket: connected (reader and writer)");
+                                (reader, Some(writer))
+                            }
+                        };
+                        let session_end = Self::run_session(
+                            ws,
+                            events_ws,
+                            &from_main_receiver,
+                            from_websocket_sender,
+                            &latency,
+                            &mut pending,
+                        );
+                        match session_end {
+                            SessionEnd::Closed => break,
+                            SessionEnd::ClosedByServer => {
+                                set_state(&state, ConnectionState::Lost);
+                                return;
+                            }
+                            SessionEnd::Disconnected => eprintln!("WebSocket: connection lost"),
+                        }
+                    }
+                    Err(error) => eprintln!("WebSocket: unable to connect: {}", error),
+                }
+
+                attempt += 1;
+                if attempt > RECONNECT_MAX_ATTEMPTS {
+                    eprintln!(
+                        "WebSocket: give up reconnection after {} attempts",
+                        attempt - 1
+                    );
+                    set_state(&state, ConnectionState::Lost);
+                    return;
+                }
+                set_state(&state, ConnectionState::Reconnecting { attempt });
+
+                let delay = reconnect_delay(attempt);
+                println!(
+                    "WebSocket: reconnect in {}ms (attempt {})",
+                    delay.as_millis(),
+                    attempt
+                );
+                // Wait before reconnect but stay aware of close requested by main
+                let start = SystemTime::now();
+                let mut close_requested = false;
+                while start.elapsed().unwrap_or(delay) < delay {
+                    match from_main_receiver
+                        .recv_timeout(Duration::from_millis(SENDER_POLL_INTERVAL_MS))
+                    {
+                        Ok(event) => {
+                            if let event::ZoneEventType::ClientWantClose = event.event_type {
+                                close_requested = true;
+                                break;
+                            }
+                            queue_pending(&mut pending, event);
+                        }
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => {
+                            close_requested = true;
+                            break;
+                        }
+                    }
+                }
+                if close_requested {
+                    println!("WebSocket: close requested while disconnected");
                     break;
                 }
             }
 
-            let mut closed = ws_sender_closed.lock().unwrap();
-            *closed = true;
-        });
-
-        self.ws_reader_handle = Some(ws_reader_handle);
-        self.ws_sender_handle = Some(ws_sender_handle);
+            set_state(&state, ConnectionState::Closed);
+            println!("WebSocket: closed");
+        }));
     }
 
-    // FIXME BS NOW: sur https ENTER n'ouvre plus le chat ...
-    // Explication: le serveur répond parfois directement sur le ws qui parle, or ici le ws_reader
-    // n'écoute plus les réponses ...
-    pub fn connect(&mut self) {
-        let from_main_receiver = Arc::clone(&self.from_main_receiver);
-        let from_websocket_sender = Arc::clone(&self.from_websocket_sender);
-        let ws_reader_closed = Arc::clone(&self.ws_reader_closed);
-        let ws_sender_closed = Arc::clone(&self.ws_sender_closed);
+    fn open(
+        ws_address: &str,
+        headers: &Headers,
+        tls: bool,
+        network_config: &NetworkConfig,
+    ) -> Result<Connection, RollingError> {
+        let mut url = Url::parse(ws_address)
+            .map_err(|error| RollingError::new(format!("Invalid address: {}", error)))?;
+        if url.set_scheme(if tls { "wss" } else { "ws" }).is_err() {
+            return Err(RollingError::new(format!(
+                "Invalid address scheme: {}",
+                url.scheme()
+            )));
+        }
 
         let token = util::rand_string(32);
-        let (mut ws_reader, mut ws_writer) = (
-            ClientBuilder::new(&format!("{}&token={}", self.ws_address, token))
-                .unwrap()
-                .connect(None)
-                .unwrap(),
-            ClientBuilder::new(&format!("{}&reader_token={}", self.ws_address, token))
-                .unwrap()
-                .connect(None)
-                .unwrap(),
-        );
+        let mut reader_url = url.clone();
+        reader_url.query_pairs_mut().append_pair("token", &token);
+        let reader = ClientBuilder::from_url(&reader_url)
+            .custom_headers(headers)
+            .add_protocol(DUPLEX_PROTOCOL)
+            .connect_on(Self::open_stream(&reader_url, tls, network_config)?)
+            .map_err(|error| RollingError::new(format!("{}", error)))?;
+        if reader
+            .protocols()
+            .iter()
+            .any(|protocol| protocol == DUPLEX_PROTOCOL)
+        {
+            return Ok(Connection::Duplex(reader));
+        }
 
-        // ws reader
-        let ws_reader_handle = thread::spawn(move || {
-            let from_websocket_sender = from_websocket_sender.lock().unwrap();
-            for message in ws_reader.incoming_messages() {
-                match message {
-                    Ok(OwnedMessage::Text(msg)) => {
-                        let value: Value = serde_json::from_str(&msg).unwrap();
-                        match event::ZoneEvent::from_value(value) {
-                            Ok(event) => {
-                                let mut break_ = false;
-                                if let event::ZoneEventType::ServerPermitClose = event.event_type {
-                                    break_ = true;
-                                }
-
-                                if let Err(SendError(_e)) = from_websocket_sender.send(event) {
-                                    eprintln!("WebSocket(receiver): Something went wrong during process of received event");
-                                }
-
-                                if break_ {
-                                    println!("WebSocket(receiver): Receive close event");
-                                    break;
-                                }
-                            }
-                            Err(err) => println!("Error while decoding event: {}", err.message),
-                        }
-                    }
-                    Ok(OwnedMessage::Close(_)) => {
-                        println!("WebSocket(receiver): Close");
-                        break;
+        // Server don't know duplex mode, events must be written on another websocket
+        let mut writer_url = url;
+        writer_url
+            .query_pairs_mut()
+            .append_pair("reader_token", &token);
+        let writer = ClientBuilder::from_url(&writer_url)
+            .custom_headers(headers)
+            .connect_on(Self::open_stream(&writer_url, tls, network_config)?)
+            .map_err(|error| RollingError::new(format!("{}", error)))?;
+        Ok(Connection::Split { reader, writer })
+    }
+
+    fn open_stream(
+        url: &Url,
+        tls: bool,
+        network_config: &NetworkConfig,
+    ) -> Result<Box<dyn NetworkStream + Send>, RollingError> {
+        let host = url
+            .host_str()
+            .ok_or_else(|| RollingError::new(format!("No host in address {}", url)))?;
+        let port = url
+            .port_or_known_default()
+            .unwrap_or(if tls { 443 } else { 80 });
+        network_config.connect(host, port, tls, Duration::from_millis(CONNECT_TIMEOUT_MS))
+    }
+
+    fn send_message(writer: &mut BoxedStream, message: &OwnedMessage) -> WebSocketResult<()> {
+        // Client frames must be masked
+        Sender::new(true).send_message(writer, message)
+    }
+
+    fn send_event(writer: &mut BoxedStream, event: &event::ZoneEvent) -> WebSocketResult<()> {
+        let message_json_str = serde_json::to_string(event).unwrap();
+        Self::send_message(writer, &OwnedMessage::Text(message_json_str))
+    }
+
+    /// Send ping if required. Return false if connection is dead.
+    fn keep_alive(writer: &mut BoxedStream, heartbeat: &mut Heartbeat) -> bool {
+        if heartbeat.is_dead() {
+            eprintln!(
+                "WebSocket: No pong received since {}ms, connection is dead",
+                HEARTBEAT_TIMEOUT_MS
+            );
+            return false;
+        }
+        if !heartbeat.ping_required() {
+            return true;
+        }
+
+        if let Err(error) = Self::send_message(writer, &OwnedMessage::Ping(heartbeat.ping())) {
+            eprintln!("WebSocket: Unable to send ping: {}", error);
+            return false;
+        }
+
+        true
+    }
+
+    fn proceed_incoming(
+        writer: &mut BoxedStream,
+        message: WebSocketResult<OwnedMessage>,
+        heartbeat: &mut Heartbeat,
+        from_websocket_sender: &mpsc::Sender<event::ZoneEvent>,
+        latency: &Arc<Mutex<Option<Duration>>>,
+    ) -> Incoming {
+        match message {
+            Ok(OwnedMessage::Text(msg)) => {
+                let value: Value = match serde_json::from_str(&msg) {
+                    Ok(value) => value,
+                    Err(error) => {
+                        eprintln!("WebSocket(receiver): Invalid json received: {}", error);
+                        return Incoming::Continue;
                     }
-                    Err(WebSocketError::NoDataAvailable) => {
-                        println!("WebSocket(receiver): WebSocketError: NoDataAvailable");
-                        break;
+                };
+                match event::ZoneEvent::from_value(value) {
+                    Ok(event) => {
+                        let mut close_permitted = false;
+                        if let event::ZoneEventType::ServerPermitClose = event.event_type {
+                            close_permitted = true;
+                        }
+
+                        if let Err(SendError(_e)) = from_websocket_sender.send(event) {
+                            eprintln!("WebSocket(receiver): Something went wrong during process of received event");
+                        }
+
+                        if close_permitted {
+                            println!("WebSocket(receiver): Receive close event");
+                            return Incoming::Closed;
+                        }
                     }
-                    _ => eprintln!(
-                        "WebSocket(receiver): Unknown websocket message received: {:?}",
-                        message
-                    ), // TODO add ping/pong (OwnedMessage::ping|pong)
+                    Err(err) => println!("Error while decoding event: {}", err.message),
+                }
+            }
+            Ok(OwnedMessage::Ping(payload)) => {
+                if let Err(error) = Self::send_message(writer, &OwnedMessage::Pong(payload)) {
+                    eprintln!("WebSocket(receiver): Unable to send pong: {}", error);
+                    return Incoming::Lost;
+                }
+            }
+            Ok(OwnedMessage::Pong(payload)) => {
+                if heartbeat.pong(&payload).is_some() {
+                    *latency.lock().unwrap() = heartbeat.latency();
+                }
+            }
+            Ok(OwnedMessage::Close(close_data)) => {
+                match &close_data {
+                    Some(close_data) => println!(
+                        "WebSocket(receiver): Closed by server (code {}: {})",
+                        close_data.status_code, close_data.reason
+                    ),
+                    None => println!("WebSocket(receiver): Closed by server"),
                 }
+                // Closing handshake: answer with same status
+                if let Err(error) = Self::send_message(writer, &OwnedMessage::Close(close_data)) {
+                    eprintln!("WebSocket(receiver): Unable to answer close: {}", error);
+                }
+                return Incoming::ClosedByServer;
+            }
+            Err(WebSocketError::NoDataAvailable) => {
+                println!("WebSocket(receiver): WebSocketError: NoDataAvailable");
+                return Incoming::Lost;
             }
+            Err(error) => {
+                eprintln!("WebSocket(receiver): WebSocketError: {}", error);
+                return Incoming::Lost;
+            }
+            _ => eprintln!(
+                "WebSocket(receiver): Unknown websocket message received: {:?}",
+                message
+            ),
+        }
 
-            let mut closed = ws_reader_closed.lock().unwrap();
-            *closed = true;
-            println!("WebSocket(receiver): Closing ...");
-        });
+        Incoming::Continue
+    }
 
-        // ws sender
-        let ws_sender_handle = thread::spawn(move || {
-            let from_main_receiver = from_main_receiver.lock().unwrap();
+    /// Read given websocket and write events and pings on it (or on `events_ws` if given, when
+    /// server don't accept duplex mode) until connection is closed or lost. Everything is done
+    /// in this thread, so writes never wait a read to finish.
+    fn run_session(
+        ws: WebSocketClient,
+        events_ws: Option<WebSocketClient>,
+        from_main_receiver: &mpsc::Receiver<event::ZoneEvent>,
+        from_websocket_sender: mpsc::Sender<event::ZoneEvent>,
+        latency: &Arc<Mutex<Option<Duration>>>,
+        pending: &mut Vec<event::ZoneEvent>,
+    ) -> SessionEnd {
+        let mut ws = match WebSocketStream::new(ws) {
+            Ok(ws) => ws,
+            Err(error) => {
+                eprintln!("WebSocket: Unable to prepare connection: {}", error);
+                return SessionEnd::Disconnected;
+            }
+        };
+        let mut events_stream = events_ws.map(|events_ws| events_ws.into_stream().0);
+        let mut heartbeat = Heartbeat::new();
+        let mut close_requested_at: Option<SystemTime> = None;
 
-            for received in from_main_receiver.iter() {
-                let message_json_str = serde_json::to_string(&received).unwrap();
-                let message = Message::text(message_json_str);
-                ws_writer.send_message(&message).unwrap();
+        let incoming = 'session: loop {
+            let messages = match ws.read_messages() {
+                Ok(messages) => messages.into_iter().map(Ok).collect(),
+                Err(error) => vec![Err(error)],
+            };
+            for message in messages {
+                match Self::proceed_incoming(
+                    &mut ws.stream,
+                    message,
+                    &mut heartbeat,
+                    &from_websocket_sender,
+                    latency,
+                ) {
+                    Incoming::Continue => {}
+                    incoming => break 'session incoming,
+                }
+            }
 
-                if let event::ZoneEventType::ClientWantClose = received.event_type {
-                    // Get out for loop (and finish thread)
-                    println!("WebSocket(sender): Closing ...");
-                    break;
+            if let Some(close_requested_at) = close_requested_at {
+                // Wait server permit close
+                if close_requested_at.elapsed().unwrap_or_default()
+                    > Duration::from_secs(CLOSE_TIMEOUT_S)
+                {
+                    eprintln!("WebSocket: timeout reached while waiting close permission");
+                    break Incoming::Lost;
+                }
+            } else {
+                loop {
+                    let received = if !pending.is_empty() {
+                        pending.remove(0)
+                    } else {
+                        match from_main_receiver.try_recv() {
+                            Ok(received) => received,
+                            Err(TryRecvError::Empty) => break,
+                            Err(TryRecvError::Disconnected) => {
+                                close_requested_at = Some(SystemTime::now());
+                                break;
+                            }
+                        }
+                    };
+
+                    let events_writer = events_stream.as_mut().unwrap_or(&mut ws.stream);
+                    if let Err(error) = Self::send_event(events_writer, &received) {
+                        eprintln!("WebSocket: Unable to send message: {}", error);
+                        pending.insert(0, received);
+                        break 'session Incoming::Lost;
+                    }
+                    if let event::ZoneEventType::ClientWantClose = received.event_type {
+                        println!("WebSocket: Closing ...");
+                        close_requested_at = Some(SystemTime::now());
+                        break;
+                    }
                 }
             }
 
-            let mut closed = ws_sender_closed.lock().unwrap();
-            *closed = true;
-        });
+            if !Self::keep_alive(&mut ws.stream, &mut heartbeat) {
+                break Incoming::Lost;
+            }
+        };
+
+        *latency.lock().unwrap() = None;
+        match incoming {
+            Incoming::Closed => SessionEnd::Closed,
+            _ if close_requested_at.is_some() => SessionEnd::Closed,
+            Incoming::ClosedByServer => SessionEnd::ClosedByServer,
+            _ => SessionEnd::Disconnected,
+        }
+    }
 
-        self.ws_reader_handle = Some(ws_reader_handle);
-        self.ws_sender_handle = Some(ws_sender_handle);
+    pub fn state(&self) -> ConnectionState {
+        *self.state.lock().unwrap()
+    }
+
+    /// Average round trip time of last pings, None if not measured yet or disconnected
+    pub fn latency(&self) -> Option<Duration> {
+        *self.latency.lock().unwrap()
+    }
+
+    /// Return true (once) if connection has been established again after it was lost. Zone
+    /// must then be re-synchronized.
+    pub fn take_reconnected(&self) -> bool {
+        let mut reconnected = self.reconnected.lock().unwrap();
+        let value = *reconnected;
+        *reconnected = false;
+        value
     }
 
     pub fn send(&self, event: event::ZoneEvent) {
-        self.from_main_sender.send(event).unwrap();
+        if let Some(recorder) = &self.recorder {
+            recorder.record(EventDirection::Outgoing, &event);
+        }
+        if let Err(error) = self.from_main_sender.send(event) {
+            eprintln!("WebSocket: unable to send event: {}", error);
+        }
     }
 
     pub fn pending_events(&self) -> Vec<event::ZoneEvent> {
         let mut events: Vec<event::ZoneEvent> = Vec::new();
         let iter = &mut self.from_websocket_receiver.try_iter();
         while let Some(val) = iter.next() {
+            if let Some(recorder) = &self.recorder {
+                recorder.record(EventDirection::Incoming, &val);
+            }
             events.push(val);
         }
         events
@@ -246,34 +841,280 @@ impl ZoneSocket {
     pub fn close(&mut self) -> Result<(), Error> {
         println!("DEBUG :: CLOSE WS");
         self.closing = true;
+        if self.state() == ConnectionState::Lost {
+            return Ok(());
+        }
         self.send(event::ZoneEvent {
             event_type: event::ZoneEventType::ClientWantClose,
             event_type_name: String::from(event::CLIENT_WANT_CLOSE),
         });
 
-        // FIXME: make a loop to look at booleans and exit when ok or when timeout
         let start = SystemTime::now();
-        let timeout = Duration::from_secs(5);
+        let timeout = Duration::from_secs(CLOSE_TIMEOUT_S + 1);
         loop {
-            println!("DEBUG :: CHECKE ENDED ...");
-            let ws_sender_closed = *self.ws_sender_closed.try_lock().unwrap();
-            let ws_reader_closed = *self.ws_reader_closed.try_lock().unwrap();
-
-            if ws_sender_closed && ws_reader_closed {
+            let state = self.state();
+            if state == ConnectionState::Closed || state == ConnectionState::Lost {
                 break;
             }
 
             if start.elapsed().unwrap() > timeout {
                 eprintln!(
-                    "WebSockets: timeout reached, force closing (ws_sender_closed: {}, ws_reader_closed: {})",
-                    ws_sender_closed, ws_reader_closed
+                    "WebSockets: timeout reached, force closing (state: {:?})",
+                    state
                 );
                 break;
             }
             thread::sleep(Duration::from_millis(100));
         }
 
-        println!("DEBUG :: BOTH WS CLOSED");
+        println!("DEBUG :: WS CLOSED");
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ini::Ini;
+    use websocket::ws::dataframe::DataFrame as _;
+
+    fn exit_event() -> event::ZoneEvent {
+        event::ZoneEvent {
+            event_type_name: event::CHARACTER_EXIT_ZONE.to_string(),
+            event_type: event::ZoneEventType::CharacterExit {
+                character_id: "bob".to_string(),
+            },
+        }
+    }
+
+    fn move_event() -> event::ZoneEvent {
+        event::ZoneEvent {
+            event_type_name: event::PLAYER_MOVE.to_string(),
+            event_type: event::ZoneEventType::PlayerMove {
+                to_row_i: 2,
+                to_col_i: 4,
+                character_id: "abc".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_replay() {
+        let recorded = |elapsed_ms, direction, event| RecordedEvent {
+            elapsed_ms,
+            timestamp_ms: 0,
+            direction,
+            event,
+        };
+        let mut socket = ZoneSocket::replay(
+            vec![
+                recorded(0, EventDirection::Incoming, exit_event()),
+                recorded(0, EventDirection::Outgoing, move_event()),
+                recorded(600, EventDirection::Incoming, exit_event()),
+            ],
+            2.0,
+        );
+
+        // Second incoming event is due after 300ms
+        thread::sleep(Duration::from_millis(100));
+        let first_events = socket.pending_events();
+        socket.send(move_event());
+        thread::sleep(Duration::from_millis(400));
+        let second_events = socket.pending_events();
+        socket.close().unwrap();
+
+        assert_eq!(1, first_events.len());
+        assert_eq!(event::CHARACTER_EXIT_ZONE, first_events[0].event_type_name);
+        assert_eq!(1, second_events.len());
+        assert_eq!(event::CHARACTER_EXIT_ZONE, second_events[0].event_type_name);
+        assert_eq!(ConnectionState::Closed, socket.state());
+    }
+
+    #[test]
+    fn test_pending_events() {
+        let mut pending = vec![];
+        queue_pending(&mut pending, move_event());
+        for _ in 0..PENDING_EVENTS_MAX {
+            queue_pending(&mut pending, exit_event());
+        }
+        assert_eq!(PENDING_EVENTS_MAX, pending.len());
+        assert_eq!(event::CHARACTER_EXIT_ZONE, pending[0].event_type_name);
+
+        pending.insert(0, move_event());
+        drop_stale_pending(&mut pending);
+        assert_eq!(PENDING_EVENTS_MAX, pending.len());
+        assert!(pending
+            .iter()
+            .all(|event| event.event_type_name == event::CHARACTER_EXIT_ZONE));
+    }
+
+    #[test]
+    fn test_closed_by_server() {
+        let mut server = websocket::sync::Server::bind("127.0.0.1:0").unwrap();
+        let port = server.local_addr().unwrap().port();
+        let mut socket = ZoneSocket::new(
+            format!("http://127.0.0.1:{}/ws/zones/1/1/events", port),
+            Headers::new(),
+        );
+
+        socket.connect(false);
+        let mut client = server
+            .accept()
+            .ok()
+            .unwrap()
+            .use_protocol(DUPLEX_PROTOCOL)
+            .accept()
+            .ok()
+            .unwrap();
+        client
+            .send_message(&OwnedMessage::Close(Some(websocket::CloseData::new(
+                1008,
+                "Kicked".to_string(),
+            ))))
+            .unwrap();
+        // Client answers to close
+        let answer = loop {
+            match client.recv_message().unwrap() {
+                OwnedMessage::Ping(_) => {}
+                message => break message,
+            }
+        };
+        let started_at = Instant::now();
+        while socket.state() != ConnectionState::Lost
+            && started_at.elapsed() < Duration::from_secs(5)
+        {
+            thread::sleep(Duration::from_millis(10));
+        }
+        // And don't reconnect
+        server.set_nonblocking(true).unwrap();
+        thread::sleep(Duration::from_millis(RECONNECT_FIRST_DELAY_MS * 2));
+
+        assert!(matches!(answer, OwnedMessage::Close(Some(ref data)) if data.status_code == 1008));
+        assert_eq!(ConnectionState::Lost, socket.state());
+        assert!(server.accept().is_err());
+    }
+
+    #[test]
+    fn test_frame_buffer() {
+        let mut bytes = vec![];
+        for frame in &[
+            DataFrame::new(false, Opcode::Text, b"hel".to_vec()),
+            DataFrame::new(true, Opcode::Ping, b"1".to_vec()),
+            DataFrame::new(true, Opcode::Continuation, b"lo".to_vec()),
+        ] {
+            frame.write_to(&mut bytes, false).unwrap();
+        }
+        let mut frame_buffer = FrameBuffer::new(vec![]);
+        let mut messages = vec![];
+
+        // Bytes are received one by one
+        for byte in bytes {
+            frame_buffer.bytes.push(byte);
+            while let Some(message) = frame_buffer.next_message().unwrap() {
+                messages.push(message);
+            }
+        }
+
+        assert_eq!(
+            vec![
+                OwnedMessage::Ping(b"1".to_vec()),
+                OwnedMessage::Text("hello".to_string())
+            ],
+            messages
+        );
+        assert!(frame_buffer.bytes.is_empty());
+    }
+
+    #[test]
+    fn test_tls_duplex_session() {
+        let identity = native_tls::Identity::from_pkcs12(
+            include_bytes!("../tests/fixtures/tls/server.p12"),
+            "rollgui",
+        )
+        .unwrap();
+        let acceptor = native_tls::TlsAcceptor::new(identity).unwrap();
+        let mut server = websocket::sync::Server::bind_secure("127.0.0.1:0", acceptor).unwrap();
+        let port = server.local_addr().unwrap().port();
+        let server_handle = thread::spawn(move || {
+            let mut client = server
+                .accept()
+                .ok()
+                .unwrap()
+                .use_protocol(DUPLEX_PROTOCOL)
+                .accept()
+                .ok()
+                .unwrap();
+            // Client writes while these events are read
+            for _ in 0..100 {
+                let text = serde_json::to_string(&exit_event()).unwrap();
+                client.send_message(&OwnedMessage::Text(text)).unwrap();
+            }
+
+            let mut received = vec![];
+            loop {
+                match client.recv_message().unwrap() {
+                    OwnedMessage::Text(text) => {
+                        let event: event::ZoneEvent = serde_json::from_str(&text).unwrap();
+                        if event.event_type_name == event::CLIENT_WANT_CLOSE {
+                            let permit_close = event::ZoneEvent {
+                                event_type_name: event::SERVER_PERMIT_CLOSE.to_string(),
+                                event_type: event::ZoneEventType::ServerPermitClose,
+                            };
+                            let text = serde_json::to_string(&permit_close).unwrap();
+                            client.send_message(&OwnedMessage::Text(text)).unwrap();
+                            return received;
+                        }
+                        received.push(event.event_type_name);
+                    }
+                    OwnedMessage::Ping(payload) => {
+                        client.send_message(&OwnedMessage::Pong(payload)).unwrap()
+                    }
+                    _ => {}
+                }
+            }
+        });
+        let conf =
+            Ini::load_from_str("[network]\nca_bundle = tests/fixtures/tls/ca.pem\n").unwrap();
+        let mut socket = ZoneSocket::new(
+            format!("https://localhost:{}/ws/zones/1/1/events", port),
+            Headers::new(),
+        );
+        socket.set_network_config(NetworkConfig::from_conf(&conf).unwrap());
+
+        socket.connect(true);
+        for _ in 0..3 {
+            socket.send(exit_event());
+        }
+        let started_at = Instant::now();
+        let mut events = vec![];
+        while events.len() < 100 && started_at.elapsed() < Duration::from_secs(10) {
+            events.extend(socket.pending_events());
+            thread::sleep(Duration::from_millis(10));
+        }
+        socket.close().unwrap();
+
+        assert_eq!(100, events.len());
+        assert_eq!(ConnectionState::Closed, socket.state());
+        assert_eq!(
+            vec![event::CHARACTER_EXIT_ZONE.to_string(); 3],
+            server_handle.join().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_heartbeat() {
+        let mut heartbeat = Heartbeat::new();
+        assert!(heartbeat.ping_required());
+        let payload = heartbeat.ping();
+        assert!(!heartbeat.ping_required());
+
+        heartbeat.last_pong_at = Instant::now() - Duration::from_millis(HEARTBEAT_TIMEOUT_MS + 1);
+        assert!(heartbeat.is_dead());
+
+        // Only pong of current ping is measured, but any pong keep connection alive
+        assert_eq!(None, heartbeat.pong(&[0]));
+        assert!(!heartbeat.is_dead());
+        assert!(heartbeat.pong(&payload).is_some());
+        assert!(heartbeat.latency().is_some());
+    }
+}
@@ -0,0 +1,528 @@
+use crate::entity::build::Build;
+use crate::entity::character::Character;
+use crate::entity::corpse::AnimatedCorpse;
+use crate::entity::resource::Resource;
+use crate::entity::stuff::Stuff;
+use crate::event::{ZoneEvent, ZoneEventType};
+use crate::level::Level;
+use crate::tile::zone::Tiles;
+use crate::tile::TransportType;
+use pathfinding::prelude::{absdiff, astar};
+use std::collections::HashMap;
+
+/// Pathfinding cost of a move to a side tile. Diagonal moves cost more (about sqrt(2)) so
+/// straight paths are preferred.
+const STRAIGHT_MOVE_COST: u32 = 10;
+const DIAGONAL_MOVE_COST: u32 = 14;
+
+/// Result of `ZoneState::apply`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Applied {
+    /// Event concerned zone state and has been applied
+    Changed,
+    /// Player left zone on server side: zone must be reloaded
+    PlayerLeft,
+    /// Event don't concern zone state (chat, top bar, ...)
+    Ignored,
+}
+
+/// Zone entities found on a tile
+#[derive(Debug, Default)]
+pub struct TileEntities<'a> {
+    pub characters: Vec<&'a Character>,
+    pub stuffs: Vec<&'a Stuff>,
+    pub resources: Vec<&'a Resource>,
+    pub builds: Vec<&'a Build>,
+    pub animated_corpses: Vec<&'a AnimatedCorpse>,
+}
+
+impl TileEntities<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.characters.is_empty()
+            && self.stuffs.is_empty()
+            && self.resources.is_empty()
+            && self.builds.is_empty()
+            && self.animated_corpses.is_empty()
+    }
+}
+
+/// Zone world state (level and entities), updated by zone events. It doesn't know about
+/// rendering or inputs.
+#[derive(Debug)]
+pub struct ZoneState {
+    player_id: String,
+    pub tiles: Tiles,
+    pub level: Level,
+    pub characters: HashMap<String, Character>,
+    pub stuffs: HashMap<String, Stuff>,
+    pub resources: Vec<Resource>,
+    pub builds: HashMap<i32, Build>,
+    pub animated_corpses: HashMap<i32, AnimatedCorpse>,
+    builds_positions: HashMap<(i16, i16), Vec<i32>>,
+}
+
+impl ZoneState {
+    /// Zone without entities, see `with_*` methods to add them
+    pub fn new(player_id: String, tiles: Tiles, level: Level) -> Self {
+        Self {
+            player_id,
+            tiles,
+            level,
+            characters: HashMap::new(),
+            stuffs: HashMap::new(),
+            resources: vec![],
+            builds: HashMap::new(),
+            animated_corpses: HashMap::new(),
+            builds_positions: HashMap::new(),
+        }
+    }
+
+    pub fn with_characters(mut self, characters: HashMap<String, Character>) -> Self {
+        self.characters = characters;
+        self
+    }
+
+    pub fn with_stuffs(mut self, stuffs: HashMap<String, Stuff>) -> Self {
+        self.stuffs = stuffs;
+        self
+    }
+
+    pub fn with_resources(mut self, resources: Vec<Resource>) -> Self {
+        self.resources = resources;
+        self
+    }
+
+    pub fn with_builds(mut self, builds: HashMap<i32, Build>) -> Self {
+        self.builds = builds;
+        self.update_builds_positions();
+        self
+    }
+
+    pub fn with_animated_corpses(mut self, animated_corpses: HashMap<i32, AnimatedCorpse>) -> Self {
+        self.animated_corpses = animated_corpses;
+        self
+    }
+
+    fn update_builds_positions(&mut self) {
+        self.builds_positions = HashMap::new();
+        for build in self.builds.values() {
+            self.builds_positions
+                .entry((build.row_i as i16, build.col_i as i16))
+                .or_default()
+                .push(build.id);
+        }
+    }
+
+    /// Replace entities which can change without being notified while disconnected
+    pub fn replace_entities(
+        &mut self,
+        characters: Vec<Character>,
+        stuffs: Vec<Stuff>,
+        builds: Vec<Build>,
+    ) {
+        self.characters = characters
+            .into_iter()
+            .map(|character| (character.id.clone(), character))
+            .collect();
+        self.stuffs = stuffs
+            .into_iter()
+            .map(|stuff| (stuff.id.to_string(), stuff))
+            .collect();
+        self.builds = builds.into_iter().map(|build| (build.id, build)).collect();
+        self.update_builds_positions();
+    }
+
+    pub fn apply(&mut self, event: &ZoneEvent) -> Applied {
+        match &event.event_type {
+            ZoneEventType::PlayerMove {
+                to_row_i,
+                to_col_i,
+                character_id,
+            } => {
+                if let Some(moved_character) = self.characters.get_mut(character_id.as_str()) {
+                    moved_character.zone_row_i = *to_row_i;
+                    moved_character.zone_col_i = *to_col_i;
+                } else if character_id != &self.player_id {
+                    eprintln!("Unknown character {} for move", character_id)
+                }
+            }
+            ZoneEventType::CharacterEnter {
+                zone_row_i,
+                zone_col_i,
+                character_id,
+            } => {
+                println!("{} is enter in zone", character_id);
+                self.characters.insert(
+                    character_id.clone(),
+                    Character {
+                        id: character_id.clone(),
+                        zone_row_i: *zone_row_i,
+                        zone_col_i: *zone_col_i,
+                        // FIXME BS NOW : avatar_uuid & avatar_is_validated,
+                        avatar_uuid: None,
+                        avatar_is_validated: false,
+                    },
+                );
+            }
+            ZoneEventType::CharacterExit { character_id } => {
+                if self.characters.remove(character_id).is_some() {
+                    println!("{} exit from zone", character_id);
+                } else if character_id == &self.player_id {
+                    return Applied::PlayerLeft;
+                } else {
+                    println!(
+                        "{} left zone but was not in list of characters",
+                        character_id
+                    );
+                }
+            }
+            ZoneEventType::NewBuild { build } => {
+                self.builds.insert(build.id, build.clone());
+                self.update_builds_positions();
+            }
+            ZoneEventType::ZoneTileReplace {
+                row_i,
+                col_i,
+                new_tile_id,
+            } => {
+                println!("Replace tile at {}:{} with {}", row_i, col_i, new_tile_id);
+                match self
+                    .level
+                    .rows
+                    .get_mut(*row_i as usize)
+                    .and_then(|row| row.cols.get_mut(*col_i as usize))
+                {
+                    Some(tile_id) => *tile_id = new_tile_id.clone(),
+                    None => eprintln!("Tile {}:{} to replace is outside zone", row_i, col_i),
+                }
+            }
+            ZoneEventType::AnimatedCorpseMove {
+                to_row_i,
+                to_col_i,
+                animated_corpse_id,
+            } => {
+                if let Some(moved_animated_corpse) =
+                    self.animated_corpses.get_mut(animated_corpse_id)
+                {
+                    moved_animated_corpse.zone_row_i = *to_row_i;
+                    moved_animated_corpse.zone_col_i = *to_col_i;
+                } else {
+                    eprintln!("Unknown animated corpse {} for move", animated_corpse_id)
+                }
+            }
+            ZoneEventType::ZoneGroundResourceRemoved {
+                row_i,
+                col_i,
+                resource_id,
+            } => self.resources.retain(|resource| {
+                !(resource.zone_row_i == *row_i
+                    && resource.zone_col_i == *col_i
+                    && &resource.id == resource_id)
+            }),
+            ZoneEventType::ZoneGroundStuffRemoved { stuff_id } => {
+                self.stuffs.retain(|_, stuff| stuff.id != *stuff_id);
+            }
+            ZoneEventType::ZoneGroundResourceAdded {
+                row_i,
+                col_i,
+                resource_id,
+            } => {
+                self.resources.push(Resource {
+                    id: resource_id.clone(),
+                    zone_row_i: *row_i,
+                    zone_col_i: *col_i,
+                });
+            }
+            ZoneEventType::ZoneGroundStuffAdded {
+                id_,
+                stuff_id,
+                zone_row_i,
+                zone_col_i,
+                classes,
+            } => {
+                self.stuffs.insert(
+                    id_.to_string(),
+                    Stuff {
+                        id: *id_,
+                        stuff_id: stuff_id.clone(),
+                        zone_row_i: *zone_row_i,
+                        zone_col_i: *zone_col_i,
+                        classes: classes.clone(),
+                    },
+                );
+            }
+            _ => return Applied::Ignored,
+        }
+
+        Applied::Changed
+    }
+
+    pub fn entities_at(&self, row_i: i16, col_i: i16) -> TileEntities<'_> {
+        let position = (row_i as i32, col_i as i32);
+        TileEntities {
+            characters: self
+                .characters
+                .values()
+                .filter(|character| character.position() == position)
+                .collect(),
+            stuffs: self
+                .stuffs
+                .values()
+                .filter(|stuff| stuff.position() == position)
+                .collect(),
+            resources: self
+                .resources
+                .iter()
+                .filter(|resource| resource.position() == position)
+                .collect(),
+            builds: self.builds_at(row_i, col_i),
+            animated_corpses: self
+                .animated_corpses
+                .values()
+                .filter(|animated_corpse| animated_corpse.position() == position)
+                .collect(),
+        }
+    }
+
+    pub fn has_build_at(&self, row_i: i16, col_i: i16) -> bool {
+        self.builds_positions.contains_key(&(row_i, col_i))
+    }
+
+    pub fn builds_at(&self, row_i: i16, col_i: i16) -> Vec<&Build> {
+        match self.builds_positions.get(&(row_i, col_i)) {
+            Some(build_ids) => build_ids
+                .iter()
+                .filter_map(|build_id| self.builds.get(build_id))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// Tile can be traversed with given transport type and no build block it. Builds without
+    /// traversability information don't block.
+    pub fn is_traversable(&self, row_i: i16, col_i: i16, transport_type: TransportType) -> bool {
+        self.tiles
+            .traversable(&self.level.tile_id(row_i, col_i), transport_type)
+            && self.builds_at(row_i, col_i).iter().all(|build| {
+                match build.traversable.get(transport_type.as_str()) {
+                    Some(traversable) => *traversable,
+                    None => build.traversable.is_empty(),
+                }
+            })
+    }
+
+    /// Reachable side tiles with their cost. Diagonal moves are not permitted between two
+    /// blocked tiles corners (e.g. through a wall corner).
+    pub fn successors(
+        &self,
+        position: (i16, i16),
+        transport_type: TransportType,
+    ) -> Vec<((i16, i16), u32)> {
+        let (row_i, col_i) = position;
+        let mut successors = vec![];
+
+        for modifier_row_i in -1..=1 {
+            for modifier_col_i in -1..=1 {
+                if modifier_row_i == 0 && modifier_col_i == 0 {
+                    continue;
+                }
+
+                let next = (row_i + modifier_row_i, col_i + modifier_col_i);
+                if !self.is_traversable(next.0, next.1, transport_type) {
+                    continue;
+                }
+
+                let cost = if modifier_row_i != 0 && modifier_col_i != 0 {
+                    if !self.is_traversable(row_i, next.1, transport_type)
+                        || !self.is_traversable(next.0, col_i, transport_type)
+                    {
+                        continue;
+                    }
+                    DIAGONAL_MOVE_COST
+                } else {
+                    STRAIGHT_MOVE_COST
+                };
+                successors.push((next, cost));
+            }
+        }
+
+        successors
+    }
+
+    /// Cheapest path (excluding `from`) to go to `to` with given transport type
+    pub fn find_path(
+        &self,
+        from: (i16, i16),
+        to: (i16, i16),
+        transport_type: TransportType,
+    ) -> Option<Vec<(i16, i16)>> {
+        let (path, _) = astar(
+            &from,
+            |position| self.successors(*position, transport_type),
+            |(row_i, col_i)| {
+                let row_distance = absdiff(*row_i, to.0) as u32;
+                let col_distance = absdiff(*col_i, to.1) as u32;
+                let diagonal_distance = row_distance.min(col_distance);
+                let straight_distance = row_distance.max(col_distance) - diagonal_distance;
+                diagonal_distance * DIAGONAL_MOVE_COST + straight_distance * STRAIGHT_MOVE_COST
+            },
+            |position| *position == to,
+        )?;
+
+        Some(path.into_iter().skip(1).collect())
+    }
+}
+
+/// State of "player" zone without entities, for tests. Level can use GRASS (`⡩`, walkable),
+/// SEA (`~`, swimmable) and ROCK (`#`, blocked) tiles.
+#[cfg(test)]
+pub fn test_state(level: &str) -> ZoneState {
+    let tiles = Tiles::new(serde_json::json!([
+        {"id": "GRASS", "char": "⡩", "traversable": {"WALKING": true}},
+        {"id": "SEA", "char": "~", "traversable": {"WALKING": false, "SWIMMING": true}},
+        {"id": "ROCK", "char": "#", "traversable": {"WALKING": false}},
+    ]));
+    let level = Level::new(level, &tiles, "PLAIN".to_string()).unwrap();
+    ZoneState::new("player".to_string(), tiles, level)
+}
+
+/// Zone state of one ground line with character "bob" and heavy stuff 7 "STONE" at (0, 1)
+#[cfg(test)]
+pub fn test_state_with_entities() -> ZoneState {
+    let mut characters = HashMap::new();
+    characters.insert(
+        "bob".to_string(),
+        Character {
+            id: "bob".to_string(),
+            zone_row_i: 0,
+            zone_col_i: 1,
+            avatar_uuid: None,
+            avatar_is_validated: false,
+        },
+    );
+    let mut stuffs = HashMap::new();
+    stuffs.insert(
+        "7".to_string(),
+        Stuff {
+            id: 7,
+            stuff_id: "STONE".to_string(),
+            zone_row_i: 0,
+            zone_col_i: 1,
+            classes: vec!["HEAVY".to_string()],
+        },
+    );
+    test_state("⡩⡩⡩⡩")
+        .with_characters(characters)
+        .with_stuffs(stuffs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn event(value: serde_json::Value) -> ZoneEvent {
+        ZoneEvent::from_value(value).unwrap()
+    }
+
+    fn state() -> ZoneState {
+        test_state("⡩⡩⡩\n⡩~⡩")
+    }
+
+    #[test]
+    fn test_apply_entities_events() {
+        let mut state = state();
+
+        let applied = state.apply(&event(json!({
+            "type": "CHARACTER_ENTER_ZONE",
+            "data": {"zone_row_i": 0, "zone_col_i": 1, "character_id": "bob"}
+        })));
+        state.apply(&event(json!({
+            "type": "ZONE_GROUND_STUFF_APPEAR",
+            "data": {"id": 7, "stuff_id": "STONE", "zone_row_i": 0, "zone_col_i": 1, "classes": []}
+        })));
+
+        assert_eq!(applied, Applied::Changed);
+        assert_eq!(state.entities_at(0, 1).characters.len(), 1);
+        assert_eq!(state.entities_at(0, 1).stuffs.len(), 1);
+        assert!(state.entities_at(0, 0).is_empty());
+
+        state.apply(&event(json!({
+            "type": "PLAYER_MOVE",
+            "data": {"to_row_i": 1, "to_col_i": 2, "character_id": "bob"}
+        })));
+        state.apply(&event(
+            json!({"type": "ZONE_GROUND_STUFF_REMOVE", "data": {"stuff_id": 7}}),
+        ));
+
+        assert!(state.entities_at(0, 1).is_empty());
+        assert_eq!(state.entities_at(1, 2).characters[0].id, "bob");
+        assert_eq!(
+            state.apply(&event(json!({
+                "type": "CHARACTER_EXIT_ZONE",
+                "data": {"character_id": "player"}
+            }))),
+            Applied::PlayerLeft
+        );
+        assert_eq!(
+            state.apply(&event(json!({
+                "type": "TOP_BAR_MESSAGE",
+                "data": {"message": "hello", "type_": "NORMAL"}
+            }))),
+            Applied::Ignored
+        );
+    }
+
+    #[test]
+    fn test_traversable() {
+        let mut state = state();
+        let new_build = |id: i32| {
+            event(json!({
+                "type": "NEW_BUILD",
+                "data": {"build": {
+                    "id": id, "build_id": "WALL", "row_i": 0, "col_i": 2, "classes": [],
+                    "traversable": {"WALKING": false}, "is_floor": false
+                }}
+            }))
+        };
+
+        assert!(state.is_traversable(0, 2, TransportType::Walking));
+        assert!(!state.is_traversable(1, 1, TransportType::Walking));
+        assert!(state.is_traversable(1, 1, TransportType::Swimming));
+        assert!(!state.is_traversable(5, 5, TransportType::Walking));
+
+        state.apply(&new_build(1));
+        state.apply(&new_build(1));
+        state.apply(&new_build(2));
+
+        assert!(!state.is_traversable(0, 2, TransportType::Walking));
+        assert!(!state.is_traversable(0, 2, TransportType::Swimming));
+        assert_eq!(state.builds_at(0, 2).len(), 2);
+
+        state.apply(&event(json!({
+            "type": "ZONE_TILE_REPLACE",
+            "data": {"zone_row_i": 1, "zone_col_i": 1, "new_tile_id": "GRASS"}
+        })));
+
+        assert!(state.is_traversable(1, 1, TransportType::Walking));
+    }
+
+    #[test]
+    fn test_find_path() {
+        let state = test_state("⡩~⡩\n⡩~⡩\n⡩⡩⡩");
+
+        // Walking go around sea, without cutting its corner
+        assert_eq!(
+            state.find_path((0, 0), (0, 2), TransportType::Walking),
+            Some(vec![(1, 0), (2, 0), (2, 1), (2, 2), (1, 2), (0, 2)])
+        );
+        assert_eq!(
+            state.find_path((0, 1), (1, 1), TransportType::Swimming),
+            Some(vec![(1, 1)])
+        );
+        assert_eq!(
+            state.find_path((0, 1), (2, 1), TransportType::Swimming),
+            None
+        );
+    }
+}
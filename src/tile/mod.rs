@@ -1,4 +1,26 @@
+use serde::{Deserialize, Serialize};
+
 pub mod world;
 pub mod zone;
 
 pub type TileId = String;
+
+/// How a character moves. Its name is the key of tiles and builds `traversable` maps.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TransportType {
+    #[serde(rename = "SWIMMING")]
+    Swimming,
+    // Unknown transport types (newer server) are handled as walking
+    #[serde(rename = "WALKING", other)]
+    #[default]
+    Walking,
+}
+
+impl TransportType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransportType::Walking => "WALKING",
+            TransportType::Swimming => "SWIMMING",
+        }
+    }
+}
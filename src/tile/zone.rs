@@ -1,12 +1,13 @@
 use serde_json::Value;
 use std::collections::HashMap;
 
-use crate::tile::TileId;
+use crate::tile::{TileId, TransportType};
 
 #[derive(Debug)]
 pub struct Tiles {
     codes: HashMap<u16, TileId>,
-    browseables: HashMap<TileId, bool>,
+    /// Traversability of tiles by transport type name
+    traversables: HashMap<TileId, HashMap<String, bool>>,
 }
 
 pub const NOTHING: &str = "NOTHING";
@@ -15,24 +16,28 @@ pub const UNKNOWN: &str = "UNKNOWN";
 impl Tiles {
     pub fn new(data: Value) -> Self {
         let mut codes = HashMap::new();
-        let mut browseables = HashMap::new();
+        let mut traversables = HashMap::new();
 
         for tile_value in data.as_array().unwrap() {
             let tile_id: &str = tile_value["id"].as_str().unwrap();
             let char: u16 = tile_value["char"].as_str().unwrap().chars().nth(0).unwrap() as u16;
 
             codes.insert(char, tile_id.to_string());
-            // TODO evolve browseables schema (WALKING, etc)
             if let Some(traversable) = tile_value["traversable"].as_object() {
-                if let Some(walking) = traversable.get("WALKING") {
-                    if let Some(can_walk) = walking.as_bool() {
-                        browseables.insert(tile_id.to_string(), can_walk);
-                    }
-                }
+                let traversable: HashMap<String, bool> = traversable
+                    .iter()
+                    .filter_map(|(transport_type, can_traverse)| {
+                        Some((transport_type.clone(), can_traverse.as_bool()?))
+                    })
+                    .collect();
+                traversables.insert(tile_id.to_string(), traversable);
             }
         }
 
-        Tiles { codes, browseables }
+        Tiles {
+            codes,
+            traversables,
+        }
     }
 
     pub fn tile_id(&self, code: u16) -> String {
@@ -42,10 +47,12 @@ impl Tiles {
         String::from(UNKNOWN)
     }
 
-    pub fn browseable(&self, tile_id: &str) -> bool {
-        if let Some(browseable) = self.browseables.get(tile_id) {
-            return *browseable;
-        }
-        false
+    /// Tiles without traversability information for this transport type can't be traversed
+    pub fn traversable(&self, tile_id: &str, transport_type: TransportType) -> bool {
+        self.traversables
+            .get(tile_id)
+            .and_then(|traversable| traversable.get(transport_type.as_str()))
+            .cloned()
+            .unwrap_or(false)
     }
 }
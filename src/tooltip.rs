@@ -0,0 +1,113 @@
+use crate::entity::build::Build;
+use crate::entity::character::Character;
+use crate::entity::corpse::AnimatedCorpse;
+use crate::entity::resource::Resource;
+use crate::entity::stuff::Stuff;
+use crate::state::ZoneState;
+
+/// Hovered tile description is displayed after this delay
+pub const TOOLTIP_DELAY_MS: u64 = 300;
+/// Hovered tile description is expanded to all tile entities after this delay
+pub const TOOLTIP_EXPAND_DELAY_MS: u64 = 1500;
+
+pub fn character_label(character: &Character, player_id: &str) -> String {
+    if character.id == player_id {
+        "Vous".to_string()
+    } else {
+        format!("Personnage : {}", character.id)
+    }
+}
+
+pub fn animated_corpse_label(animated_corpse: &AnimatedCorpse) -> String {
+    format!("Créature : {}", animated_corpse.type_)
+}
+
+pub fn build_label(build: &Build) -> String {
+    format!("Construction : {}", build.build_id)
+}
+
+pub fn resource_label(resource: &Resource) -> String {
+    format!("Ressource : {}", resource.id)
+}
+
+pub fn stuff_label(stuff: &Stuff) -> String {
+    if stuff.classes.is_empty() {
+        format!("Objet : {}", stuff.stuff_id)
+    } else {
+        format!("Objet : {} ({})", stuff.stuff_id, stuff.classes.join(", "))
+    }
+}
+
+pub fn tile_label(state: &ZoneState, tile: (i16, i16)) -> String {
+    format!("Terrain : {}", state.level.tile_id(tile.0, tile.1))
+}
+
+/// Description lines of zone tile: its entities (characters first, then animated corpses,
+/// builds, resources and stuffs) then tile itself. When not expanded, only first line is given
+/// with count of other ones.
+pub fn tooltip_lines(
+    state: &ZoneState,
+    player_id: &str,
+    tile: (i16, i16),
+    expanded: bool,
+) -> Vec<String> {
+    let (row_i, col_i) = tile;
+    let entities = state.entities_at(row_i, col_i);
+    let mut lines = vec![];
+
+    for character in entities.characters.iter() {
+        lines.push(character_label(character, player_id));
+    }
+    for animated_corpse in entities.animated_corpses.iter() {
+        lines.push(animated_corpse_label(animated_corpse));
+    }
+    for build in entities.builds.iter() {
+        lines.push(build_label(build));
+    }
+    for resource in entities.resources.iter() {
+        lines.push(resource_label(resource));
+    }
+    for stuff in entities.stuffs.iter() {
+        lines.push(stuff_label(stuff));
+    }
+    lines.push(tile_label(state, tile));
+
+    if !expanded && lines.len() > 1 {
+        let others = lines.len() - 1;
+        lines.truncate(1);
+        lines.push(format!("+{} (Alt pour tout afficher)", others));
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::state::test_state_with_entities;
+
+    #[test]
+    fn test_tooltip_lines() {
+        let state = test_state_with_entities();
+
+        assert_eq!(
+            vec!["Terrain : GRASS".to_string()],
+            tooltip_lines(&state, "player", (0, 0), false)
+        );
+        assert_eq!(
+            vec![
+                "Personnage : bob".to_string(),
+                "+2 (Alt pour tout afficher)".to_string()
+            ],
+            tooltip_lines(&state, "player", (0, 1), false)
+        );
+        assert_eq!(
+            vec![
+                "Personnage : bob".to_string(),
+                "Objet : STONE (HEAVY)".to_string(),
+                "Terrain : GRASS".to_string()
+            ],
+            tooltip_lines(&state, "player", (0, 1), true)
+        );
+    }
+}